@@ -27,8 +27,10 @@ PKG_FILES := \
       driver                                   \
       libcargo                                 \
       librusti                                 \
+      librefactor                              \
       librustc                                 \
       compiletest                              \
+      rust-demangle                            \
       etc                                      \
       libfuzzer                                \
       libcore                                  \
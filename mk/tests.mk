@@ -14,7 +14,7 @@
 ######################################################################
 
 # The names of crates that must be tested
-TEST_CRATES = core std syntax rustc rustdoc rusti cargo
+TEST_CRATES = core std syntax rustc rustdoc rusti refactor cargo
 
 # Markdown files under doc/ that should have their code extracted and run
 DOC_TEST_NAMES = tutorial tutorial-ffi tutorial-macros tutorial-borrowed-ptr tutorial-tasks rust
@@ -171,6 +171,8 @@ check-stage$(1)-T-$(2)-H-$(3)-exec:     				\
         check-stage$(1)-T-$(2)-H-$(3)-crates-exec                      \
 	check-stage$(1)-T-$(2)-H-$(3)-bench-exec			\
 	check-stage$(1)-T-$(2)-H-$(3)-doc-exec \
+	$$(if $$(CFG_GDB),check-stage$(1)-T-$(2)-H-$(3)-debuginfo-exec) \
+	check-stage$(1)-T-$(2)-H-$(3)-rmake-exec			\
 	check-stage$(1)-T-$(2)-H-$(3)-pretty-exec
 
 check-stage$(1)-T-$(2)-H-$(3)-crates-exec: \
@@ -239,6 +241,12 @@ $(3)/test/rustitest.stage$(1)-$(2)$$(X):					\
 	@$$(call E, compile_and_link: $$@)
 	$$(STAGE$(1)_T_$(2)_H_$(3)) -o $$@ $$< --test
 
+$(3)/test/refactortest.stage$(1)-$(2)$$(X):					\
+		$$(REFACTOR_LIB) $$(REFACTOR_INPUTS)		\
+		$$(TLIB$(1)_T_$(2)_H_$(3))/$$(CFG_LIBRUSTC)
+	@$$(call E, compile_and_link: $$@)
+	$$(STAGE$(1)_T_$(2)_H_$(3)) -o $$@ $$< --test
+
 $(3)/test/rustdoctest.stage$(1)-$(2)$$(X):					\
 		$$(RUSTDOC_LIB) $$(RUSTDOC_INPUTS)		\
 		$$(TLIB$(1)_T_$(2)_H_$(3))/$$(CFG_LIBRUSTC)
@@ -284,6 +292,7 @@ CFAIL_RC := $(wildcard $(S)src/test/compile-fail/*.rc)
 CFAIL_RS := $(wildcard $(S)src/test/compile-fail/*.rs)
 BENCH_RS := $(wildcard $(S)src/test/bench/*.rs)
 PRETTY_RS := $(wildcard $(S)src/test/pretty/*.rs)
+DEBUGINFO_RS := $(wildcard $(S)src/test/debug-info/*.rs)
 
 # perf tests are the same as bench tests only they run under
 # a performance monitor.
@@ -296,6 +305,7 @@ CFAIL_TESTS := $(CFAIL_RC) $(CFAIL_RS)
 BENCH_TESTS := $(BENCH_RS)
 PERF_TESTS := $(PERF_RS)
 PRETTY_TESTS := $(PRETTY_RS)
+DEBUGINFO_TESTS := $(DEBUGINFO_RS)
 
 CTEST_SRC_BASE_rpass = run-pass
 CTEST_BUILD_BASE_rpass = run-pass
@@ -327,6 +337,11 @@ CTEST_BUILD_BASE_perf = perf
 CTEST_MODE_perf = run-pass
 CTEST_RUNTOOL_perf = $(CTEST_PERF_RUNTOOL)
 
+CTEST_SRC_BASE_debuginfo = debug-info
+CTEST_BUILD_BASE_debuginfo = debug-info
+CTEST_MODE_debuginfo = debug-info
+CTEST_RUNTOOL_debuginfo = $(CTEST_RUNTOOL)
+
 define DEF_CTEST_VARS
 
 # All the per-stage build rules you might want to call from the
@@ -358,6 +373,7 @@ CTEST_DEPS_rfail_$(1)-T-$(2)-H-$(3) = $$(RFAIL_TESTS)
 CTEST_DEPS_cfail_$(1)-T-$(2)-H-$(3) = $$(CFAIL_TESTS)
 CTEST_DEPS_bench_$(1)-T-$(2)-H-$(3) = $$(BENCH_TESTS)
 CTEST_DEPS_perf_$(1)-T-$(2)-H-$(3) = $$(PERF_TESTS)
+CTEST_DEPS_debuginfo_$(1)-T-$(2)-H-$(3) = $$(DEBUGINFO_TESTS)
 
 endef
 
@@ -388,7 +404,7 @@ $$(call TEST_OK_FILE,$(1),$(2),$(3),$(4)): \
 
 endef
 
-CTEST_NAMES = rpass rpass-full rfail cfail bench perf
+CTEST_NAMES = rpass rpass-full rfail cfail bench perf debuginfo
 
 $(foreach host,$(CFG_TARGET_TRIPLES), \
  $(eval $(foreach target,$(CFG_TARGET_TRIPLES), \
@@ -483,6 +499,45 @@ $(foreach host,$(CFG_TARGET_TRIPLES), \
   $(eval $(call DEF_DOC_TEST_HOST,$(host),$(docname)))))
 
 
+######################################################################
+# Run-make tests
+######################################################################
+
+# Each directory of src/test/run-make is a test whose Makefile drives
+# rustc itself, for what a single compiletest run can't check
+
+RMAKE_TESTS := $(patsubst $(S)src/test/run-make/%/,%, \
+                 $(wildcard $(S)src/test/run-make/*/))
+
+define DEF_RMAKE_FOR_T_H
+
+check-stage$(1)-T-$(2)-H-$(3)-rmake-exec: \
+        $$(foreach test,$$(RMAKE_TESTS), \
+           $(3)/test/run-make/$$(test)-$(1)-T-$(2)-H-$(3).ok)
+
+$(3)/test/run-make/%-$(1)-T-$(2)-H-$(3).ok: \
+		$$(S)src/test/run-make/%/Makefile \
+		$$(S)src/test/run-make/tools.mk \
+		$$(HBIN$(1)_H_$(3))/rustc$$(X) \
+		$$(SREQ$(1)_T_$(2)_H_$(3))
+	@$$(call E, run-make: $$*)
+	@rm -rf $(3)/test/run-make/$$*
+	@mkdir -p $(3)/test/run-make/$$*
+	$$(Q)$$(CFG_PYTHON) $$(S)src/etc/maketest.py \
+		$$(S)src/test/run-make/$$* \
+		$$(HBIN$(1)_H_$(3))/rustc$$(X) \
+		$(3)/test/run-make/$$* \
+		$$(TLIB$(1)_T_$(2)_H_$(3)) \
+		&& touch $$@
+
+endef
+
+$(foreach host,$(CFG_TARGET_TRIPLES), \
+ $(foreach target,$(CFG_TARGET_TRIPLES), \
+  $(foreach stage,$(STAGES), \
+   $(eval $(call DEF_RMAKE_FOR_T_H,$(stage),$(target),$(host))))))
+
+
 ######################################################################
 # Shortcut rules
 ######################################################################
@@ -496,6 +551,8 @@ TEST_GROUPS = \
 	cfail \
 	bench \
 	perf \
+	debuginfo \
+	rmake \
 	doc \
 	$(foreach docname,$(DOC_TEST_NAMES),$(docname)) \
 	pretty \
@@ -30,6 +30,14 @@ RUSTDOC_INPUTS := $(wildcard $(S)src/librustdoc/*.rs)
 RUSTI_LIB := $(S)src/librusti/rusti.rc
 RUSTI_INPUTS := $(wildcard $(S)src/librusti/*.rs)
 
+# The source-to-source refactoring tool
+REFACTOR_LIB := $(S)src/librefactor/refactor.rc
+REFACTOR_INPUTS := $(wildcard $(S)src/librefactor/*.rs)
+
+# The filter that demangles symbol names
+DEMANGLE_CRATE := $(S)src/rust-demangle/rust-demangle.rc
+DEMANGLE_INPUTS := $(wildcard $(S)src/rust-demangle/*.rs)
+
 # FIXME: These are only built for the host arch. Eventually we'll
 # have tools that need to built for other targets.
 define TOOLS_STAGE_N_TARGET
@@ -57,6 +65,15 @@ $$(TBIN$(1)_T_$(4)_H_$(3))/compiletest$$(X):			\
 	@$$(call E, compile_and_link: $$@)
 	$$(STAGE$(1)_T_$(4)_H_$(3)) -o $$@ $$<
 
+$$(TBIN$(1)_T_$(4)_H_$(3))/rust-demangle$$(X):		\
+		$$(DEMANGLE_CRATE) $$(DEMANGLE_INPUTS)		\
+		$$(TSREQ$(1)_T_$(4)_H_$(3))					\
+		$$(TLIB$(1)_T_$(4)_H_$(3))/$$(CFG_CORELIB)	\
+		$$(TLIB$(1)_T_$(4)_H_$(3))/$$(CFG_STDLIB)	\
+		$$(TLIB$(1)_T_$(4)_H_$(3))/$$(CFG_LIBRUSTC)
+	@$$(call E, compile_and_link: $$@)
+	$$(STAGE$(1)_T_$(4)_H_$(3)) -o $$@ $$<
+
 $$(TLIB$(1)_T_$(4)_H_$(3))/$$(CFG_LIBCARGO):		\
 		$$(CARGO_LIB) $$(CARGO_INPUTS)				\
 		$$(TSREQ$(1)_T_$(4)_H_$(3))					\
@@ -102,6 +119,21 @@ $$(TBIN$(1)_T_$(4)_H_$(3))/rusti$$(X):			\
 	@$$(call E, compile_and_link: $$@)
 	$$(STAGE$(1)_T_$(4)_H_$(3)) --cfg rusti -o $$@ $$<
 
+$$(TLIB$(1)_T_$(4)_H_$(3))/$$(CFG_LIBREFACTOR):		\
+		$$(REFACTOR_LIB) $$(REFACTOR_INPUTS)			\
+		$$(TSREQ$(1)_T_$(4)_H_$(3))					\
+		$$(TLIB$(1)_T_$(4)_H_$(3))/$$(CFG_CORELIB)	\
+		$$(TLIB$(1)_T_$(4)_H_$(3))/$$(CFG_STDLIB)	\
+		$$(TLIB$(1)_T_$(4)_H_$(3))/$$(CFG_LIBRUSTC)
+	@$$(call E, compile_and_link: $$@)
+	$$(STAGE$(1)_T_$(4)_H_$(3)) -o $$@ $$< && touch $$@
+
+$$(TBIN$(1)_T_$(4)_H_$(3))/refactor$$(X):			\
+		$$(DRIVER_CRATE) 							\
+		$$(TLIB$(1)_T_$(4)_H_$(3))/$$(CFG_LIBREFACTOR)
+	@$$(call E, compile_and_link: $$@)
+	$$(STAGE$(1)_T_$(4)_H_$(3)) --cfg refactor -o $$@ $$<
+
 endef
 
 define TOOLS_STAGE_N_HOST
@@ -133,6 +165,13 @@ $$(HBIN$(2)_H_$(4))/compiletest$$(X):				\
 	@$$(call E, cp: $$@)
 	$$(Q)cp $$< $$@
 
+$$(HBIN$(2)_H_$(4))/rust-demangle$$(X):				\
+		$$(TBIN$(1)_T_$(4)_H_$(3))/rust-demangle$$(X)	\
+		$$(HLIB$(2)_H_$(4))/$$(CFG_LIBRUSTC)		\
+		$$(HSREQ$(2)_H_$(4))
+	@$$(call E, cp: $$@)
+	$$(Q)cp $$< $$@
+
 
 $$(HLIB$(2)_H_$(4))/$$(CFG_LIBCARGO):				\
 		$$(TLIB$(1)_T_$(4)_H_$(3))/$$(CFG_LIBCARGO)	\
@@ -185,6 +224,23 @@ $$(HBIN$(2)_H_$(4))/rusti$$(X):				\
 	@$$(call E, cp: $$@)
 	$$(Q)cp $$< $$@
 
+$$(HLIB$(2)_H_$(4))/$$(CFG_LIBREFACTOR):					\
+		$$(TLIB$(1)_T_$(4)_H_$(3))/$$(CFG_LIBREFACTOR)	\
+		$$(HLIB$(2)_H_$(4))/$$(CFG_LIBRUSTC)			\
+		$$(HSREQ$(2)_H_$(4))
+	@$$(call E, cp: $$@)
+	$$(Q)cp $$< $$@
+	$$(Q)cp -R $$(TLIB$(1)_T_$(4)_H_$(3))/$(LIBREFACTOR_GLOB) \
+		$$(wildcard $$(TLIB$(1)_T_$(4)_H_$(3))/$(LIBREFACTOR_DSYM_GLOB)) \
+	        $$(HLIB$(2)_H_$(4))
+
+$$(HBIN$(2)_H_$(4))/refactor$$(X):				\
+		$$(TBIN$(1)_T_$(4)_H_$(3))/refactor$$(X)	\
+		$$(HLIB$(2)_H_$(4))/$$(CFG_LIBREFACTOR)	\
+		$$(HSREQ$(2)_H_$(4))
+	@$$(call E, cp: $$@)
+	$$(Q)cp $$< $$@
+
 endef
 
 $(foreach host,$(CFG_TARGET_TRIPLES),				\
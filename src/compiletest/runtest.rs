@@ -22,6 +22,7 @@ use common::mode_run_pass;
 use common::mode_run_fail;
 use common::mode_compile_fail;
 use common::mode_pretty;
+use common::mode_debug_info;
 use common::config;
 use errors;
 use header;
@@ -43,7 +44,8 @@ pub fn run(config: config, testfile: ~str) {
       mode_compile_fail => run_cfail_test(config, props, &testfile),
       mode_run_fail => run_rfail_test(config, props, &testfile),
       mode_run_pass => run_rpass_test(config, props, &testfile),
-      mode_pretty => run_pretty_test(config, props, &testfile)
+      mode_pretty => run_pretty_test(config, props, &testfile),
+      mode_debug_info => run_debuginfo_test(config, props, &testfile)
     }
 }
 
@@ -224,6 +226,53 @@ actual:\n\
     }
 }
 
+fn run_debuginfo_test(config: config, props: TestProps, testfile: &Path) {
+    // compile test file (it should have 'compile-flags:-g' in the header)
+    let mut ProcRes = compile_test(config, props, testfile);
+    if ProcRes.status != 0 {
+        fatal_ProcRes(~"compilation failed!", ProcRes);
+    }
+
+    // write debugger script
+    let script_str = str::append(str::connect(props.debugger_cmds, "\n"),
+                                 ~"\nquit\n");
+    debug!("script_str = %s", script_str);
+    dump_output_file(config, testfile, script_str, ~"debugger.script");
+
+    // run debugger script with gdb
+    let debugger_script = make_out_name(config, testfile,
+                                        ~"debugger.script");
+    let debugger_opts = ~[~"-quiet", ~"-batch", ~"-nx",
+                          ~"-command=" + debugger_script.to_str(),
+                          make_exe_name(config, testfile).to_str()];
+    let ProcArgs = ProcArgs {prog: ~"gdb", args: debugger_opts};
+    ProcRes = compose_and_run(config, testfile, ProcArgs, ~[],
+                              config.run_lib_path, None);
+    if ProcRes.status != 0 {
+        fatal_ProcRes(~"gdb failed to execute", ProcRes);
+    }
+
+    let num_check_lines = vec::len(props.check_lines);
+    if num_check_lines > 0 {
+        // check if each line in props.check_lines appears in the
+        // output (in order)
+        let mut i = 0u;
+        for str::split_char(ProcRes.stdout, '\n').each |line| {
+            if props.check_lines[i].trim() == line.trim() {
+                i += 1u;
+            }
+            if i == num_check_lines {
+                // all lines checked
+                break;
+            }
+        }
+        if i != num_check_lines {
+            fatal_ProcRes(fmt!("line not found in debugger output: %s",
+                               props.check_lines[i]), ProcRes);
+        }
+    }
+}
+
 fn check_error_patterns(props: TestProps,
                         testfile: &Path,
                         ProcRes: ProcRes) {
@@ -288,15 +337,22 @@ fn check_expected_errors(expected_errors: ~[errors::ExpectedError],
     //    filename:line1:col1: line2:col2: *warning:* msg
     // where line1:col1: is the starting point, line2:col2:
     // is the ending point, and * represents ANSI color codes.
+    // Span labels are matched as notes; see `label_as_note`.
+    let mut snippet_line = None;
     for str::split_char(ProcRes.stderr, '\n').each |line| {
+        let line = match label_as_note(copy *line, testfile,
+                                       &mut snippet_line) {
+          Some(move note) => note,
+          None => copy *line
+        };
         let mut was_expected = false;
         for vec::eachi(expected_errors) |i, ee| {
             if !found_flags[i] {
                 debug!("prefix=%s ee.kind=%s ee.msg=%s line=%s",
-                       prefixes[i], ee.kind, ee.msg, *line);
-                if (str::starts_with(*line, prefixes[i]) &&
-                    str::contains(*line, ee.kind) &&
-                    str::contains(*line, ee.msg)) {
+                       prefixes[i], ee.kind, ee.msg, line);
+                if (str::starts_with(line, prefixes[i]) &&
+                    str::contains(line, ee.kind) &&
+                    str::contains(line, ee.msg)) {
                     found_flags[i] = true;
                     was_expected = true;
                     break;
@@ -305,13 +361,13 @@ fn check_expected_errors(expected_errors: ~[errors::ExpectedError],
         }
 
         // ignore this msg which gets printed at the end
-        if str::contains(*line, ~"aborting due to") {
+        if str::contains(line, ~"aborting due to") {
             was_expected = true;
         }
 
-        if !was_expected && is_compiler_error_or_warning(*line) {
+        if !was_expected && is_compiler_error_or_warning(copy line) {
             fatal_ProcRes(fmt!("unexpected compiler error or warning: '%s'",
-                               *line),
+                               line),
                           ProcRes);
         }
     }
@@ -325,6 +381,42 @@ fn check_expected_errors(expected_errors: ~[errors::ExpectedError],
     }
 }
 
+// The compiler prints the source lines that a diagnostic points at, each
+// followed by the labels of the spans that start on it:
+//    filename:line <source>
+//                  ---- label
+// Returns such a label as a note on its line, `filename:line: note: label`,
+// so that `//~ NOTE` can expect it. `snippet_line` is the line of the last
+// source line printed.
+fn label_as_note(line: ~str, testfile: &Path,
+                 snippet_line: &mut Option<uint>) -> Option<~str> {
+    let file = testfile.to_str();
+    let mut i = 0u;
+    if scan_string(copy line, copy file, &mut i) &&
+        scan_char(copy line, ':', &mut i) {
+        let start = i;
+        *snippet_line = if scan_integer(copy line, &mut i) &&
+            scan_char(copy line, ' ', &mut i) {
+            uint::from_str(str::slice(line, start, i - 1u))
+        } else {
+            None
+        };
+        return None;
+    }
+    let n = match *snippet_line {
+      Some(n) => n,
+      None => return None
+    };
+    let marks = str::trim_left(line);
+    match str::find(marks, |c| c != '^' && c != '~' && c != '-') {
+      Some(i) if i > 0u => {
+        Some(fmt!("%s:%u: note:%s", file, n,
+                  str::slice(marks, i, marks.len())))
+      }
+      _ => None
+    }
+}
+
 fn is_compiler_error_or_warning(line: ~str) -> bool {
     let mut i = 0u;
     return
@@ -26,4 +26,7 @@ extern mod this(name = "rusti", vers = "0.6");
 #[cfg(rustc)]
 extern mod this(name = "rustc", vers = "0.6");
 
+#[cfg(refactor)]
+extern mod this(name = "refactor", vers = "0.6");
+
 fn main() { this::main() }
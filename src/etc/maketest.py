@@ -0,0 +1,33 @@
+#!/usr/bin/env python
+# xfail-license
+
+# Runs the Makefile of a run-make test. The test can use $(RUSTC), the
+# compiler under test, $(TMPDIR), a fresh directory for what it builds,
+# $(TARGET_LIB_DIR), where the target's libraries are, and $(PYTHON), the
+# interpreter running this script.
+#
+# Usage: maketest.py <test dir> <rustc> <tmp dir> <target lib dir>
+
+import subprocess
+import os
+import sys
+
+os.putenv('RUSTC', os.path.abspath(sys.argv[2]))
+os.putenv('TMPDIR', os.path.abspath(sys.argv[3]))
+os.putenv('TARGET_LIB_DIR', os.path.abspath(sys.argv[4]))
+os.putenv('PYTHON', sys.executable)
+
+proc = subprocess.Popen(['make', '-C', sys.argv[1]],
+                        stdout=subprocess.PIPE,
+                        stderr=subprocess.PIPE)
+out, err = proc.communicate()
+status = proc.wait()
+
+if status != 0:
+    print('----- ' + sys.argv[1] + ' failed -----')
+    print('------ stdout ------------------------------------------')
+    print(out)
+    print('------ stderr ------------------------------------------')
+    print(err)
+    print('--------------------------------------------------------')
+    sys.exit(status)
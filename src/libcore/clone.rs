@@ -19,3 +19,89 @@ impl (): Clone {
     #[inline(always)]
     fn clone(&self) -> () { () }
 }
+
+impl bool: Clone {
+    #[inline(always)]
+    fn clone(&self) -> bool { *self }
+}
+
+impl char: Clone {
+    #[inline(always)]
+    fn clone(&self) -> char { *self }
+}
+
+impl int: Clone {
+    #[inline(always)]
+    fn clone(&self) -> int { *self }
+}
+
+impl i8: Clone {
+    #[inline(always)]
+    fn clone(&self) -> i8 { *self }
+}
+
+impl i16: Clone {
+    #[inline(always)]
+    fn clone(&self) -> i16 { *self }
+}
+
+impl i32: Clone {
+    #[inline(always)]
+    fn clone(&self) -> i32 { *self }
+}
+
+impl i64: Clone {
+    #[inline(always)]
+    fn clone(&self) -> i64 { *self }
+}
+
+impl uint: Clone {
+    #[inline(always)]
+    fn clone(&self) -> uint { *self }
+}
+
+impl u8: Clone {
+    #[inline(always)]
+    fn clone(&self) -> u8 { *self }
+}
+
+impl u16: Clone {
+    #[inline(always)]
+    fn clone(&self) -> u16 { *self }
+}
+
+impl u32: Clone {
+    #[inline(always)]
+    fn clone(&self) -> u32 { *self }
+}
+
+impl u64: Clone {
+    #[inline(always)]
+    fn clone(&self) -> u64 { *self }
+}
+
+impl float: Clone {
+    #[inline(always)]
+    fn clone(&self) -> float { *self }
+}
+
+impl f32: Clone {
+    #[inline(always)]
+    fn clone(&self) -> f32 { *self }
+}
+
+impl f64: Clone {
+    #[inline(always)]
+    fn clone(&self) -> f64 { *self }
+}
+
+impl ~str: Clone {
+    #[inline(always)]
+    fn clone(&self) -> ~str { copy *self }
+}
+
+impl<T> @T: Clone {
+    /// Shares the box rather than copying its contents
+    #[inline(always)]
+    fn clone(&self) -> @T { *self }
+}
@@ -0,0 +1,611 @@
+// Copyright 2013 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Support for the `format!` and `write!` syntax extensions.
+//!
+//! Unlike `fmt!`, arguments are referred to by position or by name, every
+//! conversion goes through a trait, and the output is written straight
+//! into an `io::Writer`:
+//!
+//! ~~~~~~
+//! write!(io::stdout(), "{0} is {age:>3u} years old\n", name, age = 30u);
+//! let s = format!("{:.2f}", 3.14159);
+//! ~~~~~~
+//!
+//! The syntax of a format string is:
+//!
+//! ~~~~~~
+//! Format := '{' Argument? (':' Spec)? '}'
+//! Argument := [0-9]+ | Identifier
+//! Spec := (Fill? Align)? Width? ('.' Precision)? Type?
+//! Fill := any character other than '}'
+//! Align := '<' | '>' | '^'
+//! Width, Precision := [0-9]+
+//! Type := [bcdfiostuxX?]
+//! ~~~~~~
+//!
+//! `{{` and `}}` stand for literal braces.
+//!
+//! * An argument given by position counts from 0. An omitted argument is
+//! the one after the previous omitted argument.
+//! * A named argument refers to an argument given as `name = expr` in the
+//! call, after all positional ones.
+//! * Fill, align and width pad the result to a minimum width. Numbers are
+//! aligned right and everything else left unless an alignment is given.
+//! A `0` fill with a number goes between the sign and the digits.
+//! * Precision is the maximum number of characters shown for strings, the
+//! minimum number of digits for integers and the number of decimal places
+//! for floats.
+//!
+//! The type selects the trait that formats the argument:
+//!
+//! * none - `ToStr`
+//! * b - `Bool`
+//! * c - `Char`
+//! * d, i - `Signed`
+//! * f - `Float`
+//! * o - `Octal`
+//! * s - `Str`
+//! * t - `Binary`
+//! * u - `Unsigned`
+//! * x - `LowerHex`
+//! * X - `UpperHex`
+//! * ? - any type, printed as by `%?`
+//!
+//! The numeric types implement their traits in their own modules.
+
+use io;
+use io::WriterUtil;
+use prelude::*;
+use str;
+use to_str::ToStr;
+use uint;
+
+#[deriving_eq]
+pub enum Alignment {
+    AlignLeft,
+    AlignRight,
+    AlignCenter,
+    /// Left for strings, right for numbers
+    AlignDefault,
+}
+
+/// How a single argument is laid out
+#[deriving_eq]
+pub struct Spec {
+    fill: char,
+    align: Alignment,
+    width: Option<uint>,
+    precision: Option<uint>,
+}
+
+pub trait Str {
+    fn fmt(&self, w: io::Writer, spec: &Spec);
+}
+
+pub trait Char {
+    fn fmt(&self, w: io::Writer, spec: &Spec);
+}
+
+pub trait Bool {
+    fn fmt(&self, w: io::Writer, spec: &Spec);
+}
+
+pub trait Signed {
+    fn fmt(&self, w: io::Writer, spec: &Spec);
+}
+
+pub trait Unsigned {
+    fn fmt(&self, w: io::Writer, spec: &Spec);
+}
+
+pub trait LowerHex {
+    fn fmt(&self, w: io::Writer, spec: &Spec);
+}
+
+pub trait UpperHex {
+    fn fmt(&self, w: io::Writer, spec: &Spec);
+}
+
+pub trait Octal {
+    fn fmt(&self, w: io::Writer, spec: &Spec);
+}
+
+pub trait Binary {
+    fn fmt(&self, w: io::Writer, spec: &Spec);
+}
+
+pub trait Float {
+    fn fmt(&self, w: io::Writer, spec: &Spec);
+}
+
+impl &str: Str {
+    fn fmt(&self, w: io::Writer, spec: &Spec) { pad_str(w, spec, *self) }
+}
+
+impl ~str: Str {
+    fn fmt(&self, w: io::Writer, spec: &Spec) { pad_str(w, spec, *self) }
+}
+
+impl @str: Str {
+    fn fmt(&self, w: io::Writer, spec: &Spec) { pad_str(w, spec, *self) }
+}
+
+impl char: Char {
+    fn fmt(&self, w: io::Writer, spec: &Spec) {
+        pad_str(w, spec, str::from_char(*self))
+    }
+}
+
+impl bool: Bool {
+    fn fmt(&self, w: io::Writer, spec: &Spec) {
+        pad_str(w, spec, if *self { "true" } else { "false" })
+    }
+}
+
+/// Writes a string, cut to at most `spec.precision` characters
+pub fn pad_str(w: io::Writer, spec: &Spec, s: &str) {
+    match spec.precision {
+      Some(max) if max < str::char_len(s) => {
+        pad(w, spec, str::substr(s, 0, max), AlignLeft)
+      }
+      _ => pad(w, spec, s, AlignLeft)
+    }
+}
+
+/**
+ * Writes the magnitude `n` of an integer in base `radix`, with a leading
+ * `-` if it is negative, zero-extended to at least `spec.precision`
+ * digits. The digits go from a buffer on the stack to the writer.
+ */
+pub fn pad_integral(w: io::Writer, spec: &Spec, negative: bool, n: u64,
+                    radix: uint, upper: bool) {
+    // A u64 has at most 64 digits, in base 2
+    let mut buf = [mut 0u8, ..64];
+    let mut start = buf.len();
+    let mut n = n;
+    loop {
+        let d = (n % radix as u64) as u8;
+        start -= 1;
+        buf[start] = if d < 10 {
+            '0' as u8 + d
+        } else if upper {
+            'A' as u8 + d - 10
+        } else {
+            'a' as u8 + d - 10
+        };
+        n /= radix as u64;
+        if n == 0 { break; }
+    }
+    let digits = buf.view(start, buf.len());
+
+    let sign = if negative { 1 } else { 0 };
+    let zeros = match spec.precision {
+      Some(min) if min > digits.len() => min - digits.len(),
+      _ => 0
+    };
+    let (before, after) = padding(spec, sign + zeros + digits.len(),
+                                  AlignRight);
+    // Zero-filling goes between the sign and the digits
+    let (before, zeros) = match (spec.fill, spec.align) {
+      ('0', AlignDefault) | ('0', AlignRight) => (0, zeros + before),
+      _ => (before, zeros)
+    };
+    for uint::range(0, before) |_| { w.write_char(spec.fill); }
+    if negative { w.write_char('-'); }
+    for uint::range(0, zeros) |_| { w.write_char('0'); }
+    w.write(digits);
+    for uint::range(0, after) |_| { w.write_char(spec.fill); }
+}
+
+/// Writes a number that has already been converted to a string
+pub fn pad_number(w: io::Writer, spec: &Spec, s: &str) {
+    match (spec.fill, spec.align, spec.width) {
+      ('0', AlignDefault, Some(width)) | ('0', AlignRight, Some(width)) => {
+        let (sign, digits) = split_sign(s);
+        w.write_str(sign);
+        let rest = if width > sign.len() { width - sign.len() } else { 0 };
+        pad(w, &Spec { width: Some(rest), .. *spec }, digits, AlignRight)
+      }
+      _ => pad(w, spec, s, AlignRight)
+    }
+}
+
+fn split_sign(s: &str) -> (~str, ~str) {
+    if s.len() > 0 && (s[0] == '-' as u8 || s[0] == '+' as u8) {
+        (s.slice(0, 1), s.slice(1, s.len()))
+    } else {
+        (~"", s.to_owned())
+    }
+}
+
+fn pad(w: io::Writer, spec: &Spec, s: &str, default: Alignment) {
+    let (before, after) = padding(spec, str::char_len(s), default);
+    for uint::range(0, before) |_| { w.write_char(spec.fill); }
+    w.write_str(s);
+    for uint::range(0, after) |_| { w.write_char(spec.fill); }
+}
+
+// How many fill characters go before and after `len` characters to make
+// them `spec.width` wide
+fn padding(spec: &Spec, len: uint, default: Alignment) -> (uint, uint) {
+    let diff = match spec.width {
+      Some(width) if width > len => width - len,
+      _ => return (0, 0)
+    };
+    let align = if spec.align == AlignDefault { default } else { spec.align };
+    match align {
+      AlignRight => (diff, 0),
+      AlignCenter => (diff / 2, diff - diff / 2),
+      AlignLeft | AlignDefault => (0, diff)
+    }
+}
+
+// The format string parser, used by the syntax extension at compile time
+#[doc(hidden)]
+pub mod parse {
+    use char;
+    use fmt::{Spec, Alignment, AlignLeft, AlignRight, AlignCenter};
+    use fmt::AlignDefault;
+    use prelude::*;
+    use str;
+
+    #[deriving_eq]
+    pub enum Position {
+        /// `{}`: the argument after the last one that was also `{}`
+        ArgumentNext,
+        ArgumentIs(uint),
+        ArgumentNamed(~str),
+    }
+
+    #[deriving_eq]
+    pub struct Argument {
+        position: Position,
+        spec: Spec,
+        /// The type character, if any
+        ty: Option<char>,
+        /// The byte range of the whole `{...}` within the format string
+        lo: uint,
+        hi: uint,
+    }
+
+    // A fragment of the output
+    #[deriving_eq]
+    pub enum Piece { PieceString(~str), PieceArgument(Argument), }
+
+    /// Reports an error at the given byte range of the format string
+    pub type ErrorFn = @fn(uint, uint, &str) -> !;
+
+    pub fn parse_format_string(s: &str, err: ErrorFn) -> ~[Piece] {
+        fn flush(pieces: &mut ~[Piece], buf: &mut ~str) {
+            if !buf.is_empty() {
+                pieces.push(PieceString(copy *buf));
+                *buf = ~"";
+            }
+        }
+
+        let lim = s.len();
+        let mut pieces = ~[];
+        let mut buf = ~"";
+        let mut i = 0;
+
+        while i < lim {
+            if s[i] == '{' as u8 {
+                if i + 1 < lim && s[i + 1] == '{' as u8 {
+                    str::push_char(&mut buf, '{');
+                    i += 2;
+                    loop;
+                }
+                flush(&mut pieces, &mut buf);
+                let close = match str::find_char_from(s, '}', i) {
+                    Some(close) => close,
+                    None => err(i, lim, "unterminated format argument: \
+                                         missing `}`")
+                };
+                pieces.push(PieceArgument(parse_argument(s, i, close + 1,
+                                                         err)));
+                i = close + 1;
+            } else if s[i] == '}' as u8 {
+                if i + 1 < lim && s[i + 1] == '}' as u8 {
+                    str::push_char(&mut buf, '}');
+                    i += 2;
+                    loop;
+                }
+                err(i, i + 1, "unmatched `}` in format string; \
+                               use `}}` for a literal brace");
+            } else {
+                let str::CharRange {ch, next} = str::char_range_at(s, i);
+                str::push_char(&mut buf, ch);
+                i = next;
+            }
+        }
+
+        flush(&mut pieces, &mut buf);
+        pieces
+    }
+
+    /// Parses the argument `{...}` that spans bytes `lo` to `hi` of `s`
+    pub fn parse_argument(s: &str, lo: uint, hi: uint, err: ErrorFn)
+                       -> Argument {
+        let end = hi - 1;
+        let mut i = lo + 1;
+
+        let position = match peek_num(s, i, end) {
+            Some((n, next)) => { i = next; ArgumentIs(n) }
+            None if i < end && is_ident_start(s[i] as char) => {
+                let start = i;
+                while i < end && is_ident_continue(s[i] as char) { i += 1; }
+                ArgumentNamed(s.slice(start, i))
+            }
+            None => ArgumentNext
+        };
+
+        let mut spec = Spec {
+            fill: ' ',
+            align: AlignDefault,
+            width: None,
+            precision: None
+        };
+        let mut ty = None;
+
+        if i < end {
+            if s[i] != ':' as u8 {
+                err(lo, hi, "expected `:` or `}` after the argument");
+            }
+            i += 1;
+
+            // A fill character is only recognized in front of an alignment
+            if i < end {
+                let first = str::char_range_at(s, i);
+                let second = if first.next < end {
+                    alignment(s[first.next] as char)
+                } else {
+                    None
+                };
+                match second {
+                  Some(align) => {
+                    spec.fill = first.ch;
+                    spec.align = align;
+                    i = first.next + 1;
+                  }
+                  None => match alignment(first.ch) {
+                    Some(align) => { spec.align = align; i = first.next; }
+                    None => ()
+                  }
+                }
+            }
+
+            match peek_num(s, i, end) {
+                Some((n, next)) => { spec.width = Some(n); i = next; }
+                None => ()
+            }
+
+            if i < end && s[i] == '.' as u8 {
+                match peek_num(s, i + 1, end) {
+                    Some((n, next)) => { spec.precision = Some(n); i = next; }
+                    None => err(lo, hi, "expected a precision after `.`")
+                }
+            }
+
+            if i < end {
+                let c = s[i] as char;
+                match c {
+                  'b' | 'c' | 'd' | 'f' | 'i' | 'o' | 's' | 't' | 'u' |
+                  'x' | 'X' | '?' => { ty = Some(c); i += 1; }
+                  _ => err(lo, hi, fmt!("unknown format type `%c`", c))
+                }
+            }
+
+            if i < end {
+                err(lo, hi, "invalid format specification");
+            }
+        }
+
+        Argument {
+            position: position,
+            spec: spec,
+            ty: ty,
+            lo: lo,
+            hi: hi
+        }
+    }
+
+    fn alignment(c: char) -> Option<Alignment> {
+        match c {
+          '<' => Some(AlignLeft),
+          '>' => Some(AlignRight),
+          '^' => Some(AlignCenter),
+          _ => None
+        }
+    }
+
+    fn is_ident_start(c: char) -> bool {
+        char::is_alphabetic(c) || c == '_'
+    }
+
+    fn is_ident_continue(c: char) -> bool {
+        char::is_alphanumeric(c) || c == '_'
+    }
+
+    fn peek_num(s: &str, i: uint, lim: uint) -> Option<(uint, uint)> {
+        let mut i = i;
+        let mut accum = 0;
+        let mut found = false;
+
+        while i < lim {
+            match char::to_digit(s[i] as char, 10) {
+                Some(x) => {
+                    found = true;
+                    accum *= 10;
+                    accum += x;
+                    i += 1;
+                }
+                None => break
+            }
+        }
+
+        if found { Some((accum, i)) } else { None }
+    }
+}
+
+// Functions called by the code that `format!` and `write!` expand to. Each
+// conversion is a generic function bounded by its trait, so that a type
+// that can't be formatted is reported against the format string.
+#[doc(hidden)]
+pub mod rt {
+    use fmt::*;
+    use io;
+    use io::WriterUtil;
+    use prelude::*;
+    use sys;
+    use to_str::ToStr;
+
+    pub fn write_str(w: io::Writer, s: &str) { w.write_str(s) }
+
+    pub fn to_str<T: ToStr>(w: io::Writer, spec: &Spec, x: &T) {
+        pad_str(w, spec, x.to_str())
+    }
+    pub fn string<T: Str>(w: io::Writer, spec: &Spec, x: &T) {
+        x.fmt(w, spec)
+    }
+    pub fn character<T: Char>(w: io::Writer, spec: &Spec, x: &T) {
+        x.fmt(w, spec)
+    }
+    pub fn boolean<T: Bool>(w: io::Writer, spec: &Spec, x: &T) {
+        x.fmt(w, spec)
+    }
+    pub fn signed<T: Signed>(w: io::Writer, spec: &Spec, x: &T) {
+        x.fmt(w, spec)
+    }
+    pub fn unsigned<T: Unsigned>(w: io::Writer, spec: &Spec, x: &T) {
+        x.fmt(w, spec)
+    }
+    pub fn lower_hex<T: LowerHex>(w: io::Writer, spec: &Spec, x: &T) {
+        x.fmt(w, spec)
+    }
+    pub fn upper_hex<T: UpperHex>(w: io::Writer, spec: &Spec, x: &T) {
+        x.fmt(w, spec)
+    }
+    pub fn octal<T: Octal>(w: io::Writer, spec: &Spec, x: &T) {
+        x.fmt(w, spec)
+    }
+    pub fn binary<T: Binary>(w: io::Writer, spec: &Spec, x: &T) {
+        x.fmt(w, spec)
+    }
+    pub fn floating<T: Float>(w: io::Writer, spec: &Spec, x: &T) {
+        x.fmt(w, spec)
+    }
+    pub fn poly<T>(w: io::Writer, spec: &Spec, x: &T) {
+        pad_str(w, spec, sys::log_str(x))
+    }
+}
+
+// Bulk of the tests are in src/test/run-pass/syntax-extension-format.rs
+#[cfg(test)]
+mod test {
+    use fmt::parse::*;
+    use fmt::{Spec, AlignDefault, AlignLeft, AlignCenter, pad_integral};
+    use io;
+    use prelude::*;
+    use str;
+    use vec;
+
+    fn fail_on_error(_lo: uint, _hi: uint, msg: &str) -> ! {
+        die!(msg.to_owned())
+    }
+
+    fn plain() -> Spec {
+        Spec { fill: ' ', align: AlignDefault, width: None, precision: None }
+    }
+
+    #[test]
+    fn parse_literal_and_braces() {
+        assert parse_format_string("a {{b}} c", fail_on_error) ==
+            ~[PieceString(~"a {b} c")];
+    }
+
+    #[test]
+    fn parse_arguments() {
+        let pieces = parse_format_string("{}-{1}{name}", fail_on_error);
+        assert pieces == ~[
+            PieceArgument(Argument { position: ArgumentNext, spec: plain(),
+                                     ty: None, lo: 0, hi: 2 }),
+            PieceString(~"-"),
+            PieceArgument(Argument { position: ArgumentIs(1), spec: plain(),
+                                     ty: None, lo: 3, hi: 6 }),
+            PieceArgument(Argument { position: ArgumentNamed(~"name"),
+                                     spec: plain(), ty: None,
+                                     lo: 6, hi: 12 }),
+        ];
+    }
+
+    #[test]
+    fn parse_spec() {
+        match parse_format_string("{0:*^10.3s}", fail_on_error) {
+          [PieceArgument(ref arg)] => {
+            assert arg.spec == Spec { fill: '*', align: AlignCenter,
+                                      width: Some(10), precision: Some(3) };
+            assert arg.ty == Some('s');
+          }
+          _ => die!()
+        }
+        match parse_format_string("{:<5}", fail_on_error) {
+          [PieceArgument(ref arg)] => {
+            assert arg.spec == Spec { align: AlignLeft, width: Some(5),
+                                      .. plain() };
+            assert arg.ty == None;
+          }
+          _ => die!()
+        }
+    }
+
+    #[test]
+    #[should_fail]
+    #[ignore(cfg(windows))]
+    fn parse_unknown_type() {
+        parse_format_string("{:q}", fail_on_error);
+    }
+
+    #[test]
+    #[should_fail]
+    #[ignore(cfg(windows))]
+    fn parse_unterminated() {
+        parse_format_string("{0", fail_on_error);
+    }
+
+    #[test]
+    fn pad_integral_digits() {
+        fn integral(spec: Spec, negative: bool, n: u64,
+                    radix: uint, upper: bool) -> ~str {
+            do io::with_str_writer |w| {
+                pad_integral(w, &spec, negative, n, radix, upper)
+            }
+        }
+        assert integral(plain(), false, 0, 10, false) == ~"0";
+        assert integral(plain(), true, 1 << 63, 10, false) ==
+            ~"-9223372036854775808";
+        assert integral(plain(), false, 0xbeef, 16, true) == ~"BEEF";
+        assert integral(plain(), false, -1 as u64, 2, false) ==
+            str::from_chars(vec::from_elem(64, '1'));
+        assert integral(Spec { fill: '0', width: Some(6), .. plain() },
+                        true, 42, 10, false) == ~"-00042";
+        assert integral(Spec { align: AlignCenter, width: Some(7),
+                               precision: Some(3), .. plain() },
+                        true, 5, 8, false) == ~" -005  ";
+    }
+}
+
+// Local Variables:
+// mode: rust;
+// fill-column: 78;
+// indent-tabs-mode: nil
+// c-basic-offset: 4
+// buffer-file-coding-system: utf-8-unix
+// End:
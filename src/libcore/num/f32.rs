@@ -12,9 +12,11 @@
 
 use cmath;
 use cmp;
+use fmt;
+use io;
 use libc::{c_float, c_int};
 use num;
-use option::Option;
+use option::{None, Option, Some};
 use from_str;
 use to_str;
 
@@ -445,6 +447,16 @@ impl f32: num::ToStrRadix {
     }
 }
 
+impl f32: fmt::Float {
+    fn fmt(&self, w: io::Writer, spec: &fmt::Spec) {
+        let s = match spec.precision {
+          Some(digits) => to_str_exact(*self, digits),
+          None => to_str_digits(*self, 6)
+        };
+        fmt::pad_number(w, spec, s)
+    }
+}
+
 /**
  * Convert a string in base 10 to a float.
  * Accepts a optional decimal exponent.
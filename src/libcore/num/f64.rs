@@ -12,10 +12,12 @@
 
 use cmath;
 use cmp;
+use fmt;
+use io;
 use libc::{c_double, c_int};
 use libc;
 use num;
-use option::Option;
+use option::{None, Option, Some};
 use to_str;
 use from_str;
 
@@ -469,6 +471,16 @@ impl f64: num::ToStrRadix {
     }
 }
 
+impl f64: fmt::Float {
+    fn fmt(&self, w: io::Writer, spec: &fmt::Spec) {
+        let s = match spec.precision {
+          Some(digits) => to_str_exact(*self, digits),
+          None => to_str_digits(*self, 6)
+        };
+        fmt::pad_number(w, spec, s)
+    }
+}
+
 /**
  * Convert a string in base 10 to a float.
  * Accepts a optional decimal exponent.
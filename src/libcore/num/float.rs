@@ -25,6 +25,8 @@ use m_float = f64;
 use cmp::{Eq, Ord};
 use cmp;
 use f64;
+use fmt;
+use io;
 use num;
 use num::Num::from_int;
 use option::{None, Option, Some};
@@ -212,6 +214,16 @@ impl float: num::ToStrRadix {
     }
 }
 
+impl float: fmt::Float {
+    fn fmt(&self, w: io::Writer, spec: &fmt::Spec) {
+        let s = match spec.precision {
+          Some(digits) => to_str_exact(*self, digits),
+          None => to_str_digits(*self, 6)
+        };
+        fmt::pad_number(w, spec, s)
+    }
+}
+
 /**
  * Convert a string in base 10 to a float.
  * Accepts a optional decimal exponent.
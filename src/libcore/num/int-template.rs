@@ -13,6 +13,8 @@ use T = self::inst::T;
 use char;
 use cmp::{Eq, Ord};
 use cmp;
+use fmt;
+use io;
 use to_str::ToStr;
 use from_str::FromStr;
 use num::{ToStrRadix, FromStrRadix};
@@ -295,6 +297,15 @@ impl T : ToStrRadix {
     }
 }
 
+impl T : fmt::Signed {
+    fn fmt(&self, w: io::Writer, spec: &fmt::Spec) {
+        // The negation wraps for the most negative value, whose
+        // magnitude is still right as a u64
+        let n = if *self < 0 { -(*self as i64) as u64 } else { *self as u64 };
+        fmt::pad_integral(w, spec, *self < 0, n, 10, false)
+    }
+}
+
 #[test]
 fn test_from_str() {
     assert from_str(~"0") == Some(0 as T);
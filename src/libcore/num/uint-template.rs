@@ -14,6 +14,8 @@ use T_SIGNED = self::inst::T_SIGNED;
 use char;
 use cmp::{Eq, Ord};
 use cmp;
+use fmt;
+use io;
 use to_str::ToStr;
 use from_str::FromStr;
 use num::{ToStrRadix, FromStrRadix};
@@ -257,6 +259,36 @@ impl T : ToStrRadix {
     }
 }
 
+impl T : fmt::Unsigned {
+    fn fmt(&self, w: io::Writer, spec: &fmt::Spec) {
+        fmt::pad_integral(w, spec, false, *self as u64, 10, false)
+    }
+}
+
+impl T : fmt::LowerHex {
+    fn fmt(&self, w: io::Writer, spec: &fmt::Spec) {
+        fmt::pad_integral(w, spec, false, *self as u64, 16, false)
+    }
+}
+
+impl T : fmt::UpperHex {
+    fn fmt(&self, w: io::Writer, spec: &fmt::Spec) {
+        fmt::pad_integral(w, spec, false, *self as u64, 16, true)
+    }
+}
+
+impl T : fmt::Octal {
+    fn fmt(&self, w: io::Writer, spec: &fmt::Spec) {
+        fmt::pad_integral(w, spec, false, *self as u64, 8, false)
+    }
+}
+
+impl T : fmt::Binary {
+    fn fmt(&self, w: io::Writer, spec: &fmt::Spec) {
+        fmt::pad_integral(w, spec, false, *self as u64, 2, false)
+    }
+}
+
 #[test]
 pub fn test_to_str() {
     assert to_str_radix(0 as T, 10u) == ~"0";
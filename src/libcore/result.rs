@@ -22,6 +22,7 @@ use vec;
 
 /// The result type
 #[deriving_eq]
+#[must_use]
 pub enum Result<T, U> {
     /// Contains the successful result value
     Ok(T),
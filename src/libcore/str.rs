@@ -1452,6 +1452,39 @@ pub pure fn len(s: &str) -> uint {
 /// Returns the number of characters that a string holds
 pub pure fn char_len(s: &str) -> uint { count_chars(s, 0u, len(s)) }
 
+/**
+ * Returns the Levenshtein distance between two strings: the number of
+ * single-character insertions, deletions and substitutions needed to turn
+ * one into the other.
+ */
+pub fn levdistance(s: &str, t: &str) -> uint {
+    let slen = char_len(s);
+    let tlen = char_len(t);
+
+    if slen == 0u { return tlen; }
+    if tlen == 0u { return slen; }
+
+    let mut dcol = vec::from_fn(tlen + 1u, |x| x);
+
+    for each_chari(s) |i, sc| {
+        let mut current = i;
+        dcol[0] = current + 1u;
+
+        for each_chari(t) |j, tc| {
+            let next = dcol[j + 1u];
+            if sc == tc {
+                dcol[j + 1u] = current;
+            } else {
+                dcol[j + 1u] = uint::min(current, next);
+                dcol[j + 1u] = uint::min(dcol[j + 1u], dcol[j]) + 1u;
+            }
+            current = next;
+        }
+    }
+
+    return dcol[tlen];
+}
+
 /*
 Section: Misc
 */
@@ -2688,6 +2721,16 @@ mod tests {
         t(["hi"], " ", "hi");
     }
 
+    #[test]
+    fn test_levdistance() {
+        assert levdistance(~"", ~"") == 0u;
+        assert levdistance(~"abc", ~"") == 3u;
+        assert levdistance(~"", ~"abc") == 3u;
+        assert levdistance(~"kitten", ~"sitting") == 3u;
+        assert levdistance(~"flaw", ~"lawn") == 2u;
+        assert levdistance(~"ไท华", ~"ไ华") == 1u;
+    }
+
     #[test]
     fn test_repeat() {
         assert repeat(~"x", 4) == ~"xxxx";
@@ -0,0 +1,110 @@
+// Copyright 2013 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+/*!
+Runs the front end of the compiler over a crate
+
+A refactoring needs to know what every name in the crate resolves to,
+including the names of fields and methods, which are only known once the
+crate has been type checked. The passes are run the way
+`driver::compile_upto` runs them, stopping after type checking. Errors in
+the crate are reported as usual and fail the task.
+
+`#[cfg(test)]` code is analyzed along with the rest of the crate, but the
+test harness isn't generated.
+*/
+
+use core::prelude::*;
+
+use rustc::driver::driver;
+use rustc::driver::session::Session;
+use rustc::driver::session;
+use rustc::front;
+use rustc::metadata::creader;
+use rustc::middle::{freevars, lint, region, resolve, ty, typeck};
+use rustc::middle;
+use syntax::ast;
+use syntax::ast_map;
+use syntax::codemap::FileMap;
+use syntax::diagnostic;
+use syntax;
+
+pub struct Analysis {
+    sess: Session,
+    crate: @ast::crate,
+    tcx: ty::ctxt,
+    method_map: typeck::method_map
+}
+
+/// Analyzes the crate whose root is `file`
+pub fn from_file(file: &Path, lib_paths: ~[Path]) -> Analysis {
+    analyze(driver::file_input(copy *file), lib_paths)
+}
+
+/// Analyzes a crate given as a string, which is useful for testing
+pub fn from_str(source: ~str) -> Analysis {
+    analyze(driver::str_input(source), ~[])
+}
+
+fn analyze(input: driver::input, lib_paths: ~[Path]) -> Analysis {
+    let options = @{
+        binary: ~"refactor",
+        addl_lib_search_paths: lib_paths,
+        test: true,
+        .. *session::basic_options()
+    };
+    let sess = driver::build_session(options, diagnostic::emit);
+    let cfg = driver::build_configuration(sess, ~"refactor", copy input);
+
+    let crate = driver::parse_input(sess, copy cfg, input);
+    *sess.building_library = session::building_library(
+        sess.opts.crate_type, crate, sess.opts.test);
+
+    let crate = front::config::strip_unconfigured_items(crate);
+    let crate = syntax::ext::expand::expand_crate(
+        sess.parse_sess, copy cfg,
+        |cx, vi| driver::load_extern_syntax(sess, cx, vi), false, crate);
+    let crate = front::intrinsic_inject::inject_intrinsic(sess, crate);
+    let crate = front::core_inject::maybe_inject_libcore_ref(sess, crate);
+    lint::build_settings_crate(sess, crate);
+
+    let ast_map = ast_map::map_crate(sess.diagnostic(), *crate);
+    creader::read_crates(sess.diagnostic(), *crate, sess.cstore,
+                         sess.filesearch,
+                         session::sess_os_to_meta_os(sess.targ_cfg.os),
+                         sess.opts.static, sess.parse_sess.interner);
+    let lang_items = middle::lang_items::collect_language_items(crate, sess);
+    let resolved = resolve::resolve_crate(sess, lang_items, crate);
+    let def_map = resolved.def_map;
+
+    let freevars = freevars::annotate_freevars(def_map, crate);
+    let region_map = region::resolve_crate(sess, def_map, crate);
+    let rp_set = region::determine_rp_in_crate(sess, ast_map, def_map,
+                                               crate);
+    let tcx = ty::mk_ctxt(sess, def_map, ast_map, freevars, region_map,
+                          rp_set, move lang_items, crate);
+    let (method_map, _) = typeck::check_crate(tcx, resolved.trait_map,
+                                              crate);
+    sess.abort_if_errors();
+
+    Analysis {
+        sess: sess,
+        crate: crate,
+        tcx: tcx,
+        method_map: method_map
+    }
+}
+
+pub impl Analysis {
+    /// The file containing the crate root
+    fn root_file(&self) -> @FileMap {
+        self.sess.codemap.lookup_char_pos(self.crate.span.lo).file
+    }
+}
@@ -0,0 +1,262 @@
+// Copyright 2013 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+/*!
+Changing source files
+
+A refactoring is made of edits, each of which replaces a range of a file's
+source with new text; everything outside those ranges, comments and
+formatting included, is left alone. No file is written until all the
+edits have been worked out. The crate is then analyzed again, and if it
+no longer compiles, or the target isn't named in as many places as it was
+before, every file is put back the way it was.
+*/
+
+use core::prelude::*;
+
+use analysis;
+use occurrences;
+use target;
+use text;
+
+use core::io::WriterUtil;
+use core::io;
+use core::str;
+use core::task;
+use core::vec;
+use std::sort;
+use syntax::codemap::FileMap;
+
+pub struct Edit {
+    file: @FileMap,
+    /// The range of the file's source to replace
+    lo: uint,
+    hi: uint,
+    text: ~str
+}
+
+/// The edits that make up a refactoring, and what to check once they've
+/// been made
+pub struct Change {
+    edits: ~[Edit],
+    /// The path of the target once the edits have been made
+    new_path: ~str,
+    /// The number of places the target is named in
+    uses: uint
+}
+
+/**
+ * Sorts edits by file and position, dropping duplicates, which expanded
+ * code can give rise to. Edits that overlap can't both be made, so they
+ * are an error.
+ */
+pub fn normalize(edits: &[Edit]) -> Result<~[Edit], ~str> {
+    let sorted = do sort::merge_sort(edits) |a, b| {
+        if a.file.start_pos != b.file.start_pos {
+            a.file.start_pos < b.file.start_pos
+        } else if a.lo != b.lo {
+            a.lo < b.lo
+        } else {
+            a.hi <= b.hi
+        }
+    };
+
+    let mut result: ~[Edit] = ~[];
+    for sorted.each |e| {
+        if !result.is_empty() {
+            let last = &result[result.len() - 1];
+            if last.file.start_pos == e.file.start_pos {
+                if last.lo == e.lo && last.hi == e.hi && last.text == e.text {
+                    loop;
+                }
+                if e.lo < last.hi {
+                    return Err(fmt!("%s: conflicting changes",
+                                    position(e)));
+                }
+            }
+        }
+        result.push(copy *e);
+    }
+    Ok(result)
+}
+
+/// Applies the edits to `src`, which they must all be for; the edits must
+/// have been normalized
+pub fn apply(src: &str, edits: &[Edit]) -> ~str {
+    let mut result = ~"";
+    let mut pos = 0;
+    for edits.each |e| {
+        result += str::slice(src, pos, e.lo);
+        result += e.text;
+        pos = e.hi;
+    }
+    result += str::slice(src, pos, src.len());
+    result
+}
+
+/// The files that the edits are in, in order
+pub fn files(edits: &[Edit]) -> ~[@FileMap] {
+    let mut files: ~[@FileMap] = ~[];
+    for edits.each |e| {
+        if !files.any(|f| f.start_pos == e.file.start_pos) {
+            files.push(e.file);
+        }
+    }
+    files
+}
+
+/// The edits that are in `file`
+pub fn edits_in(edits: &[Edit], file: @FileMap) -> ~[Edit] {
+    vec::filtered(edits, |e| e.file.start_pos == file.start_pos)
+}
+
+/// Describes each of the edits on a line of its own, for a dry run
+pub fn describe(edits: &[Edit]) -> ~str {
+    do io::with_str_writer |wr| {
+        for edits.each |e| {
+            let old = str::slice(*e.file.src, e.lo, e.hi);
+            let what = if old.is_empty() {
+                fmt!("insert %s", show(e.text))
+            } else if e.text.is_empty() {
+                fmt!("remove %s", show(old))
+            } else {
+                fmt!("replace %s with %s", show(old), show(e.text))
+            };
+            wr.write_line(fmt!("%s: %s", position(e), what));
+        }
+    }
+}
+
+/**
+ * Writes the changed files, then checks that the crate rooted at `crate`
+ * still compiles and that the target is named in as many places as it
+ * was. If it doesn't, the files are restored.
+ */
+pub fn commit(change: &Change, crate: &Path, lib_paths: ~[Path])
+           -> Result<(), ~str> {
+    let files = files(change.edits);
+    for files.each |file| {
+        if str::starts_with(file.name, "<") {
+            return Err(fmt!("%s isn't a file, so it can't be changed",
+                            file.name));
+        }
+    }
+
+    let mut written = ~[];
+    let mut result = Ok(());
+    for files.each |file| {
+        let src = apply(*file.src, edits_in(change.edits, *file));
+        match write_file(&Path(file.name), src) {
+          Ok(()) => written.push(*file),
+          Err(e) => {
+            result = Err(e);
+            break;
+          }
+        }
+    }
+
+    if result.is_ok() {
+        let crate = copy *crate;
+        let new_path = copy change.new_path;
+        let uses = change.uses;
+        result = match do task::try |move crate, move lib_paths,
+                                     move new_path| {
+            check(&crate, lib_paths, new_path, uses)
+        } {
+          Ok(checked) => checked,
+          Err(()) => Err(~"the crate doesn't compile once changed")
+        };
+    }
+
+    if result.is_err() {
+        for written.each |file| {
+            match write_file(&Path(file.name), *file.src) {
+              Ok(()) => (),
+              Err(e) => io::stderr().write_line(fmt!("error: %s", e))
+            }
+        }
+    }
+    result
+}
+
+// Analyzes the changed crate, which fails if it doesn't compile, and counts
+// the places that name the target
+fn check(crate: &Path, lib_paths: ~[Path], path: &str, uses: uint)
+      -> Result<(), ~str> {
+    let a = analysis::from_file(crate, lib_paths);
+    match target::find(&a, path) {
+      Ok(target) => {
+        let found = occurrences::collect(&a, target).len();
+        if found == uses {
+            Ok(())
+        } else {
+            Err(fmt!("`%s` would be named in %u places instead of %u",
+                     path, found, uses))
+        }
+      }
+      Err(e) => Err(e)
+    }
+}
+
+fn write_file(path: &Path, src: &str) -> Result<(), ~str> {
+    match io::file_writer(path, ~[io::Create, io::Truncate]) {
+      Ok(wr) => {
+        wr.write_str(src);
+        Ok(())
+      }
+      Err(e) => Err(fmt!("couldn't write %s: %s", path.to_str(), e))
+    }
+}
+
+fn position(e: &Edit) -> ~str {
+    let (line, col) = text::line_col(*e.file.src, e.lo);
+    fmt!("%s:%u:%u", e.file.name, line, col)
+}
+
+// Text spanning several lines is only described by its length
+fn show(s: &str) -> ~str {
+    if str::contains_char(s, '\n') {
+        fmt!("%u lines", str::lines(str::trim(s)).len())
+    } else {
+        fmt!("`%s`", s)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use core::prelude::*;
+
+    use edit::*;
+
+    use syntax::codemap::{CodeMap, FileMap};
+
+    fn edit(file: @FileMap, lo: uint, hi: uint, text: &str) -> Edit {
+        Edit { file: file, lo: lo, hi: hi, text: text.to_owned() }
+    }
+
+    #[test]
+    fn should_apply_edits_in_order() {
+        let cm = CodeMap::new();
+        let file = cm.new_filemap(~"a.rs", @~"fn foo() { foo() }");
+        let edits = normalize(~[edit(file, 11, 14, "bar"),
+                                edit(file, 3, 6, "bar"),
+                                edit(file, 11, 14, "bar")]).get();
+        assert edits.len() == 2;
+        assert apply(*file.src, edits) == ~"fn bar() { bar() }";
+    }
+
+    #[test]
+    fn should_refuse_overlapping_edits() {
+        let cm = CodeMap::new();
+        let file = cm.new_filemap(~"a.rs", @~"fn foo() { }");
+        assert normalize(~[edit(file, 3, 6, "bar"),
+                           edit(file, 0, 4, "")]).is_err();
+    }
+}
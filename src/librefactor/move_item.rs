@@ -0,0 +1,588 @@
+// Copyright 2013 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+/*!
+Moving items to other modules
+
+The item's text, along with its attributes and doc comments, is cut from
+its module and added to the end of the destination. Every path that names
+the item, or a variant of a moved enum, is replaced by a global path to
+its new place, as in `::dest::Item`, and every `use` of it imports it from
+the destination instead.
+
+Inside the item, a path that starts with the name of something declared
+or imported in the old module is made global too, so that it still names
+the same thing. The item is made public if it's named from outside
+itself, since that code is now in a different module.
+
+Functions, constants, types, structs, enums and traits can be moved, but
+not modules or impls; an impl doesn't need to be in the same module as
+its type.
+*/
+
+use core::prelude::*;
+
+use analysis::Analysis;
+use edit::{Change, Edit};
+use edit;
+use occurrences::{Definition, PathEnd, UsePathEnd, UseListName};
+use occurrences;
+use target::ItemTarget;
+use target;
+use text;
+
+use core::char;
+use core::str;
+use core::uint;
+use core::vec;
+use syntax::ast;
+use syntax::ast_map;
+use syntax::ast_util::local_def;
+use syntax::codemap::{FileMap, Pos};
+use syntax::visit;
+
+/// Moves the item named by `path` to the module named by `dest`, which is
+/// the crate root if it's empty
+pub fn move_item(a: &Analysis, path: &str, dest: &str)
+              -> Result<Change, ~str> {
+    let (item, item_path) = match target::find(a, path) {
+      Ok(ItemTarget(did)) => {
+        match a.tcx.items.get(&did.node) {
+          ast_map::node_item(item, item_path) => (item, item_path),
+          _ => return Err(fmt!("`%s` isn't an item", path))
+        }
+      }
+      Ok(_) => return Err(fmt!("`%s` isn't an item", path)),
+      Err(e) => return Err(e)
+    };
+    match item.node {
+      ast::item_mod(*) | ast::item_foreign_mod(*) | ast::item_impl(*) |
+      ast::item_mac(*) => {
+        return Err(fmt!("`%s` can't be moved; only functions, constants, \
+                         types, structs, enums and traits can", path));
+      }
+      _ => ()
+    }
+    let name = a.sess.str_of(item.ident);
+
+    let mut old_names = ~[];
+    for item_path.each |elt| {
+        match *elt {
+          ast_map::path_mod(ident) => old_names.push(a.sess.str_of(ident)),
+          ast_map::path_name(_) => {
+            return Err(fmt!("`%s` is declared inside a function, so it \
+                             can't be moved", path));
+          }
+        }
+    }
+    let dest_names = if dest.is_empty() || dest == "::" {
+        ~[]
+    } else {
+        str::split_str(dest, "::")
+    };
+    if dest_names == old_names {
+        return Err(fmt!("`%s` is already in %s", path,
+                        if dest_names.is_empty() { ~"the crate root" }
+                        else { fmt!("`%s`", dest) }));
+    }
+    let dest_mod = match find_module(a, dest_names) {
+      Some(m) => m,
+      None => return Err(fmt!("can't find the module `%s`", dest))
+    };
+    if target::module_names(a, &module(a, dest_mod)).contains(&name) {
+        return Err(fmt!("`%s` already declares something named `%s`",
+                        dest, name));
+    }
+
+    // The text of the item, from its first attribute
+    let file = a.sess.codemap.lookup_char_pos(item.span.lo).file;
+    if item.span.expn_info.is_some() ||
+            (str::starts_with(file.name, "<") &&
+             file.start_pos != a.root_file().start_pos) {
+        return Err(fmt!("`%s` was generated by a syntax extension", path));
+    }
+    let src: &str = *file.src;
+    let mut lo = (item.span.lo - file.start_pos).to_uint();
+    let hi = (item.span.hi - file.start_pos).to_uint();
+    let item_lo = lo;
+    for item.attrs.each |attr| {
+        if attr.span.lo >= file.start_pos && attr.span.lo < item.span.lo {
+            lo = uint::min(lo, (attr.span.lo - file.start_pos).to_uint());
+        }
+    }
+
+    // Rewrite the paths that name the item or its variants. Those inside
+    // the item are rewritten in the text that's moved.
+    let mut targets = ~[item.id];
+    match item.node {
+      ast::item_enum(ref def, _) => {
+        for (*def).variants.each |v| { targets.push(v.node.id); }
+      }
+      _ => ()
+    }
+    let mut edits = ~[];
+    let mut inner = ~[];
+    let mut uses = 0;
+    let mut used_outside = false;
+    for targets.eachi |i, id| {
+        let target = ItemTarget(local_def(*id));
+        let target_name = a.sess.str_of(target::name(a, target));
+        let new_path = str::connect(dest_names + ~[target_name], "::");
+        let occurrences = occurrences::collect(a, target);
+        if i == 0 { uses = occurrences.len(); }
+
+        for occurrences.each |o| {
+            let inside = o.file.start_pos == file.start_pos &&
+                o.lo >= lo && o.hi <= hi;
+            let edit = match o.kind {
+              Definition => None,
+              // The item can still be named by itself inside itself
+              PathEnd(start) if inside && start == o.lo => None,
+              PathEnd(start) => {
+                Some(Edit { file: o.file, lo: start, hi: o.hi,
+                            text: ~"::" + new_path })
+              }
+              UsePathEnd(start) => {
+                Some(Edit { file: o.file, lo: start, hi: o.hi,
+                            text: copy new_path })
+              }
+              UseListName => {
+                let (line, col) = text::line_col(*o.file.src, o.lo);
+                return Err(fmt!("%s:%u:%u: `%s` is imported in a list, \
+                                 which it would have to be taken out of",
+                                o.file.name, line, col, target_name));
+              }
+              _ => a.sess.bug(~"an item is named as a member")
+            };
+            match edit {
+              Some(edit) if inside => inner.push(edit),
+              Some(edit) => {
+                used_outside = true;
+                edits.push(edit);
+              }
+              None => ()
+            }
+        }
+    }
+
+    // Qualify what the item names from its old module
+    let old_mod = match find_module(a, old_names) {
+      Some(m) => m,
+      None => a.sess.bug(~"the module of the item can't be found")
+    };
+    let scope = scope(a, &module(a, old_mod), old_names, item.id);
+    inner.push_all(qualify(a, item, scope, file));
+
+    if used_outside {
+        match item.vis {
+          ast::public => (),
+          ast::private => {
+            inner.push(Edit { file: file, lo: item_lo, hi: item_lo + 4,
+                              text: ~"pub" });
+          }
+          ast::inherited => {
+            inner.push(Edit { file: file, lo: item_lo, hi: item_lo,
+                              text: ~"pub " });
+          }
+        }
+    }
+
+    let inner = match edit::normalize(inner) {
+      Ok(inner) => inner,
+      Err(e) => return Err(e)
+    };
+    let shifted = do inner.map |e| {
+        Edit { lo: e.lo - lo, hi: e.hi - lo, .. copy *e }
+    };
+    let moved = edit::apply(str::view(src, lo, hi), shifted);
+    let indent = if text::is_blank(src, text::line_start(src, lo), lo) {
+        lo - text::line_start(src, lo)
+    } else {
+        0
+    };
+
+    edits.push(removal(file, lo, hi));
+    match insertion(a, dest_mod, moved, indent) {
+      Ok(edit) => edits.push(edit),
+      Err(e) => return Err(e)
+    }
+    Ok(Change {
+        edits: edits,
+        new_path: str::connect(dest_names + ~[name], "::"),
+        uses: uses
+    })
+}
+
+// The module item named by the path `names`, which is None for the crate
+// root; or None if there's no such module
+fn find_module(a: &Analysis, names: &[~str]) -> Option<Option<@ast::item>> {
+    if names.is_empty() { return Some(None); }
+    match target::find_names(a, names) {
+      Some(ItemTarget(did)) => {
+        match a.tcx.items.get(&did.node) {
+          ast_map::node_item(item, _) => {
+            match item.node {
+              ast::item_mod(_) => Some(Some(item)),
+              _ => None
+            }
+          }
+          _ => None
+        }
+      }
+      _ => None
+    }
+}
+
+fn module(a: &Analysis, m: Option<@ast::item>) -> ast::_mod {
+    match m {
+      None => copy a.crate.node.module,
+      Some(item) => {
+        match item.node {
+          ast::item_mod(ref m) => copy *m,
+          _ => a.sess.bug(~"expected a module")
+        }
+      }
+    }
+}
+
+// The edit that removes the text between `lo` and `hi`, along with the
+// rest of its lines if there's nothing else on them
+fn removal(file: @FileMap, lo: uint, hi: uint) -> Edit {
+    let src: &str = *file.src;
+    let mut lo = lo, hi = hi;
+    let start = text::line_start(src, lo);
+    let end = match str::find_char_from(src, '\n', hi) {
+      Some(end) => end + 1,
+      None => src.len()
+    };
+    if text::is_blank(src, start, lo) && text::is_blank(src, hi, end) {
+        lo = start;
+        hi = end;
+        // Don't leave two blank lines where there was one on each side
+        let next = match str::find_char_from(src, '\n', hi) {
+          Some(next) => next + 1,
+          None => src.len()
+        };
+        if hi < src.len() && text::is_blank(src, hi, next) &&
+                (lo == 0 || text::is_blank(src, text::line_start(src, lo - 1),
+                                           lo)) {
+            hi = next;
+        }
+    }
+    Edit { file: file, lo: lo, hi: hi, text: ~"" }
+}
+
+// The edit that adds the text of the moved item, which was indented by
+// `indent` columns, to the end of the module `m`
+fn insertion(a: &Analysis, m: Option<@ast::item>, moved: ~str,
+             indent: uint) -> Result<Edit, ~str> {
+    let (file, close) = match m {
+      None => (a.root_file(), None),
+      Some(item) => {
+        let file = a.sess.codemap.lookup_char_pos(item.span.hi).file;
+        let hi = (item.span.hi - file.start_pos).to_uint();
+        if hi > 0 && file.src[hi - 1] == '}' as u8 {
+            (file, Some(hi - 1))
+        } else {
+            // A module in a file of its own ends at the end of the file
+            match file_of(a, item) {
+              Some(file) => (file, None),
+              None => {
+                return Err(fmt!("can't tell which file `%s` is in, since \
+                                 it's empty", a.sess.str_of(item.ident)));
+              }
+            }
+        }
+      }
+    };
+    let src: &str = *file.src;
+
+    match close {
+      Some(close) => {
+        // Replace the whitespace before the closing brace
+        let mut lo = close;
+        while lo > 0 && char::is_whitespace(src[lo - 1] as char) {
+            lo -= 1;
+        }
+        let base = indentation(src, close);
+        let mut text = ~"\n";
+        if lo > 0 && src[lo - 1] != '{' as u8 { text += ~"\n"; }
+        text += reindent(moved, indent, base + ~"    ");
+        text += ~"\n" + base;
+        Ok(Edit { file: file, lo: lo, hi: close, text: text })
+      }
+      None => {
+        let mut text = ~"";
+        if !src.is_empty() && !str::ends_with(src, "\n") { text += ~"\n"; }
+        text += ~"\n" + reindent(moved, indent, ~"") + ~"\n";
+        Ok(Edit { file: file, lo: src.len(), hi: src.len(), text: text })
+      }
+    }
+}
+
+// The file that the contents of a module declared as `mod m;` are in,
+// which is known from where its first item or `use` is
+fn file_of(a: &Analysis, item: @ast::item) -> Option<@FileMap> {
+    let sp = match item.node {
+      ast::item_mod(ref m) if !m.view_items.is_empty() => {
+        m.view_items[0].span
+      }
+      ast::item_mod(ref m) if !m.items.is_empty() => m.items[0].span,
+      _ => return None
+    };
+    Some(a.sess.codemap.lookup_char_pos(sp.lo).file)
+}
+
+// The spaces and tabs that the line containing `pos` starts with
+fn indentation(src: &str, pos: uint) -> ~str {
+    let start = text::line_start(src, pos);
+    let mut end = start;
+    while end < src.len() &&
+            (src[end] == ' ' as u8 || src[end] == '\t' as u8) {
+        end += 1;
+    }
+    str::slice(src, start, end)
+}
+
+// Indents `s` by `new` instead of by `old` columns; its first line has no
+// indentation of its own
+fn reindent(s: &str, old: uint, new: &str) -> ~str {
+    let mut lines = ~[];
+    for str::lines(s).eachi |i, line| {
+        let mut line = copy *line;
+        if i > 0 && line.len() >= old &&
+                text::is_blank(line, 0, old) {
+            line = str::slice(line, old, line.len());
+        }
+        lines.push(if line.is_empty() { line } else { new + line });
+    }
+    str::connect(lines, "\n")
+}
+
+// What the names in scope in the module `m`, apart from the moved item's,
+// refer to, as pairs of a name and a global path
+fn scope(a: &Analysis, m: &ast::_mod, m_names: &[~str], moved: ast::node_id)
+      -> ~[(~str, ~str)] {
+    let global = |names: &[~str]| ~"::" + str::connect(names, "::");
+    let mut scope = ~[];
+    for m.items.each |item| {
+        if item.id == moved { loop; }
+        let name = a.sess.str_of(item.ident);
+        match item.node {
+          ast::item_impl(*) => (),
+          ast::item_enum(ref def, _) => {
+            scope.push((copy name, global(vec::append_one(
+                m_names.to_owned(), copy name))));
+            for (*def).variants.each |v| {
+                let v_name = a.sess.str_of(v.node.name);
+                scope.push((copy v_name, global(vec::append_one(
+                    m_names.to_owned(), copy v_name))));
+            }
+          }
+          _ => {
+            scope.push((copy name, global(vec::append_one(
+                m_names.to_owned(), copy name))));
+          }
+        }
+    }
+    for m.view_items.each |vi| {
+        match vi.node {
+          // Crates are only linked at the top level
+          ast::view_item_use(ident, _, _) if m_names.is_empty() => {
+            let name = a.sess.str_of(ident);
+            scope.push((copy name, global(~[copy name])));
+          }
+          ast::view_item_use(*) => (),
+          ast::view_item_import(ref vps) => {
+            for vps.each |vp| {
+                match vp.node {
+                  ast::view_path_simple(ident, p, _, _) => {
+                    let names = p.idents.map(|i| a.sess.str_of(*i));
+                    scope.push((a.sess.str_of(ident), global(names)));
+                  }
+                  ast::view_path_list(p, ref idents, _) => {
+                    let names = p.idents.map(|i| a.sess.str_of(*i));
+                    for idents.each |id| {
+                        let name = a.sess.str_of(id.node.name);
+                        scope.push((copy name, global(vec::append_one(
+                            copy names, copy name))));
+                    }
+                  }
+                  ast::view_path_glob(*) => ()
+                }
+            }
+          }
+        }
+    }
+    scope
+}
+
+struct QualifyCtxt {
+    a: @Analysis,
+    scope: ~[(~str, ~str)],
+    file: @FileMap,
+    edits: @mut ~[Edit]
+}
+
+// The edits that make the paths inside `item` that start with a name in
+// `scope` global
+fn qualify(a: &Analysis, item: @ast::item, scope: ~[(~str, ~str)],
+           file: @FileMap) -> ~[Edit] {
+    let cx = @QualifyCtxt {
+        a: @copy *a,
+        scope: scope,
+        file: file,
+        edits: @mut ~[]
+    };
+    let v = visit::mk_simple_visitor(@visit::SimpleVisitor {
+        visit_item: |i| {
+            match i.node {
+              ast::item_trait(_, ref supertraits, _) => {
+                for supertraits.each |tr| {
+                    qualify_path(cx, tr.path, tr.ref_id);
+                }
+              }
+              _ => ()
+            }
+        },
+        visit_pat: |p| {
+            match p.node {
+              ast::pat_ident(_, path, _) | ast::pat_enum(path, _) |
+              ast::pat_struct(path, _, _) => qualify_path(cx, path, p.id),
+              _ => ()
+            }
+        },
+        visit_expr: |e| {
+            match e.node {
+              ast::expr_path(p) | ast::expr_struct(p, _, _) => {
+                qualify_path(cx, p, e.id);
+              }
+              _ => ()
+            }
+        },
+        visit_ty: |t| {
+            match t.node {
+              ast::ty_path(p, id) => qualify_path(cx, p, id),
+              _ => ()
+            }
+        },
+        .. *visit::default_simple_visitor()
+    });
+    (v.visit_item)(item, (), v);
+    copy *cx.edits
+}
+
+fn qualify_path(cx: @QualifyCtxt, p: @ast::path, id: ast::node_id) {
+    if p.global { return; }
+    // A path of one segment may name a local variable, a binding or a type
+    // parameter, which stay where they are
+    if p.idents.len() == 1 {
+        match cx.a.tcx.def_map.find(&id) {
+          Some(ast::def_fn(*)) | Some(ast::def_static_method(*)) |
+          Some(ast::def_const(*)) | Some(ast::def_ty(*)) |
+          Some(ast::def_struct(*)) | Some(ast::def_variant(*)) |
+          Some(ast::def_mod(*)) | Some(ast::def_foreign_mod(*)) => (),
+          _ => return
+        }
+    }
+
+    let first = cx.a.sess.str_of(p.idents[0]);
+    let mut global = None;
+    for cx.scope.each |s| {
+        match *s {
+          (ref name, ref path) if *name == first => {
+            global = Some(copy *path);
+            break;
+          }
+          _ => ()
+        }
+    }
+    let global = match global {
+      Some(global) => global,
+      None => return
+    };
+    // Paths made up by syntax extensions aren't in the source
+    if p.span.lo < cx.file.start_pos { return; }
+    let lo = (p.span.lo - cx.file.start_pos).to_uint();
+    let hi = lo + first.len();
+    if hi > cx.file.src.len() ||
+            text::find_first(*cx.file.src, lo, hi, first) != Some(lo) {
+        return;
+    }
+    cx.edits.push(Edit { file: cx.file, lo: lo, hi: hi, text: global });
+}
+
+#[cfg(test)]
+mod test {
+    use core::prelude::*;
+
+    use analysis;
+    use edit;
+    use move_item::*;
+
+    // Moves `path` in `source` and returns the changed source
+    fn move_str(source: ~str, path: &str, dest: &str) -> Result<~str, ~str> {
+        let a = analysis::from_str(source);
+        do move_item(&a, path, dest).chain |change| {
+            let edits = edit::normalize(change.edits).get();
+            Ok(edit::apply(*a.root_file().src, edits))
+        }
+    }
+
+    #[test]
+    fn should_move_a_function() {
+        let source = ~"mod m {\n\
+                       \x20   pub fn foo() -> int { bar() }\n\
+                       \x20   pub fn bar() -> int { 1 }\n\
+                       }\n\
+                       mod n {\n\
+                       }\n\
+                       fn main() { m::foo(); }";
+        assert move_str(source, "m::foo", "n").get() ==
+            ~"mod m {\n\
+              \x20   pub fn bar() -> int { 1 }\n\
+              }\n\
+              mod n {\n\
+              \x20   pub fn foo() -> int { ::m::bar() }\n\
+              }\n\
+              fn main() { ::n::foo(); }";
+    }
+
+    #[test]
+    fn should_move_an_enum_and_its_variants() {
+        let source = ~"enum Color { Red, Green }\n\
+                       mod paint { }\n\
+                       fn f(c: Color) -> bool {\n\
+                       \x20   match c {\n\
+                       \x20       Red => true,\n\
+                       \x20       Green => false\n\
+                       \x20   }\n\
+                       }\n\
+                       fn main() { f(Red); }";
+        assert move_str(source, "Color", "paint").get() ==
+            ~"mod paint {\n\
+              \x20   pub enum Color { Red, Green }\n\
+              }\n\
+              fn f(c: ::paint::Color) -> bool {\n\
+              \x20   match c {\n\
+              \x20       ::paint::Red => true,\n\
+              \x20       ::paint::Green => false\n\
+              \x20   }\n\
+              }\n\
+              fn main() { f(::paint::Red); }";
+    }
+
+    #[test]
+    fn should_refuse_to_move_modules() {
+        let source = ~"mod m { }\nmod n { }\nfn main() { }";
+        assert move_str(copy source, "m", "n").is_err();
+        assert move_str(source, "main", "nowhere").is_err();
+    }
+}
@@ -0,0 +1,368 @@
+// Copyright 2013 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+/*!
+Finding every place that names a target
+
+Paths are matched by what resolve says they refer to, in the def map, and
+fields and methods by the types and method origins that typeck found, so
+an identifier is only found if it really names the target, and not some
+other thing of the same name. Its position is then found in the text of
+the enclosing span.
+
+Code that isn't compiled, because of a `#[cfg]` that doesn't hold, isn't
+seen, except for `#[cfg(test)]` code, which the analysis includes.
+*/
+
+use core::prelude::*;
+
+use analysis::Analysis;
+use target::{Target, ItemTarget, FieldTarget, MethodTarget};
+use target;
+use text;
+
+use core::str;
+use rustc::middle::ty;
+use syntax::ast;
+use syntax::ast_util::{local_def, path_to_ident};
+use syntax::codemap::{BytePos, FileMap, FssNone, Pos};
+use syntax::visit;
+
+pub enum Kind {
+    /// Where the target is declared, or where a method of a trait is
+    /// implemented
+    Definition,
+    /// The last segment of a path, which starts at the given offset
+    PathEnd(uint),
+    /// The last segment of the path of a `use`, which starts at the given
+    /// offset
+    UsePathEnd(uint),
+    /// A name in a `use` list, as in `use a::{b, c}`
+    UseListName,
+    /// The name of a field or method after a `.`, or of a field in a struct
+    /// literal or pattern
+    MemberName,
+    /// A field bound to a variable of the same name in a struct pattern, as
+    /// in `Point { x, y }`
+    ShorthandField
+}
+
+pub struct Occurrence {
+    file: @FileMap,
+    /// The offsets of the name in the file's source
+    lo: uint,
+    hi: uint,
+    kind: Kind
+}
+
+struct Ctxt {
+    a: @Analysis,
+    target: Target,
+    name: ~str,
+    root: @FileMap,
+    found: @mut ~[Occurrence]
+}
+
+/// Finds the occurrences of the target, in no particular order
+pub fn collect(a: &Analysis, target: Target) -> ~[Occurrence] {
+    let cx = @Ctxt {
+        a: @copy *a,
+        target: target,
+        name: a.sess.str_of(target::name(a, target)),
+        root: a.root_file(),
+        found: @mut ~[]
+    };
+    let v = visit::mk_simple_visitor(@visit::SimpleVisitor {
+        visit_view_item: |vi| visit_view_item(cx, vi),
+        visit_item: |i| visit_item(cx, i),
+        visit_pat: |p| visit_pat(cx, p),
+        visit_expr: |e| visit_expr(cx, e),
+        visit_ty: |t| visit_ty(cx, t),
+        .. *visit::default_simple_visitor()
+    });
+    visit::visit_crate(*a.crate, (), v);
+    copy *cx.found
+}
+
+fn visit_view_item(cx: @Ctxt, vi: @ast::view_item) {
+    match vi.node {
+      ast::view_item_import(ref vps) => {
+        for vps.each |vp| {
+            match vp.node {
+              ast::view_path_simple(_, p, _, _) => {
+                let names = p.idents.map(|i| cx.a.sess.str_of(*i));
+                if target::find_names(cx.a, names) == Some(cx.target) {
+                    add_path(cx, p, true);
+                }
+              }
+              ast::view_path_list(p, ref idents, _) => {
+                for idents.each |id| {
+                    let mut names = p.idents.map(|i| cx.a.sess.str_of(*i));
+                    names.push(cx.a.sess.str_of(id.node.name));
+                    if target::find_names(cx.a, names) == Some(cx.target) {
+                        add_first(cx, id.span.lo, id.span.hi, UseListName);
+                    }
+                }
+              }
+              ast::view_path_glob(*) => ()
+            }
+        }
+      }
+      ast::view_item_use(*) => ()
+    }
+}
+
+fn visit_item(cx: @Ctxt, item: @ast::item) {
+    let did = local_def(item.id);
+    if cx.target == ItemTarget(did) {
+        add_first(cx, item.span.lo, item.span.hi, Definition);
+    }
+
+    match item.node {
+      ast::item_enum(ref def, _) => {
+        for (*def).variants.each |v| {
+            if cx.target == ItemTarget(local_def(v.node.id)) {
+                add_first(cx, v.span.lo, v.span.hi, Definition);
+            }
+        }
+      }
+      ast::item_struct(def, _) => {
+        for def.fields.each |field| {
+            match field.node.kind {
+              ast::named_field(ident, _, _)
+                      if cx.target == FieldTarget(did, ident) => {
+                add_first(cx, field.span.lo, field.span.hi, Definition);
+              }
+              _ => ()
+            }
+        }
+      }
+      ast::item_trait(_, ref supertraits, ref methods) => {
+        for supertraits.each |tr| {
+            visit_path(cx, tr.path, tr.ref_id);
+        }
+        for methods.each |method| {
+            let (ident, sp) = match *method {
+              ast::required(ref m) => (m.ident, m.span),
+              ast::provided(m) => (m.ident, m.span)
+            };
+            if cx.target == MethodTarget(did, ident) {
+                add_first(cx, sp.lo, sp.hi, Definition);
+            }
+        }
+      }
+      ast::item_impl(_, tr, _, ref methods) => {
+        match tr {
+          Some(tr) => visit_path(cx, tr.path, tr.ref_id),
+          None => ()
+        }
+        let trait_did = target::impl_trait(cx.a, item.id);
+        for methods.each |method| {
+            let target = match trait_did {
+              Some(trait_did) => MethodTarget(trait_did, method.ident),
+              None => MethodTarget(local_def(method.id), method.ident)
+            };
+            if cx.target == target {
+                add_first(cx, method.span.lo, method.span.hi, Definition);
+            }
+        }
+      }
+      _ => ()
+    }
+}
+
+fn visit_pat(cx: @Ctxt, p: @ast::pat) {
+    match p.node {
+      ast::pat_ident(_, path, _) | ast::pat_enum(path, _) => {
+        visit_path(cx, path, p.id);
+      }
+      ast::pat_struct(path, ref fields, _) => {
+        visit_path(cx, path, p.id);
+        match cx.target {
+          FieldTarget(did, ident)
+                  if struct_of_node(cx, p.id) == Some(did) => {
+            let mut lo = path.span.hi;
+            for fields.each |f| {
+                if f.ident == ident { add_field_pat(cx, lo, f.pat); }
+                lo = f.pat.span.hi;
+            }
+          }
+          _ => ()
+        }
+      }
+      _ => ()
+    }
+}
+
+fn visit_expr(cx: @Ctxt, e: @ast::expr) {
+    match e.node {
+      ast::expr_path(p) => visit_path(cx, p, e.id),
+      ast::expr_struct(p, ref fields, _) => {
+        visit_path(cx, p, e.id);
+        match cx.target {
+          FieldTarget(did, ident)
+                  if struct_of_node(cx, e.id) == Some(did) => {
+            for fields.each |f| {
+                if f.node.ident == ident {
+                    add_first(cx, f.span.lo, f.span.hi, MemberName);
+                }
+            }
+          }
+          _ => ()
+        }
+      }
+      ast::expr_field(base, ident, _) => {
+        // A method that is named but not called is in the method map too
+        let target = match cx.a.method_map.find(&e.id) {
+          Some(ref entry) => {
+            Some(target::origin_target(cx.a, entry.origin, ident))
+          }
+          None => {
+            struct_of_type(cx, ty::expr_ty(cx.a.tcx, base)).map(|did| {
+                FieldTarget(*did, ident)
+            })
+          }
+        };
+        if target == Some(cx.target) {
+            add_first(cx, base.span.hi, e.span.hi, MemberName);
+        }
+      }
+      ast::expr_method_call(rcvr, ident, _, _, _) => {
+        match cx.a.method_map.find(&e.id) {
+          Some(ref entry) if target::origin_target(cx.a, entry.origin,
+                                                   ident) == cx.target => {
+            add_first(cx, rcvr.span.hi, e.span.hi, MemberName);
+          }
+          _ => ()
+        }
+      }
+      _ => ()
+    }
+}
+
+fn visit_ty(cx: @Ctxt, t: @ast::Ty) {
+    match t.node {
+      ast::ty_path(p, id) => visit_path(cx, p, id),
+      _ => ()
+    }
+}
+
+// Records the path `p` if the node `id` it belongs to was resolved to the
+// target
+fn visit_path(cx: @Ctxt, p: @ast::path, id: ast::node_id) {
+    match cx.a.tcx.def_map.find(&id) {
+      Some(def) => {
+        if target::def_target(cx.a, def, path_to_ident(p)) ==
+                Some(cx.target) {
+            add_path(cx, p, false);
+        }
+      }
+      None => ()
+    }
+}
+
+// The struct that the node `id` has the type of, looking through pointers
+fn struct_of_node(cx: @Ctxt, id: ast::node_id) -> Option<ast::def_id> {
+    struct_of_type(cx, ty::node_id_to_type(cx.a.tcx, id))
+}
+
+fn struct_of_type(cx: @Ctxt, t: ty::t) -> Option<ast::def_id> {
+    match ty::get(t).sty {
+      ty::ty_struct(did, _) => Some(did),
+      _ => {
+        match ty::deref(cx.a.tcx, t, false) {
+          Some(mt) => struct_of_type(cx, mt.ty),
+          None => None
+        }
+      }
+    }
+}
+
+// The file, and the offsets within it, of the text between `lo` and `hi`,
+// if that is source text that can be rewritten. Code generated by syntax
+// extensions is given spans in files of its own, whose names are in angle
+// brackets; so is a crate given as a string.
+fn source(cx: @Ctxt, lo: BytePos, hi: BytePos)
+       -> Option<(@FileMap, uint, uint)> {
+    if hi.to_uint() < lo.to_uint() { return None; }
+    let fm = cx.a.sess.codemap.lookup_char_pos(lo).file;
+    match fm.substr {
+      FssNone => (),
+      _ => return None
+    }
+    if str::starts_with(fm.name, "<") && fm.start_pos != cx.root.start_pos {
+        return None;
+    }
+    let lo = (lo - fm.start_pos).to_uint();
+    let hi = (hi - fm.start_pos).to_uint();
+    if hi > fm.src.len() { return None; }
+    Some((fm, lo, hi))
+}
+
+// Records the first occurrence of the name between `lo` and `hi`
+fn add_first(cx: @Ctxt, lo: BytePos, hi: BytePos, kind: Kind) {
+    match source(cx, lo, hi) {
+      Some((fm, lo, hi)) => {
+        match text::find_first(*fm.src, lo, hi, cx.name) {
+          Some(pos) => add(cx, fm, pos, kind),
+          None => ()
+        }
+      }
+      None => ()
+    }
+}
+
+// Records the last segment of `p`, unless it's been renamed by a `use`
+fn add_path(cx: @Ctxt, p: @ast::path, in_use: bool) {
+    match source(cx, p.span.lo, p.span.hi) {
+      Some((fm, lo, hi)) => {
+        match text::find_last(*fm.src, lo, hi, cx.name) {
+          Some(pos) => {
+            let kind = if in_use { UsePathEnd(lo) } else { PathEnd(lo) };
+            add(cx, fm, pos, kind);
+          }
+          None => ()
+        }
+      }
+      None => ()
+    }
+}
+
+// Records a field of a struct pattern that starts after `lo` and is matched
+// against `pat`
+fn add_field_pat(cx: @Ctxt, lo: BytePos, pat: @ast::pat) {
+    match source(cx, lo, pat.span.hi) {
+      Some((fm, lo, hi)) => {
+        match text::find_first(*fm.src, lo, hi, cx.name) {
+          Some(pos) => {
+            let pat_lo = (pat.span.lo - fm.start_pos).to_uint();
+            add(cx, fm, pos,
+                if pos < pat_lo { MemberName } else { ShorthandField });
+          }
+          None => ()
+        }
+      }
+      None => ()
+    }
+}
+
+// Expanded code can have many nodes with the same span, so each position
+// is only recorded once
+fn add(cx: @Ctxt, fm: @FileMap, pos: uint, kind: Kind) {
+    for cx.found.each |o| {
+        if o.file.start_pos == fm.start_pos && o.lo == pos { return; }
+    }
+    cx.found.push(Occurrence {
+        file: fm,
+        lo: pos,
+        hi: pos + cx.name.len(),
+        kind: kind
+    });
+}
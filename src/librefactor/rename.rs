@@ -0,0 +1,287 @@
+// Copyright 2013 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+/*!
+Renaming items, fields and methods
+
+Every identifier that names the target is replaced by the new name. A
+field that a struct pattern binds to a variable of the same name, as in
+`Point { x, y }`, is given the new name explicitly, as in
+`Point { new: x, y }`, so that the variable keeps its name.
+
+Modules aren't renamed, since the name of a module may also be the name
+of the file it's in.
+*/
+
+use core::prelude::*;
+
+use analysis::Analysis;
+use edit::{Change, Edit};
+use occurrences::ShorthandField;
+use occurrences;
+use target::{Target, ItemTarget, FieldTarget, MethodTarget};
+use target;
+use text;
+
+use core::str;
+use core::vec;
+use syntax::ast;
+use syntax::ast_map;
+use syntax::parse::token;
+
+/// Renames the target named by `path` to `new_name`
+pub fn rename(a: &Analysis, path: &str, new_name: &str)
+           -> Result<Change, ~str> {
+    if !text::is_ident(new_name) {
+        return Err(fmt!("`%s` isn't an identifier", new_name));
+    }
+    if token::keyword_table().contains_key(&new_name.to_owned()) {
+        return Err(fmt!("`%s` is a keyword", new_name));
+    }
+
+    let target = match target::find(a, path) {
+      Ok(target) => target,
+      Err(e) => return Err(e)
+    };
+    match target {
+      ItemTarget(did) => {
+        match a.tcx.items.get(&did.node) {
+          ast_map::node_item(@ast::item { node: ast::item_mod(_), _ }, _) => {
+            return Err(fmt!("`%s` is a module, which can't be renamed",
+                            path));
+          }
+          _ => ()
+        }
+      }
+      _ => ()
+    }
+
+    let old_name = a.sess.str_of(target::name(a, target));
+    if old_name == new_name.to_owned() {
+        return Err(fmt!("`%s` is already named `%s`", path, new_name));
+    }
+    match conflict(a, target, new_name) {
+      Some(e) => return Err(fmt!("can't rename `%s`: %s", path, e)),
+      None => ()
+    }
+
+    let occurrences = occurrences::collect(a, target);
+    let edits = do occurrences.map |o| {
+        let text = match o.kind {
+          ShorthandField => fmt!("%s: %s", new_name, old_name),
+          _ => new_name.to_owned()
+        };
+        Edit { file: o.file, lo: o.lo, hi: o.hi, text: text }
+    };
+    Ok(Change {
+        edits: edits,
+        new_path: renamed_path(path, new_name),
+        uses: occurrences.len()
+    })
+}
+
+/// The path `path` with its last segment replaced by `new_name`
+pub fn renamed_path(path: &str, new_name: &str) -> ~str {
+    let mut names = str::split_str(path, "::");
+    names[names.len() - 1] = new_name.to_owned();
+    str::connect(names, "::")
+}
+
+// Whether something that would clash with the target is already named
+// `new_name`. Anything that this misses, like a local variable that a
+// renamed function would be shadowed by, is caught when the changed crate
+// is analyzed again.
+fn conflict(a: &Analysis, target: Target, new_name: &str) -> Option<~str> {
+    let clashes = |names: &[~str]| names.contains(&new_name.to_owned());
+    match target {
+      ItemTarget(did) => {
+        match sibling_names(a, &a.crate.node.module, did.node) {
+          Some(ref names) if clashes(*names) => {
+            Some(fmt!("`%s` is already declared in the same module",
+                      new_name))
+          }
+          _ => None
+        }
+      }
+      FieldTarget(did, _) => {
+        match a.tcx.items.get(&did.node) {
+          ast_map::node_item(@ast::item {
+              node: ast::item_struct(def, _), _ }, _) => {
+            let names = do vec::filter_mapped(def.fields) |f| {
+                match f.node.kind {
+                  ast::named_field(ident, _, _) => Some(a.sess.str_of(ident)),
+                  ast::unnamed_field => None
+                }
+            };
+            if clashes(names) {
+                Some(fmt!("there is already a field named `%s`", new_name))
+            } else {
+                None
+            }
+          }
+          _ => None
+        }
+      }
+      MethodTarget(did, _) => {
+        let names = match a.tcx.items.find(&did.node) {
+          Some(ast_map::node_item(@ast::item {
+              node: ast::item_trait(_, _, ref methods), _ }, _)) => {
+            do methods.map |m| {
+                match *m {
+                  ast::required(ref m) => a.sess.str_of(m.ident),
+                  ast::provided(m) => a.sess.str_of(m.ident)
+                }
+            }
+          }
+          Some(ast_map::node_method(_, impl_did, _)) => {
+            match a.tcx.items.get(&impl_did.node) {
+              ast_map::node_item(@ast::item {
+                  node: ast::item_impl(_, _, _, ref methods), _ }, _) => {
+                methods.map(|m| a.sess.str_of(m.ident))
+              }
+              _ => ~[]
+            }
+          }
+          _ => ~[]
+        };
+        if clashes(names) {
+            Some(fmt!("there is already a method named `%s`", new_name))
+        } else {
+            None
+        }
+      }
+    }
+}
+
+// The names declared in the module, within `m`, that declares the item or
+// variant `id`
+fn sibling_names(a: &Analysis, m: &ast::_mod, id: ast::node_id)
+              -> Option<~[~str]> {
+    if m.items.any(|item| declares(*item, id)) {
+        return Some(target::module_names(a, m));
+    }
+    for m.items.each |item| {
+        match item.node {
+          ast::item_mod(ref inner) => {
+            match sibling_names(a, inner, id) {
+              Some(names) => return Some(names),
+              None => ()
+            }
+          }
+          _ => ()
+        }
+    }
+    None
+}
+
+fn declares(item: @ast::item, id: ast::node_id) -> bool {
+    if item.id == id { return true; }
+    match item.node {
+      ast::item_enum(ref def, _) => (*def).variants.any(|v| v.node.id == id),
+      _ => false
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use core::prelude::*;
+
+    use analysis;
+    use edit;
+    use rename::*;
+
+    // Renames `path` in `source` and returns the changed source
+    fn rename_str(source: ~str, path: &str, new_name: &str)
+               -> Result<~str, ~str> {
+        let a = analysis::from_str(source);
+        do rename(&a, path, new_name).chain |change| {
+            let edits = edit::normalize(change.edits).get();
+            Ok(edit::apply(*a.root_file().src, edits))
+        }
+    }
+
+    #[test]
+    fn should_rename_a_function_and_its_calls() {
+        let source = ~"fn foo() { }\n\
+                       // foo is called here\n\
+                       fn main() { foo(); let foo = 1; foo + 1; }";
+        assert rename_str(source, "foo", "bar").get() ==
+            ~"fn bar() { }\n\
+              // foo is called here\n\
+              fn main() { bar(); let foo = 1; foo + 1; }";
+    }
+
+    #[test]
+    fn should_rename_an_item_in_a_module() {
+        let source = ~"use m::Foo;\n\
+                       mod m { pub struct Foo { x: int } }\n\
+                       fn f(p: m::Foo) -> Foo { m::Foo { x: p.x } }\n\
+                       fn main() { }";
+        assert rename_str(source, "m::Foo", "Bar").get() ==
+            ~"use m::Bar;\n\
+              mod m { pub struct Bar { x: int } }\n\
+              fn f(p: m::Bar) -> Bar { m::Bar { x: p.x } }\n\
+              fn main() { }";
+    }
+
+    #[test]
+    fn should_rename_a_field() {
+        let source = ~"struct P { x: int, y: int }\n\
+                       fn f(p: &P) -> int {\n\
+                           let P { x, y: _ } = *p; x + p.x\n\
+                       }\n\
+                       fn main() { f(&P { x: 1, y: 2 }); }";
+        assert rename_str(source, "P::x", "z").get() ==
+            ~"struct P { z: int, y: int }\n\
+              fn f(p: &P) -> int {\n\
+                  let P { z: x, y: _ } = *p; x + p.z\n\
+              }\n\
+              fn main() { f(&P { z: 1, y: 2 }); }";
+    }
+
+    #[test]
+    fn should_rename_a_trait_method_and_its_impls() {
+        let source = ~"trait T { fn go(&self) -> int; }\n\
+                       struct A { x: int }\n\
+                       struct B { x: int }\n\
+                       impl A: T { fn go(&self) -> int { self.x } }\n\
+                       impl B { fn go(&self) -> int { self.x } }\n\
+                       fn f<U: T>(u: &U) -> int { u.go() }\n\
+                       fn main() {\n\
+                           let a = A { x: 1 }, b = B { x: 2 };\n\
+                           a.go() + b.go() + f(&a);\n\
+                       }";
+        assert rename_str(source, "T::go", "run").get() ==
+            ~"trait T { fn run(&self) -> int; }\n\
+              struct A { x: int }\n\
+              struct B { x: int }\n\
+              impl A: T { fn run(&self) -> int { self.x } }\n\
+              impl B { fn go(&self) -> int { self.x } }\n\
+              fn f<U: T>(u: &U) -> int { u.run() }\n\
+              fn main() {\n\
+                  let a = A { x: 1 }, b = B { x: 2 };\n\
+                  a.run() + b.go() + f(&a);\n\
+              }";
+    }
+
+    #[test]
+    fn should_refuse_to_clash() {
+        let source = ~"fn foo() { }\nfn bar() { }\nfn main() { }";
+        assert rename_str(copy source, "foo", "bar").is_err();
+        assert rename_str(copy source, "foo", "loop").is_err();
+        assert rename_str(source, "baz", "qux").is_err();
+    }
+
+    #[test]
+    fn should_replace_the_last_segment() {
+        assert renamed_path("a::b::c", "d") == ~"a::b::d";
+        assert renamed_path("c", "d") == ~"d";
+    }
+}
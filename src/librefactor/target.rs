@@ -0,0 +1,271 @@
+// Copyright 2013 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+/*!
+What a refactoring applies to
+
+A target is named by its path from the crate root: `shapes::Circle` for an
+item or an enum variant, `shapes::Circle::radius` for a field of a struct,
+and `shapes::Shape::area` for a method of a trait or of an inherent impl.
+Renaming a method of a trait renames it in every impl of the trait too,
+so the trait and its impls are a single target.
+*/
+
+use core::prelude::*;
+
+use analysis::Analysis;
+use text;
+
+use core::str;
+use core::vec;
+use rustc::middle::typeck;
+use syntax::ast;
+use syntax::ast_map;
+use syntax::ast_util::{def_id_of_def, is_local, local_def, path_to_ident};
+
+#[deriving_eq]
+pub enum Target {
+    /// An item, or a variant of an enum
+    ItemTarget(ast::def_id),
+    /// A field of the struct with the given id
+    FieldTarget(ast::def_id, ast::ident),
+    /// A method of the trait with the given id, or a method of an inherent
+    /// impl, identified by its own id
+    MethodTarget(ast::def_id, ast::ident)
+}
+
+/// Finds the target named by a path like `a::b::Foo::bar`
+pub fn find(a: &Analysis, path: &str) -> Result<Target, ~str> {
+    let names = str::split_str(path, "::");
+    if vec::any(names, |n| !text::is_ident(*n)) {
+        return Err(fmt!("`%s` is not a path", path));
+    }
+    match find_names(a, names) {
+      Some(target) => Ok(target),
+      None => Err(fmt!("can't find `%s`", path))
+    }
+}
+
+/// Finds the target named by the path from the crate root `names`
+pub fn find_names(a: &Analysis, names: &[~str]) -> Option<Target> {
+    if names.is_empty() { return None; }
+    find_in(a, &a.crate.node.module, names)
+}
+
+fn find_in(a: &Analysis, m: &ast::_mod, names: &[~str]) -> Option<Target> {
+    let name: &str = names[0];
+    let rest = vec::view(names, 1, names.len());
+    let named = |ident: ast::ident, name: &str| a.sess.str_of(ident) == name;
+
+    for m.items.each |item| {
+        if !named(item.ident, name) { loop; }
+        match item.node {
+          ast::item_impl(*) => (),
+          _ if rest.is_empty() => {
+            return Some(ItemTarget(local_def(item.id)));
+          }
+          ast::item_mod(ref inner) => return find_in(a, inner, rest),
+          _ if rest.len() > 1 => (),
+          ast::item_struct(def, _) => {
+            for def.fields.each |field| {
+                match field.node.kind {
+                  ast::named_field(ident, _, _) if named(ident, rest[0]) => {
+                    return Some(FieldTarget(local_def(item.id), ident));
+                  }
+                  _ => ()
+                }
+            }
+          }
+          ast::item_enum(ref def, _) => {
+            for (*def).variants.each |v| {
+                if named(v.node.name, rest[0]) {
+                    return Some(ItemTarget(local_def(v.node.id)));
+                }
+            }
+          }
+          ast::item_trait(_, _, ref methods) => {
+            for methods.each |method| {
+                let ident = match *method {
+                  ast::required(ref m) => m.ident,
+                  ast::provided(m) => m.ident
+                };
+                if named(ident, rest[0]) {
+                    return Some(MethodTarget(local_def(item.id), ident));
+                }
+            }
+          }
+          _ => ()
+        }
+    }
+
+    if rest.is_empty() {
+        // Variants are named as though they were items of the module
+        for m.items.each |item| {
+            match item.node {
+              ast::item_enum(ref def, _) => {
+                for (*def).variants.each |v| {
+                    if named(v.node.name, name) {
+                        return Some(ItemTarget(local_def(v.node.id)));
+                    }
+                }
+              }
+              _ => ()
+            }
+        }
+    } else if rest.len() == 1 {
+        // Methods of inherent impls are named by their type
+        for m.items.each |item| {
+            match item.node {
+              ast::item_impl(_, None, ty, ref methods) => {
+                match ty.node {
+                  ast::ty_path(p, _) if named(path_to_ident(p), name) => {
+                    for methods.each |method| {
+                        if named(method.ident, rest[0]) {
+                            return Some(MethodTarget(local_def(method.id),
+                                                     method.ident));
+                        }
+                    }
+                  }
+                  _ => ()
+                }
+              }
+              _ => ()
+            }
+        }
+    }
+    None
+}
+
+/// The names that the items, variants and imports of `m` declare
+pub fn module_names(a: &Analysis, m: &ast::_mod) -> ~[~str] {
+    let mut names = ~[];
+    for m.items.each |item| {
+        match item.node {
+          ast::item_impl(*) => (),
+          ast::item_enum(ref def, _) => {
+            names.push(a.sess.str_of(item.ident));
+            for (*def).variants.each |v| {
+                names.push(a.sess.str_of(v.node.name));
+            }
+          }
+          _ => names.push(a.sess.str_of(item.ident))
+        }
+    }
+    for m.view_items.each |vi| {
+        match vi.node {
+          ast::view_item_use(ident, _, _) => names.push(a.sess.str_of(ident)),
+          ast::view_item_import(ref vps) => {
+            for vps.each |vp| {
+                match vp.node {
+                  ast::view_path_simple(ident, _, _, _) => {
+                    names.push(a.sess.str_of(ident));
+                  }
+                  ast::view_path_list(_, ref idents, _) => {
+                    for idents.each |id| {
+                        names.push(a.sess.str_of(id.node.name));
+                    }
+                  }
+                  ast::view_path_glob(*) => ()
+                }
+            }
+          }
+        }
+    }
+    names
+}
+
+/// The name of a target, which must be in the crate being analyzed
+pub fn name(a: &Analysis, target: Target) -> ast::ident {
+    match target {
+      ItemTarget(did) => {
+        match a.tcx.items.get(&did.node) {
+          ast_map::node_item(item, _) => item.ident,
+          ast_map::node_variant(ref v, _, _) => v.node.name,
+          _ => a.sess.bug(~"the target isn't an item")
+        }
+      }
+      FieldTarget(_, ident) | MethodTarget(_, ident) => ident
+    }
+}
+
+/// The target that a name resolved to `def` refers to, if any
+pub fn def_target(a: &Analysis, def: ast::def, ident: ast::ident)
+               -> Option<Target> {
+    match def {
+      ast::def_fn(did, _) | ast::def_const(did) | ast::def_ty(did) |
+      ast::def_mod(did) | ast::def_foreign_mod(did) |
+      ast::def_variant(_, did) => Some(ItemTarget(did)),
+      // The constructor of a tuple-like struct has an id of its own
+      ast::def_struct(did) => {
+        if is_local(did) {
+            match a.tcx.items.find(&did.node) {
+              Some(ast_map::node_struct_ctor(_, item, _)) => {
+                return Some(ItemTarget(local_def(item.id)));
+              }
+              _ => ()
+            }
+        }
+        Some(ItemTarget(did))
+      }
+      ast::def_static_method(_, Some(trait_did), _) => {
+        Some(MethodTarget(trait_did, ident))
+      }
+      ast::def_static_method(did, None, _) => {
+        Some(method_target(a, did, ident))
+      }
+      _ => None
+    }
+}
+
+/// The target of a call to the method `ident` that typeck resolved to
+/// `origin`
+pub fn origin_target(a: &Analysis, origin: typeck::method_origin,
+                     ident: ast::ident) -> Target {
+    match origin {
+      typeck::method_static(did) => method_target(a, did, ident),
+      typeck::method_param(ref mp) => MethodTarget(mp.trait_id, ident),
+      typeck::method_trait(did, _, _) | typeck::method_self(did, _) |
+      typeck::method_super(did, _) => MethodTarget(did, ident)
+    }
+}
+
+/**
+ * The target of the method with id `did`: the trait it's declared in, or
+ * that its impl implements, or otherwise the method itself
+ */
+pub fn method_target(a: &Analysis, did: ast::def_id, ident: ast::ident)
+                  -> Target {
+    if is_local(did) {
+        match a.tcx.items.find(&did.node) {
+          Some(ast_map::node_trait_method(_, trait_did, _)) => {
+            return MethodTarget(trait_did, ident);
+          }
+          Some(ast_map::node_method(_, impl_did, _)) => {
+            match impl_trait(a, impl_did.node) {
+              Some(trait_did) => return MethodTarget(trait_did, ident),
+              None => ()
+            }
+          }
+          _ => ()
+        }
+    }
+    MethodTarget(did, ident)
+}
+
+/// The trait that the impl with id `id` implements, if any
+pub fn impl_trait(a: &Analysis, id: ast::node_id) -> Option<ast::def_id> {
+    match a.tcx.items.find(&id) {
+      Some(ast_map::node_item(@ast::item {
+          node: ast::item_impl(_, Some(tr), _, _), _ }, _)) => {
+        a.tcx.def_map.find(&tr.ref_id).map(|d| def_id_of_def(*d))
+      }
+      _ => None
+    }
+}
@@ -0,0 +1,257 @@
+// Copyright 2013 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+/*!
+Finding identifiers in source text
+
+The AST only records the span of a whole path, item or expression, not of
+each identifier in it, so the identifier to rewrite is found by scanning
+the text of the span. Comments, string and character literals, lifetimes
+and attributes are skipped, so a name is only ever found where it is
+really an identifier.
+*/
+
+use core::prelude::*;
+
+use core::str;
+
+/**
+ * Calls `f` with the start and end of each identifier between `lo` and
+ * `hi`, and with how deeply it is nested in angle brackets, until `f`
+ * returns false
+ */
+pub fn each_ident(src: &str, lo: uint, hi: uint,
+                  f: fn(uint, uint, uint) -> bool) {
+    let mut i = lo;
+    let mut depth = 0u;
+    while i < hi {
+        let c = src[i];
+        let next = if i + 1 < hi { src[i + 1] } else { 0u8 };
+        if c == '/' as u8 && next == '/' as u8 {
+            while i < hi && src[i] != '\n' as u8 { i += 1; }
+        } else if c == '/' as u8 && next == '*' as u8 {
+            i = skip_block_comment(src, i, hi);
+        } else if c == '"' as u8 {
+            i = skip_string(src, i + 1, hi);
+        } else if c == '\'' as u8 {
+            i = skip_quote(src, i, hi);
+        } else if c == '#' as u8 && next == '[' as u8 {
+            i = skip_brackets(src, i + 1, hi);
+        } else if c == '-' as u8 && next == '>' as u8 {
+            i += 2;
+        } else if c == '<' as u8 {
+            depth += 1;
+            i += 1;
+        } else if c == '>' as u8 {
+            if depth > 0 { depth -= 1; }
+            i += 1;
+        } else if is_ident_start(c) {
+            let start = i;
+            while i < hi && is_ident_char(src[i]) { i += 1; }
+            if !f(start, i, depth) { return; }
+        } else if char::is_digit(c as char) {
+            // Skip the suffix too, as in `1u8`
+            while i < hi && is_ident_char(src[i]) { i += 1; }
+        } else {
+            i += 1;
+        }
+    }
+}
+
+/// The start of the first occurrence of `name` between `lo` and `hi`
+pub fn find_first(src: &str, lo: uint, hi: uint, name: &str)
+               -> Option<uint> {
+    let mut found = None;
+    for each_ident(src, lo, hi) |start, end, _| {
+        if str::view(src, start, end) == name {
+            found = Some(start);
+            break;
+        }
+    }
+    found
+}
+
+/**
+ * The start of the last occurrence of `name` between `lo` and `hi` that
+ * isn't inside angle brackets, which is the name of the last segment of a
+ * path like `a::b::<c>` or `a::b<c>`
+ */
+pub fn find_last(src: &str, lo: uint, hi: uint, name: &str)
+              -> Option<uint> {
+    let mut found = None;
+    for each_ident(src, lo, hi) |start, end, depth| {
+        if depth == 0 && str::view(src, start, end) == name {
+            found = Some(start);
+        }
+    }
+    found
+}
+
+/// Whether `s` can be used as an identifier, keywords aside
+pub fn is_ident(s: &str) -> bool {
+    if s.is_empty() || !is_ident_start(s[0]) { return false; }
+    for str::each(s) |c| {
+        if !is_ident_char(c) { return false; }
+    }
+    true
+}
+
+/// The start of the line containing `pos`
+pub fn line_start(src: &str, pos: uint) -> uint {
+    let mut i = pos;
+    while i > 0 && src[i - 1] != '\n' as u8 { i -= 1; }
+    i
+}
+
+/// The line and column of `pos`, both starting at 1
+pub fn line_col(src: &str, pos: uint) -> (uint, uint) {
+    let mut line = 1u;
+    for uint::range(0, pos) |i| {
+        if src[i] == '\n' as u8 { line += 1; }
+    }
+    (line, pos - line_start(src, pos) + 1)
+}
+
+/// Whether there is only whitespace between `lo` and `hi`
+pub fn is_blank(src: &str, lo: uint, hi: uint) -> bool {
+    for uint::range(lo, hi) |i| {
+        if !char::is_whitespace(src[i] as char) { return false; }
+    }
+    true
+}
+
+// Non-ASCII bytes are counted as identifier characters, so that a name is
+// never found in the middle of one
+pure fn is_ident_start(c: u8) -> bool {
+    char::is_alphabetic(c as char) || c == '_' as u8 || c >= 0x80u8
+}
+
+pure fn is_ident_char(c: u8) -> bool {
+    is_ident_start(c) || char::is_digit(c as char)
+}
+
+// Comments nest, so `/* a /* b */ c */` is a single comment
+fn skip_block_comment(src: &str, lo: uint, hi: uint) -> uint {
+    let mut i = lo + 2;
+    let mut level = 1u;
+    while i < hi && level > 0 {
+        if src[i] == '/' as u8 && i + 1 < hi && src[i + 1] == '*' as u8 {
+            level += 1;
+            i += 2;
+        } else if src[i] == '*' as u8 && i + 1 < hi &&
+                  src[i + 1] == '/' as u8 {
+            level -= 1;
+            i += 2;
+        } else {
+            i += 1;
+        }
+    }
+    i
+}
+
+// Skips to just after the closing quote of a string whose contents start
+// at `lo`
+fn skip_string(src: &str, lo: uint, hi: uint) -> uint {
+    let mut i = lo;
+    while i < hi {
+        if src[i] == '\\' as u8 {
+            i += 2;
+        } else if src[i] == '"' as u8 {
+            return i + 1;
+        } else {
+            i += 1;
+        }
+    }
+    hi
+}
+
+// A quote starts either a character literal or a lifetime; both are skipped
+fn skip_quote(src: &str, lo: uint, hi: uint) -> uint {
+    if lo + 1 >= hi { return hi; }
+    if src[lo + 1] == '\\' as u8 {
+        let mut i = lo + 2;
+        while i < hi && src[i] != '\'' as u8 { i += 1; }
+        return uint::min(i + 1, hi);
+    }
+    let after = str::char_range_at(src, lo + 1).next;
+    if after < hi && src[after] == '\'' as u8 {
+        return after + 1;
+    }
+    let mut i = lo + 1;
+    while i < hi && is_ident_char(src[i]) { i += 1; }
+    i
+}
+
+// Skips to just after the `]` matching the `[` at `lo`
+fn skip_brackets(src: &str, lo: uint, hi: uint) -> uint {
+    let mut i = lo + 1;
+    let mut level = 1u;
+    while i < hi && level > 0 {
+        if src[i] == '[' as u8 {
+            level += 1;
+            i += 1;
+        } else if src[i] == ']' as u8 {
+            level -= 1;
+            i += 1;
+        } else if src[i] == '"' as u8 {
+            i = skip_string(src, i + 1, hi);
+        } else {
+            i += 1;
+        }
+    }
+    i
+}
+
+#[cfg(test)]
+mod test {
+    use core::prelude::*;
+
+    use text::*;
+
+    fn all(src: &str, name: &str) -> ~[uint] {
+        let mut found = ~[];
+        for each_ident(src, 0, src.len()) |start, end, _| {
+            if str::view(src, start, end) == name { found.push(start); }
+        }
+        found
+    }
+
+    #[test]
+    fn should_find_whole_words() {
+        assert all("foo foobar bar_foo foo", "foo") == ~[0, 19];
+    }
+
+    #[test]
+    fn should_skip_comments_and_literals() {
+        let src = "// foo\n/* foo /* foo */ foo */ \"foo\\\"foo\" 'f' foo";
+        assert all(src, "foo") == ~[src.len() - 3];
+    }
+
+    #[test]
+    fn should_skip_attributes_and_lifetimes() {
+        let src = "#[foo(bar = \"]\")] fn foo(x: &'foo int)";
+        assert all(src, "foo") == ~[21];
+    }
+
+    #[test]
+    fn should_find_the_last_segment_of_a_path() {
+        let src = "a::foo::<foo<b>>";
+        assert find_last(src, 0, src.len(), "foo") == Some(3);
+        assert find_first("x.foo(foo)", 1, 10, "foo") == Some(2);
+    }
+
+    #[test]
+    fn should_check_identifiers() {
+        assert is_ident("_foo1");
+        assert !is_ident("1foo");
+        assert !is_ident("foo::bar");
+        assert !is_ident("");
+    }
+}
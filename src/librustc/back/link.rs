@@ -10,7 +10,7 @@
 
 use core::prelude::*;
 
-use back::rpath;
+use back::{lto, rpath};
 use driver::session;
 use lib::llvm::llvm;
 use lib::llvm::{ModuleRef, mk_pass_manager, mk_target_data, True, False};
@@ -29,6 +29,7 @@ use core::char;
 use core::cmp;
 use core::hash;
 use core::io::{Writer, WriterUtil};
+use core::io;
 use core::libc::{c_int, c_uint, c_char};
 use core::os::consts::{macos, freebsd, linux, android, win32};
 use core::os;
@@ -166,21 +167,32 @@ pub mod jit {
 
 pub mod write {
     use back::link::jit;
-    use back::link::{WriteOutputFile, output_type};
+    use back::link::{WriteOutputFile, link_objects, output_type};
     use back::link::{output_type_assembly, output_type_bitcode};
     use back::link::{output_type_exe, output_type_llvm_assembly};
     use back::link::{output_type_object};
+    use back::lto;
     use driver::session;
     use lib::llvm::llvm;
     use lib::llvm::{False, True, ModuleRef, mk_pass_manager, mk_target_data};
+    use lib::llvm::{AppendingLinkage, ExternalLinkage, InternalLinkage};
+    use lib::llvm::{HiddenVisibility, Linkage, PassManagerRef};
+    use lib::llvm::PrivateLinkage;
+    use lib::llvm::{TargetDataRef, ValueRef};
     use lib;
     use session::Session;
 
     use core::char;
     use core::libc::{c_char, c_int, c_uint};
+    use core::os;
     use core::path::Path;
+    use core::ptr;
     use core::str;
+    use core::uint;
     use core::vec;
+    use std::future;
+    use std::oldmap::HashMap;
+    use std::sort;
 
     pub fn is_object_or_assembly_or_exe(ot: output_type) -> bool {
         if ot == output_type_assembly || ot == output_type_object ||
@@ -190,10 +202,70 @@ pub mod write {
         return false;
     }
 
+    // Runs the function passes of `optimize` over `llmod`, and adds its
+    // module passes to `llpm`.
+    unsafe fn add_optimization_passes(lltd: TargetDataRef,
+                                      llpm: PassManagerRef,
+                                      llmod: ModuleRef,
+                                      optimize: session::OptLevel) {
+        let fpm = mk_pass_manager();
+        llvm::LLVMAddTargetData(lltd, fpm.llpm);
+
+        let FPMB = llvm::LLVMPassManagerBuilderCreate();
+        llvm::LLVMPassManagerBuilderSetOptLevel(FPMB, 2u as c_uint);
+        llvm::LLVMPassManagerBuilderPopulateFunctionPassManager(
+            FPMB, fpm.llpm);
+        llvm::LLVMPassManagerBuilderDispose(FPMB);
+
+        llvm::LLVMRunPassManager(fpm.llpm, llmod);
+        let mut threshold = 225;
+        if optimize == session::Aggressive { threshold = 275; }
+
+        let MPMB = llvm::LLVMPassManagerBuilderCreate();
+        llvm::LLVMPassManagerBuilderSetOptLevel(MPMB, optimize as c_uint);
+        llvm::LLVMPassManagerBuilderSetSizeLevel(MPMB, False);
+        llvm::LLVMPassManagerBuilderSetDisableUnitAtATime(MPMB, False);
+        llvm::LLVMPassManagerBuilderSetDisableUnrollLoops(MPMB, False);
+        llvm::LLVMPassManagerBuilderSetDisableSimplifyLibCalls(MPMB, False);
+
+        if threshold != 0u {
+            llvm::LLVMPassManagerBuilderUseInlinerWithThreshold
+                (MPMB, threshold as c_uint);
+        }
+        llvm::LLVMPassManagerBuilderPopulateModulePassManager(MPMB, llpm);
+
+        llvm::LLVMPassManagerBuilderDispose(MPMB);
+    }
+
+    // Adds the passes that LLVM runs over a whole program at link time
+    unsafe fn add_lto_passes(llpm: PassManagerRef) {
+        let PMB = llvm::LLVMPassManagerBuilderCreate();
+        // Our own code has already made everything but `main` internal
+        llvm::LLVMPassManagerBuilderPopulateLTOPassManager(PMB, llpm,
+                                                           False, True);
+        llvm::LLVMPassManagerBuilderDispose(PMB);
+    }
+
+    fn codegen_opt_level(optimize: session::OptLevel) -> c_int {
+        let LLVMOptNone       = 0 as c_int; // -O0
+        let LLVMOptLess       = 1 as c_int; // -O1
+        let LLVMOptDefault    = 2 as c_int; // -O2, -Os
+        let LLVMOptAggressive = 3 as c_int; // -O3
+
+        match optimize {
+          session::No => LLVMOptNone,
+          session::Less => LLVMOptLess,
+          session::Default => LLVMOptDefault,
+          session::Aggressive => LLVMOptAggressive
+        }
+    }
+
     pub fn run_passes(sess: Session, llmod: ModuleRef, output: &Path) {
         unsafe {
             let opts = sess.opts;
             if sess.time_llvm_passes() { llvm::LLVMRustEnableTimePasses(); }
+            let whole_program = lto::links_crate_bitcode(sess);
+            if whole_program { lto::link_crate_bitcode(sess, llmod, output); }
             let mut pm = mk_pass_manager();
             let td = mk_target_data(
                 /*bad*/copy sess.targ_cfg.target_strs.data_layout);
@@ -231,54 +303,20 @@ pub mod write {
             // Also: Should we expose and use the pass lists used by the opt
             // tool?
 
-            if opts.optimize != session::No {
-                let fpm = mk_pass_manager();
-                llvm::LLVMAddTargetData(td.lltd, fpm.llpm);
-
-                let FPMB = llvm::LLVMPassManagerBuilderCreate();
-                llvm::LLVMPassManagerBuilderSetOptLevel(FPMB, 2u as c_uint);
-                llvm::LLVMPassManagerBuilderPopulateFunctionPassManager(
-                    FPMB, fpm.llpm);
-                llvm::LLVMPassManagerBuilderDispose(FPMB);
-
-                llvm::LLVMRunPassManager(fpm.llpm, llmod);
-                let mut threshold = 225;
-                if opts.optimize == session::Aggressive { threshold = 275; }
-
-                let MPMB = llvm::LLVMPassManagerBuilderCreate();
-                llvm::LLVMPassManagerBuilderSetOptLevel(MPMB,
-                                                        opts.optimize as
-                                                            c_uint);
-                llvm::LLVMPassManagerBuilderSetSizeLevel(MPMB, False);
-                llvm::LLVMPassManagerBuilderSetDisableUnitAtATime(MPMB,
-                                                                  False);
-                llvm::LLVMPassManagerBuilderSetDisableUnrollLoops(MPMB,
-                                                                  False);
-                llvm::LLVMPassManagerBuilderSetDisableSimplifyLibCalls(MPMB,
-                                                                       False);
-
-                if threshold != 0u {
-                    llvm::LLVMPassManagerBuilderUseInlinerWithThreshold
-                        (MPMB, threshold as c_uint);
-                }
-                llvm::LLVMPassManagerBuilderPopulateModulePassManager(
-                    MPMB, pm.llpm);
-
-                llvm::LLVMPassManagerBuilderDispose(MPMB);
+            // There's no point linking the crates' bitcode only to leave it
+            // unoptimized
+            let optimize = if whole_program && opts.optimize == session::No {
+                session::Default
+            } else {
+                opts.optimize
+            };
+            if optimize != session::No {
+                add_optimization_passes(td.lltd, pm.llpm, llmod, optimize);
             }
+            if whole_program { add_lto_passes(pm.llpm); }
             if !sess.no_verify() { llvm::LLVMAddVerifierPass(pm.llpm); }
             if is_object_or_assembly_or_exe(opts.output_type) || opts.jit {
-                let LLVMOptNone       = 0 as c_int; // -O0
-                let LLVMOptLess       = 1 as c_int; // -O1
-                let LLVMOptDefault    = 2 as c_int; // -O2, -Os
-                let LLVMOptAggressive = 3 as c_int; // -O3
-
-                let mut CodeGenOptLevel = match opts.optimize {
-                  session::No => LLVMOptNone,
-                  session::Less => LLVMOptLess,
-                  session::Default => LLVMOptDefault,
-                  session::Aggressive => LLVMOptAggressive
-                };
+                let mut CodeGenOptLevel = codegen_opt_level(opts.optimize);
 
                 if opts.jit {
                     // If we are using JIT, go ahead and create and
@@ -325,7 +363,7 @@ pub mod write {
                                         buf_o,
                                         lib::llvm::AssemblyFile as c_uint,
                                         CodeGenOptLevel,
-                                        true)
+                                        sess.targ_cfg.morestack)
                                 })
                             });
                     }
@@ -347,7 +385,7 @@ pub mod write {
                                         buf_o,
                                         lib::llvm::ObjectFile as c_uint,
                                         CodeGenOptLevel,
-                                        true)
+                                        sess.targ_cfg.morestack)
                                 })
                             });
                     }
@@ -367,7 +405,7 @@ pub mod write {
                                     buf_o,
                                     FileType as c_uint,
                                     CodeGenOptLevel,
-                                    true)
+                                    sess.targ_cfg.morestack)
                             })
                         });
                 }
@@ -396,6 +434,260 @@ pub mod write {
             if sess.time_llvm_passes() { llvm::LLVMRustPrintPassTimings(); }
         }
     }
+
+    // What a task needs to optimize and emit one codegen unit.
+    struct CodegenUnit {
+        index: uint,
+        // The functions whose definitions the unit keeps
+        functions: ~[~str],
+        bitcode: Path,
+        object: Path,
+        data_layout: ~str,
+        triple: ~str,
+        morestack: bool,
+        optimize: session::OptLevel,
+        verify: bool
+    }
+
+    /**
+     * Like `run_passes`, but splits the module into
+     * `sess.opts.codegen_units` units that are optimized and emitted by
+     * parallel tasks, then combined into the one object file `output`.
+     *
+     * The functions of the crate are dealt out to the units by size. Each
+     * unit reads the whole module into an LLVM context of its own and
+     * drops the bodies of the functions it doesn't own; the first unit
+     * also keeps the data. So that the units can refer to each other,
+     * internal symbols are made external but hidden, and are suffixed with
+     * `crate_hash` so that they don't clash with those of other crates.
+     */
+    pub fn run_passes_in_units(sess: Session, llmod: ModuleRef,
+                               output: &Path, crate_hash: &str) {
+        let opts = sess.opts;
+        if opts.jit || (opts.output_type != output_type_object &&
+                        opts.output_type != output_type_exe) {
+            sess.warn(~"--codegen-units only applies to object files and \
+                        executables; using a single unit");
+            return run_passes(sess, llmod, output);
+        }
+        if lto::links_crate_bitcode(sess) {
+            sess.warn(~"-Z lto optimizes the program as one module; \
+                        ignoring --codegen-units");
+            return run_passes(sess, llmod, output);
+        }
+        // The units share LLVM's global state, which is only guarded by
+        // locks once LLVM has been told that there are several threads
+        if unsafe { llvm::LLVMStartMultithreaded() } != True {
+            sess.warn(~"LLVM was built without thread support; \
+                        ignoring --codegen-units");
+            return run_passes(sess, llmod, output);
+        }
+
+        let bitcode = output.with_filetype("units.bc");
+        let partition = unsafe {
+            let partition = partition_module(llmod, opts.codegen_units,
+                                             crate_hash);
+            str::as_c_str(bitcode.to_str(), |buf| {
+                llvm::LLVMWriteBitcodeToFile(llmod, buf)
+            });
+            llvm::LLVMDisposeModule(llmod);
+            partition
+        };
+
+        let mut results = ~[];
+        let mut objects = ~[];
+        for partition.eachi |i, functions| {
+            let object = output.with_filetype(fmt!("%u.o", i));
+            let unit = CodegenUnit {
+                index: i,
+                functions: copy *functions,
+                bitcode: copy bitcode,
+                object: copy object,
+                data_layout: copy sess.targ_cfg.target_strs.data_layout,
+                triple: copy sess.targ_cfg.target_strs.target_triple,
+                morestack: sess.targ_cfg.morestack,
+                optimize: opts.optimize,
+                verify: !sess.no_verify()
+            };
+            results.push(future::spawn(|move unit| emit_unit(unit)));
+            objects.push(object);
+        }
+        for results.each |result| {
+            match *result.get_ref() {
+              Some(ref msg) => sess.err(copy *msg),
+              None => ()
+            }
+        }
+        sess.abort_if_errors();
+
+        link_objects(sess, objects, ~[], output);
+
+        if !opts.save_temps {
+            for vec::each(vec::append_one(objects, bitcode)) |path| {
+                os::remove_file(path);
+            }
+        }
+    }
+
+    pure fn heavier(a: &(uint, ~str), b: &(uint, ~str)) -> bool {
+        match (a, b) {
+          (&(wa, _), &(wb, _)) => wa >= wb
+        }
+    }
+
+    unsafe fn is_definition(llval: ValueRef) -> bool {
+        llvm::LLVMIsDeclaration(llval) != True
+    }
+
+    unsafe fn has_linkage(llval: ValueRef, linkage: Linkage) -> bool {
+        llvm::LLVMGetLinkage(llval) == linkage as c_uint
+    }
+
+    // Makes a definition with internal linkage visible to the other units.
+    unsafe fn promote(llval: ValueRef, crate_hash: &str, anon: &mut uint) {
+        if !is_definition(llval) ||
+                !(has_linkage(llval, InternalLinkage) ||
+                  has_linkage(llval, PrivateLinkage)) {
+            return;
+        }
+        let mut name = str::raw::from_c_str(llvm::LLVMGetValueName(llval));
+        if name.is_empty() {
+            name = fmt!("anon%u", *anon);
+            *anon += 1;
+        }
+        let name = fmt!("%s.%s", name, crate_hash);
+        str::as_c_str(name, |buf| llvm::LLVMSetValueName(llval, buf));
+        lib::llvm::SetLinkage(llval, ExternalLinkage);
+        llvm::LLVMSetVisibility(llval, HiddenVisibility as c_uint);
+    }
+
+    // Returns the names of the functions that each unit defines.
+    unsafe fn partition_module(llmod: ModuleRef, units: uint,
+                               crate_hash: &str) -> ~[~[~str]] {
+        let mut anon = 0u;
+        let mut llglobal = llvm::LLVMGetFirstGlobal(llmod);
+        while llglobal != ptr::null() {
+            promote(llglobal, crate_hash, &mut anon);
+            llglobal = llvm::LLVMGetNextGlobal(llglobal);
+        }
+
+        // Functions with other linkages, such as `linkonce_odr`, stay in
+        // every unit.
+        let mut functions = ~[];
+        let mut llfn = llvm::LLVMGetFirstFunction(llmod);
+        while llfn != ptr::null() {
+            promote(llfn, crate_hash, &mut anon);
+            if is_definition(llfn) && has_linkage(llfn, ExternalLinkage) {
+                let name = str::raw::from_c_str(llvm::LLVMGetValueName(llfn));
+                let size = llvm::LLVMCountBasicBlocks(llfn) as uint;
+                functions.push((size, name));
+            }
+            llfn = llvm::LLVMGetNextFunction(llfn);
+        }
+
+        // Hand out the biggest functions first, each to the unit with the
+        // least code so far.
+        let functions = sort::merge_sort(functions, heavier);
+        let mut sizes = vec::from_elem(units, 0u);
+        let mut partition = vec::from_fn(units, |_| ~[]);
+        for functions.each |&(size, ref name)| {
+            let mut smallest = 0u;
+            for uint::range(1u, units) |i| {
+                if sizes[i] < sizes[smallest] { smallest = i; }
+            }
+            sizes[smallest] += size;
+            partition[smallest].push(copy *name);
+        }
+        partition
+    }
+
+    // Runs in a task of its own. Returns an error message on failure.
+    fn emit_unit(unit: CodegenUnit) -> Option<~str> {
+        unsafe {
+            let llcx = llvm::LLVMContextCreate();
+            let llbuf = do str::as_c_str(unit.bitcode.to_str()) |buf| {
+                llvm::LLVMRustCreateMemoryBufferWithContentsOfFile(buf)
+            };
+            if llbuf == ptr::null() {
+                llvm::LLVMContextDispose(llcx);
+                return Some(fmt!("failed to read `%s`",
+                                 unit.bitcode.to_str()));
+            }
+            let llmod = llvm::LLVMRustParseBitcodeInContext(llcx, llbuf);
+            llvm::LLVMDisposeMemoryBuffer(llbuf);
+            if llmod == ptr::null() {
+                llvm::LLVMContextDispose(llcx);
+                return Some(fmt!("failed to parse `%s`",
+                                 unit.bitcode.to_str()));
+            }
+
+            let owned = HashMap();
+            for unit.functions.each |name| {
+                owned.insert(copy *name, ());
+            }
+            let mut llfn = llvm::LLVMGetFirstFunction(llmod);
+            while llfn != ptr::null() {
+                if is_definition(llfn) && has_linkage(llfn, ExternalLinkage) {
+                    let name =
+                        str::raw::from_c_str(llvm::LLVMGetValueName(llfn));
+                    if !owned.contains_key_ref(&name) {
+                        llvm::LLVMRustMakeDeclaration(llfn);
+                    }
+                }
+                llfn = llvm::LLVMGetNextFunction(llfn);
+            }
+
+            if unit.index != 0u {
+                // The data, `llvm.used` and the like live in the first unit
+                let mut appending = ~[];
+                let mut llglobal = llvm::LLVMGetFirstGlobal(llmod);
+                while llglobal != ptr::null() {
+                    if has_linkage(llglobal, AppendingLinkage) {
+                        appending.push(llglobal);
+                    } else if is_definition(llglobal) &&
+                            has_linkage(llglobal, ExternalLinkage) {
+                        llvm::LLVMRustMakeDeclaration(llglobal);
+                    }
+                    llglobal = llvm::LLVMGetNextGlobal(llglobal);
+                }
+                for appending.each |&llglobal| {
+                    llvm::LLVMDeleteGlobal(llglobal);
+                }
+            }
+
+            let ok = {
+                let pm = mk_pass_manager();
+                let td = mk_target_data(copy unit.data_layout);
+                llvm::LLVMAddTargetData(td.lltd, pm.llpm);
+                if unit.verify { llvm::LLVMAddVerifierPass(pm.llpm); }
+                if unit.optimize != session::No {
+                    add_optimization_passes(td.lltd, pm.llpm, llmod,
+                                            unit.optimize);
+                }
+                if unit.verify { llvm::LLVMAddVerifierPass(pm.llpm); }
+                do str::as_c_str(unit.triple) |buf_t| {
+                    do str::as_c_str(unit.object.to_str()) |buf_o| {
+                        llvm::LLVMRustWriteOutputFile(
+                            pm.llpm,
+                            llmod,
+                            buf_t,
+                            buf_o,
+                            lib::llvm::ObjectFile as c_uint,
+                            codegen_opt_level(unit.optimize),
+                            unit.morestack)
+                    }
+                }
+            };
+            llvm::LLVMDisposeModule(llmod);
+            llvm::LLVMContextDispose(llcx);
+
+            if ok {
+                None
+            } else {
+                Some(fmt!("could not write `%s`", unit.object.to_str()))
+            }
+        }
+    }
 }
 
 
@@ -700,6 +992,111 @@ pub fn mangle_internal_name_by_seq(ccx: @crate_ctxt, +flav: ~str) -> ~str {
     return fmt!("%s_%u", flav, (ccx.names)(flav).repr);
 }
 
+/**
+ * The readable form of a symbol made by `mangle`, or none if `sym` isn't
+ * one. The segments are joined by `::`, and the hash that
+ * `exported_name` appends, along with the version that follows it, is
+ * shown as a last `h<hash>` segment, as in `vec::push::h4a9e1f0c`.
+ * Characters that `sanitize` replaced aren't restored.
+ */
+pub fn demangle(sym: &str) -> Option<~str> {
+    match demangle_prefix(sym) {
+      Some((ref name, n)) if n == sym.len() => Some(copy *name),
+      _ => None
+    }
+}
+
+/// `text` with every symbol made by `mangle` in it demangled
+pub fn demangle_text(text: &str) -> ~str {
+    let mut result = ~"";
+    let mut start = 0u, i = 0u;
+    while i < text.len() {
+        let boundary = i == 0u || !is_symbol_byte(text[i - 1u]);
+        let demangled = if boundary && text[i] == '_' as u8 {
+            demangle_prefix(str::view(text, i, text.len()))
+        } else {
+            None
+        };
+        match demangled {
+          Some((name, n)) => {
+            str::push_str(&mut result, str::view(text, start, i));
+            str::push_str(&mut result, name);
+            i += n;
+            start = i;
+          }
+          None => i += 1u
+        }
+    }
+    str::push_str(&mut result, str::view(text, start, text.len()));
+    result
+}
+
+// Demangles the symbol that `s` starts with, returning its readable form
+// and its length in `s`
+fn demangle_prefix(s: &str) -> Option<(~str, uint)> {
+    // Symbols on Mac OS X carry an extra underscore
+    let mut i = if str::starts_with(s, "_ZN") {
+        3u
+    } else if str::starts_with(s, "__ZN") {
+        4u
+    } else {
+        return None;
+    };
+
+    let mut segments = ~[];
+    while i < s.len() && s[i] != 'E' as u8 {
+        let mut len = 0u;
+        let digits = i;
+        while i < s.len() && is_digit_byte(s[i]) {
+            len = len * 10u + (s[i] - '0' as u8) as uint;
+            i += 1u;
+        }
+        if i == digits || len == 0u || i + len > s.len() ||
+            !str::is_char_boundary(s, i + len) {
+            return None;
+        }
+        segments.push(str::slice(s, i, i + len));
+        i += len;
+    }
+    if i == s.len() || segments.is_empty() { return None; }
+
+    let n = segments.len();
+    let (names, hash) = if n > 2u && is_hash(segments[n - 2u]) {
+        (vec::slice(segments, 0u, n - 2u), Some(copy segments[n - 2u]))
+    } else if n > 1u && is_hash(segments[n - 1u]) {
+        (vec::slice(segments, 0u, n - 1u), Some(copy segments[n - 1u]))
+    } else {
+        (segments, None)
+    };
+    let mut name = str::connect(names, "::");
+    match hash {
+      Some(hash) => name += ~"::h" + str::slice(hash, 1u, hash.len()),
+      None => ()
+    }
+    Some((name, i + 1u))
+}
+
+// Whether `s` is a segment made by `symbol_hash`: an underscore followed
+// by the hex digits of a 64-bit hash, whose leading zeroes are dropped
+fn is_hash(s: &str) -> bool {
+    s.len() >= 9u && s.len() <= 17u && s[0] == '_' as u8 &&
+        str::all(str::view(s, 1u, s.len()), |c| {
+            (c >= '0' && c <= '9') || (c >= 'a' && c <= 'f')
+        })
+}
+
+pure fn is_digit_byte(b: u8) -> bool {
+    b >= '0' as u8 && b <= '9' as u8
+}
+
+// Whether `b` can be part of a symbol, so that `_ZN` after it isn't the
+// start of one
+pure fn is_symbol_byte(b: u8) -> bool {
+    is_digit_byte(b) || b == '_' as u8 || b == '$' as u8 ||
+        (b >= 'a' as u8 && b <= 'z' as u8) ||
+        (b >= 'A' as u8 && b <= 'Z' as u8)
+}
+
 
 pub fn output_dll_filename(os: session::os, lm: link_meta) -> ~str {
     let libname = fmt!("%s-%s-%s", lm.name, lm.extras_hash, lm.vers);
@@ -716,6 +1113,77 @@ pub fn output_dll_filename(os: session::os, lm: link_meta) -> ~str {
 
 // If the user wants an exe generated we need to invoke
 // cc to link the object file with some libs
+pub fn get_cc_prog(sess: Session) -> ~str {
+    // In the future, FreeBSD will use clang as default compiler.
+    // It would be flexible to use cc (system's default C compiler)
+    // instead of hard-coded gcc.
+    // For win32, there is no cc command,
+    // so we add a condition to make it use gcc.
+    // A target specification file can name its own.
+    match sess.targ_cfg.linker {
+      Some(ref linker) => return copy *linker,
+      None => ()
+    }
+    if sess.targ_cfg.os == session::os_android {
+        ~"arm-linux-androideabi-g++"
+    } else if sess.targ_cfg.os == session::os_win32 { ~"gcc" }
+    else { ~"cc" }
+}
+
+pub fn get_ar_prog(sess: Session) -> ~str {
+    if sess.targ_cfg.os == session::os_android {
+        ~"arm-linux-androideabi-ar"
+    } else {
+        ~"ar"
+    }
+}
+
+// Combines object files into one, with a relocatable link; `libs` are
+// linker arguments naming archives to take any needed members from
+pub fn link_objects(sess: Session, objects: &[Path], libs: &[~str],
+                    output: &Path) {
+    let cc_prog = get_cc_prog(sess);
+    let mut cc_args = ~[~"-nostdlib", ~"-Wl,-r", ~"-o", output.to_str()];
+    for objects.each |object| { cc_args.push(object.to_str()); }
+    cc_args.push_all(libs);
+
+    debug!("%s relocatable link args: %s",
+           cc_prog, str::connect(cc_args, ~" "));
+    let prog = run::program_output(cc_prog, cc_args);
+    if 0 != prog.status {
+        sess.err(fmt!("linking object files with `%s` failed with code %d",
+                      cc_prog, prog.status));
+        sess.note(fmt!("%s arguments: %s",
+                       cc_prog, str::connect(cc_args, ~" ")));
+        sess.note(prog.err + prog.out);
+        sess.abort_if_errors();
+    }
+}
+
+pub fn staticlib_filename(stem: &str) -> ~str {
+    fmt!("lib%s.a", stem)
+}
+
+// The name of the file that `link_binary` produces; libraries get a name
+// that includes their version and metadata hash, except for static ones,
+// which C build systems have to be able to name
+pub fn linked_filename(sess: Session, out_filename: &Path,
+                       lm: link_meta) -> Path {
+    if sess.building_staticlib() {
+        /*bad*/copy *out_filename
+    } else if *sess.building_library {
+        let long_libname = output_dll_filename(sess.targ_cfg.os, lm);
+        debug!("link_meta.name:  %s", lm.name);
+        debug!("long_libname: %s", long_libname);
+        debug!("out_filename: %s", out_filename.to_str());
+        debug!("dirname(out_filename): %s", out_filename.dir_path().to_str());
+
+        out_filename.dir_path().push(long_libname)
+    } else {
+        /*bad*/copy *out_filename
+    }
+}
+
 pub fn link_binary(sess: Session,
                    obj_filename: &Path,
                    out_filename: &Path,
@@ -730,34 +1198,19 @@ pub fn link_binary(sess: Session,
         }
     }
 
-    let output = if *sess.building_library {
-        let long_libname = output_dll_filename(sess.targ_cfg.os, lm);
-        debug!("link_meta.name:  %s", lm.name);
-        debug!("long_libname: %s", long_libname);
-        debug!("out_filename: %s", out_filename.to_str());
-        debug!("dirname(out_filename): %s", out_filename.dir_path().to_str());
-
-        out_filename.dir_path().push(long_libname)
-    } else {
-        /*bad*/copy *out_filename
-    };
-
+    let output = linked_filename(sess, out_filename, lm);
     log(debug, ~"output: " + output.to_str());
 
+    if sess.building_staticlib() {
+        link_staticlib(sess, obj_filename, &output);
+        return;
+    }
+
     // The default library location, we need this to find the runtime.
     // The location of crates will be determined as needed.
     let stage: ~str = ~"-L" + sess.filesearch.get_target_lib_path().to_str();
 
-    // In the future, FreeBSD will use clang as default compiler.
-    // It would be flexible to use cc (system's default C compiler)
-    // instead of hard-coded gcc.
-    // For win32, there is no cc command,
-    // so we add a condition to make it use gcc.
-    let cc_prog: ~str =
-        if sess.targ_cfg.os == session::os_android {
-            ~"arm-linux-androideabi-g++"
-        } else if sess.targ_cfg.os == session::os_win32 { ~"gcc" }
-        else { ~"cc" };
+    let cc_prog = get_cc_prog(sess);
     // The invocations of cc share some flags across platforms
 
     let mut cc_args =
@@ -777,7 +1230,13 @@ pub fn link_binary(sess: Session,
     // # Crate linking
 
     let cstore = sess.cstore;
-    for cstore::get_used_crate_files(cstore).each |cratepath| {
+    // With -Z lto, the code of most crates is in the object file already
+    let crate_files = if lto::links_crate_bitcode(sess) {
+        lto::unmerged_crate_files(sess)
+    } else {
+        cstore::get_used_crate_files(cstore)
+    };
+    for crate_files.each |cratepath| {
         if cratepath.filetype() == Some(~".rlib") {
             cc_args.push(cratepath.to_str());
             loop;
@@ -817,34 +1276,7 @@ pub fn link_binary(sess: Session,
         }
     }
 
-    // Always want the runtime linked in
-    cc_args.push(~"-lrustrt");
-
-    // On linux librt and libdl are an indirect dependencies via rustrt,
-    // and binutils 2.22+ won't add them automatically
-    if sess.targ_cfg.os == session::os_linux {
-        cc_args.push_all(~[~"-lrt", ~"-ldl"]);
-
-        // LLVM implements the `frem` instruction as a call to `fmod`,
-        // which lives in libm. Similar to above, on some linuxes we
-        // have to be explicit about linking to it. See #2510
-        cc_args.push(~"-lm");
-    }
-    else if sess.targ_cfg.os == session::os_android {
-        cc_args.push_all(~[~"-ldl", ~"-llog",  ~"-lsupc++",
-                           ~"-lgnustl_shared"]);
-        cc_args.push(~"-lm");
-    }
-
-    if sess.targ_cfg.os == session::os_freebsd {
-        cc_args.push_all(~[~"-pthread", ~"-lrt",
-                                ~"-L/usr/local/lib", ~"-lexecinfo",
-                                ~"-L/usr/local/lib/gcc46",
-                                ~"-L/usr/local/lib/gcc44", ~"-lstdc++",
-                                ~"-Wl,-z,origin",
-                                ~"-Wl,-rpath,/usr/local/lib/gcc46",
-                                ~"-Wl,-rpath,/usr/local/lib/gcc44"]);
-    }
+    cc_args.push_all(runtime_link_args(sess));
 
     // OS X 10.6 introduced 'compact unwind info', which is produced by the
     // linker from the dwarf unwind info. Unfortunately, it does not seem to
@@ -855,8 +1287,8 @@ pub fn link_binary(sess: Session,
     }
 
     // Stack growth requires statically linking a __morestack function
-    if sess.targ_cfg.os != session::os_android {
-    cc_args.push(~"-lmorestack");
+    if sess.targ_cfg.morestack && sess.targ_cfg.os != session::os_android {
+        cc_args.push(~"-lmorestack");
     }
 
     // FIXME (#2397): At some point we want to rpath our guesses as to where
@@ -864,6 +1296,9 @@ pub fn link_binary(sess: Session,
     cc_args.push_all(rpath::get_rpath_flags(sess, &output));
 
     debug!("%s link args: %s", cc_prog, str::connect(cc_args, ~" "));
+    if sess.print_link_args() {
+        io::println(fmt!("%s %s", cc_prog, str::connect(cc_args, ~" ")));
+    }
     // We run 'cc' here
     let prog = run::program_output(cc_prog, cc_args);
     if 0 != prog.status {
@@ -888,6 +1323,192 @@ pub fn link_binary(sess: Session,
         }
     }
 }
+
+// The arguments that link the runtime, and the system libraries it needs
+fn runtime_link_args(sess: Session) -> ~[~str] {
+    // Always want the runtime linked in
+    let mut args = ~[~"-lrustrt"];
+
+    // On linux librt and libdl are an indirect dependencies via rustrt,
+    // and binutils 2.22+ won't add them automatically
+    if sess.targ_cfg.os == session::os_linux {
+        args.push_all(~[~"-lrt", ~"-ldl"]);
+
+        // LLVM implements the `frem` instruction as a call to `fmod`,
+        // which lives in libm. Similar to above, on some linuxes we
+        // have to be explicit about linking to it. See #2510
+        args.push(~"-lm");
+    }
+    else if sess.targ_cfg.os == session::os_android {
+        args.push_all(~[~"-ldl", ~"-llog",  ~"-lsupc++",
+                        ~"-lgnustl_shared"]);
+        args.push(~"-lm");
+    }
+
+    if sess.targ_cfg.os == session::os_freebsd {
+        args.push_all(~[~"-pthread", ~"-lrt",
+                        ~"-L/usr/local/lib", ~"-lexecinfo",
+                        ~"-L/usr/local/lib/gcc46",
+                        ~"-L/usr/local/lib/gcc44", ~"-lstdc++",
+                        ~"-Wl,-z,origin",
+                        ~"-Wl,-rpath,/usr/local/lib/gcc46",
+                        ~"-Wl,-rpath,/usr/local/lib/gcc44"]);
+    }
+    args
+}
+
+/**
+ * Builds a static library for programs that aren't written in Rust to link
+ * against. The crate's object file, those of the crates it uses, which are
+ * static too, and the `__morestack` glue are combined into one object, so
+ * that the archive works however the program orders its libraries. The
+ * runtime is a shared library, so it's listed along with the other native
+ * libraries that the program has to link.
+ */
+fn link_staticlib(sess: Session, obj_filename: &Path, output: &Path) {
+    let lib_path = sess.filesearch.get_target_lib_path();
+    let cstore = sess.cstore;
+
+    let mut objects = ~[copy *obj_filename];
+    for cstore::get_used_crate_files(cstore).each |cratepath| {
+        if cratepath.filetype() != Some(~".rlib") {
+            sess.err(fmt!("`%s` is a dynamic library, so it can't be \
+                           included in a static library",
+                          cratepath.to_str()));
+        }
+        objects.push(copy *cratepath);
+    }
+    sess.abort_if_errors();
+
+    let mut libs = ~[~"-L" + lib_path.to_str()];
+    // Stack growth requires statically linking a __morestack function
+    if sess.targ_cfg.morestack && sess.targ_cfg.os != session::os_android {
+        libs.push(~"-lmorestack");
+    }
+    let combined = obj_filename.with_filestem(
+        obj_filename.filestem().get() + ~"-all");
+    link_objects(sess, objects, libs, &combined);
+
+    // `ar` adds to an existing archive, which may hold objects from an
+    // earlier build
+    if os::path_exists(output) { os::remove_file(output); }
+    let ar_prog = get_ar_prog(sess);
+    let ar_args = ~[~"crs", output.to_str(), combined.to_str()];
+    debug!("%s args: %s", ar_prog, str::connect(ar_args, ~" "));
+    let prog = run::program_output(ar_prog, ar_args);
+    if 0 != prog.status {
+        sess.err(fmt!("archiving with `%s` failed with code %d",
+                      ar_prog, prog.status));
+        sess.note(fmt!("%s arguments: %s",
+                       ar_prog, str::connect(ar_args, ~" ")));
+        sess.note(prog.err + prog.out);
+        sess.abort_if_errors();
+    }
+
+    let mut native = ~[~"-L" + lib_path.to_str()];
+    for cstore::get_used_libraries(cstore).each |l| {
+        native.push(~"-l" + *l);
+    }
+    native.push_all(cstore::get_used_link_args(cstore));
+    native.push_all(runtime_link_args(sess));
+    sess.note(fmt!("programs that link %s must also be linked with: %s",
+                   output.filename().get(), str::connect(native, ~" ")));
+
+    if !sess.opts.save_temps {
+        for ~[copy *obj_filename, combined].each |path| {
+            if !os::remove_file(path) {
+                sess.warn(fmt!("failed to delete object file `%s`",
+                               path.to_str()));
+            }
+        }
+    }
+}
+#[cfg(test)]
+mod test {
+    use core::prelude::*;
+
+    use back::link::{demangle, demangle_text, linked_filename};
+    use back::link::{output_dll_filename, staticlib_filename};
+    use driver::driver::{build_session, build_session_options, optgroups};
+    use driver::session::Session;
+
+    use std::getopts::groups::getopts;
+    use std::getopts;
+    use syntax::diagnostic;
+
+    fn session_with(args: ~[~str]) -> Session {
+        let matches =
+            &match getopts(args, optgroups()) {
+              Ok(copy m) => m,
+              Err(copy f) => die!(~"session_with: " + getopts::fail_str(f))
+            };
+        let sessopts = build_session_options(
+            ~"rustc", matches, diagnostic::emit);
+        build_session(sessopts, diagnostic::emit)
+    }
+
+    #[test]
+    pub fn test_demangle_exported_name() {
+        assert demangle("_ZN3vec4push17_4a9e1f0c22b7d35a3_06E") ==
+            Some(~"vec::push::h4a9e1f0c22b7d35a");
+        assert demangle("__ZN4main17_4a9e1f0c22b7d35a3_06E") ==
+            Some(~"main::h4a9e1f0c22b7d35a");
+    }
+
+    #[test]
+    pub fn test_demangle_internal_name() {
+        assert demangle("_ZN4glue4drop13_ubox_str_Vec11_9c0f1e2d3bE") ==
+            Some(~"glue::drop::_ubox_str_Vec::h9c0f1e2d3b");
+        assert demangle("_ZN6middle5trans4baseE") ==
+            Some(~"middle::trans::base");
+    }
+
+    #[test]
+    pub fn test_demangle_not_a_symbol() {
+        assert demangle("main").is_none();
+        assert demangle("_ZN").is_none();
+        assert demangle("_ZN3vecE.1").is_none();
+        assert demangle("_ZN5vecE").is_none();
+        assert demangle("_ZN3vec").is_none();
+    }
+
+    #[test]
+    pub fn test_demangle_text() {
+        assert demangle_text(
+            "  12.5%  _ZN3vec4push17_4a9e1f0c22b7d35a3_06E.1 [rustc]") ==
+            ~"  12.5%  vec::push::h4a9e1f0c22b7d35a.1 [rustc]";
+        assert demangle_text("call _ZN3fooE@plt, x_ZN3fooE _ZN9") ==
+            ~"call foo@plt, x_ZN3fooE _ZN9";
+    }
+
+    #[test]
+    pub fn test_staticlib_filename() {
+        assert staticlib_filename("foo") == ~"libfoo.a";
+    }
+
+    // Static libraries keep the name they are given, unlike dynamic ones
+    #[test]
+    pub fn test_linked_filename() {
+        let lm = {name: (~"foo").to_managed(), vers: (~"0.1").to_managed(),
+                  extras_hash: (~"abc").to_managed()};
+
+        let sess = session_with(~[~"--staticlib"]);
+        *sess.building_library = true;
+        assert linked_filename(sess, &Path("out/libfoo.a"), lm).to_str() ==
+            ~"out/libfoo.a";
+
+        let sess = session_with(~[~"--lib"]);
+        *sess.building_library = true;
+        assert linked_filename(sess, &Path("out/foo"), lm).to_str() ==
+            Path("out").push(output_dll_filename(sess.targ_cfg.os,
+                                                 lm)).to_str();
+
+        let sess = session_with(~[~"--bin"]);
+        assert linked_filename(sess, &Path("out/foo"), lm).to_str() ==
+            ~"out/foo";
+    }
+}
+
 //
 // Local Variables:
 // mode: rust
@@ -0,0 +1,181 @@
+// Copyright 2013 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+/*!
+Link-time optimization with `-Z lto`
+
+A library built with `-Z lto` carries the LLVM bitcode of its module in
+its metadata, next to everything else that other crates need to know
+about it. When an executable is built with `-Z lto`, the bitcode of every
+crate it uses is linked into its module before `run_passes` optimizes it,
+so that calls across crates can be inlined like any other. Only `main` is
+left visible outside the module; everything else is made internal, which
+lets LLVM drop what isn't used and change the calling conventions of what
+is. The libraries of those crates are then no longer linked.
+
+A crate that wasn't built with `-Z lto`, as `core` and `std` aren't by
+default, is linked as usual.
+*/
+
+use core::prelude::*;
+
+use back::link;
+use driver::session::Session;
+use lib::llvm::llvm;
+use lib::llvm::{ModuleRef, ValueRef, True};
+use lib;
+use metadata::{cstore, decoder};
+
+use core::io::WriterUtil;
+use core::io;
+use core::libc::c_uint;
+use core::os;
+use core::ptr;
+use core::str;
+
+/// Whether the bitcode of the crates that the output uses is linked into
+/// it, which is the case for executables
+pub fn links_crate_bitcode(sess: Session) -> bool {
+    sess.lto() && !*sess.building_library && !sess.opts.jit
+}
+
+/// The bitcode of `llmod`, for the metadata of a library. It goes through
+/// the file `tmp`, which is removed.
+pub fn module_bitcode(sess: Session, llmod: ModuleRef, tmp: &Path)
+                   -> ~[u8] {
+    unsafe {
+        let status = do str::as_c_str(tmp.to_str()) |buf| {
+            llvm::LLVMWriteBitcodeToFile(llmod, buf)
+        };
+        if status != 0 {
+            sess.fatal(fmt!("failed to write bitcode to `%s`",
+                            tmp.to_str()));
+        }
+    }
+    let bitcode = match io::read_whole_file(tmp) {
+      Ok(bitcode) => bitcode,
+      Err(e) => sess.fatal(fmt!("failed to read bitcode: %s", e))
+    };
+    if !sess.opts.save_temps { os::remove_file(tmp); }
+    bitcode
+}
+
+/**
+ * Links the bitcode of every crate that the session uses and that has
+ * bitcode into `llmod`, then makes everything but the exported symbols
+ * internal. The bitcode goes through a temporary file next to `output`.
+ */
+pub fn link_crate_bitcode(sess: Session, llmod: ModuleRef, output: &Path) {
+    let tmp = output.with_filetype("crate.bc");
+    do cstore::iter_crate_data(sess.cstore) |_cnum, cdata| {
+        // Crates without bitcode are left to `unmerged_crate_files`
+        match decoder::get_bitcode(cdata.data) {
+          Some(bitcode) => unsafe {
+            link_bitcode(sess, llmod, cdata.name, bitcode, &tmp);
+          },
+          None => ()
+        }
+    }
+    if !sess.opts.save_temps { os::remove_file(&tmp); }
+
+    unsafe {
+        let mut llfn = llvm::LLVMGetFirstFunction(llmod);
+        while llfn != ptr::null() {
+            internalize(llfn);
+            llfn = llvm::LLVMGetNextFunction(llfn);
+        }
+        let mut llglobal = llvm::LLVMGetFirstGlobal(llmod);
+        while llglobal != ptr::null() {
+            internalize(llglobal);
+            llglobal = llvm::LLVMGetNextGlobal(llglobal);
+        }
+    }
+}
+
+/// The files of the crates that have to be linked as usual, since their
+/// code isn't in the module
+pub fn unmerged_crate_files(sess: Session) -> ~[Path] {
+    let mut files = ~[];
+    do cstore::iter_crate_data(sess.cstore) |_cnum, cdata| {
+        if decoder::get_bitcode(cdata.data).is_none() {
+            files.push(copy cdata.path);
+        }
+    }
+    files
+}
+
+unsafe fn link_bitcode(sess: Session, llmod: ModuleRef, name: &str,
+                       bitcode: ~[u8], tmp: &Path) {
+    match io::file_writer(tmp, ~[io::Create, io::Truncate]) {
+      Ok(wr) => wr.write(bitcode),
+      Err(e) => sess.fatal(fmt!("failed to write bitcode: %s", e))
+    }
+    let llbuf = do str::as_c_str(tmp.to_str()) |buf| {
+        llvm::LLVMRustCreateMemoryBufferWithContentsOfFile(buf)
+    };
+    if llbuf == ptr::null() {
+        link::llvm_err(sess, fmt!("failed to read the bitcode of `%s`",
+                                  name));
+    }
+    let cratemod = llvm::LLVMRustParseBitcode(llbuf);
+    llvm::LLVMDisposeMemoryBuffer(llbuf);
+    if cratemod == ptr::null() {
+        link::llvm_err(sess, fmt!("failed to parse the bitcode of `%s`",
+                                  name));
+    }
+
+    // Every crate defines some symbols of the same name, like
+    // `rust_abi_version`; the first definition is the one that's kept
+    let mut llfn = llvm::LLVMGetFirstFunction(cratemod);
+    while llfn != ptr::null() {
+        hide_duplicate(llmod, llfn, true);
+        llfn = llvm::LLVMGetNextFunction(llfn);
+    }
+    let mut llglobal = llvm::LLVMGetFirstGlobal(cratemod);
+    while llglobal != ptr::null() {
+        hide_duplicate(llmod, llglobal, false);
+        llglobal = llvm::LLVMGetNextGlobal(llglobal);
+    }
+
+    if !llvm::LLVMRustLinkModules(llmod, cratemod) {
+        link::llvm_err(sess, fmt!("failed to link the bitcode of `%s`",
+                                  name));
+    }
+}
+
+// Makes `llval`, a function or global of a crate's module, internal if
+// `llmod` already has a definition of the same name
+unsafe fn hide_duplicate(llmod: ModuleRef, llval: ValueRef, is_fn: bool) {
+    if !is_external_definition(llval) { return; }
+    let name = llvm::LLVMGetValueName(llval);
+    let existing = if is_fn {
+        llvm::LLVMGetNamedFunction(llmod, name)
+    } else {
+        llvm::LLVMGetNamedGlobal(llmod, name)
+    };
+    if existing != ptr::null() && llvm::LLVMIsDeclaration(existing) != True {
+        lib::llvm::SetLinkage(llval, lib::llvm::InternalLinkage);
+    }
+}
+
+unsafe fn internalize(llval: ValueRef) {
+    if !is_external_definition(llval) { return; }
+    // An executable only has to export its entry point
+    let name = str::raw::from_c_str(llvm::LLVMGetValueName(llval));
+    if name != ~"main" {
+        lib::llvm::SetLinkage(llval, lib::llvm::InternalLinkage);
+    }
+}
+
+unsafe fn is_external_definition(llval: ValueRef) -> bool {
+    llvm::LLVMIsDeclaration(llval) != True &&
+        llvm::LLVMGetLinkage(llval) ==
+            lib::llvm::ExternalLinkage as c_uint
+}
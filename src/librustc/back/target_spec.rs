@@ -0,0 +1,283 @@
+// Copyright 2013 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+/*!
+Target specification files
+
+Besides a target triple, `--target` takes a JSON file that describes the
+target, for targets that the compiler doesn't know about, like boards
+without an operating system or ARM without a floating-point unit:
+
+    {
+        "target-triple": "arm-none-linux-gnueabi",
+        "data-layout": "e-p:32:32:32-i64:64:64-f64:64:64-n32",
+        "target-pointer-width": 32,
+        "os": "linux",
+        "arch": "arm",
+        "linker": "arm-none-linux-gnueabi-gcc",
+        "link-args": ["-marm", "-mfloat-abi=soft"],
+        "morestack": false
+    }
+
+Only the triple, the data layout and the pointer width are required. The
+OS and the architecture, which are one of those that `target_os` and
+`target_arch` can be, default to the ones that the triple names. The
+`target-family` is `windows` on win32 and `unix` elsewhere by default, the
+linker is the one that the OS would use, and `morestack` says whether
+functions check for stack overflow and grow their stack with
+`__morestack`, which is the default.
+*/
+
+use core::prelude::*;
+
+use back::target_strs;
+use driver::driver::{get_arch, get_os};
+use driver::session;
+use metadata::loader::meta_section_name;
+use session::sess_os_to_meta_os;
+
+use core::io;
+use core::str;
+use std::json;
+use syntax::ast;
+
+pub type spec = {
+    target_triple: ~str,
+    data_layout: ~str,
+    pointer_width: uint,
+    os: session::os,
+    arch: session::arch,
+    target_family: ~str,
+    linker: Option<~str>,
+    link_args: ~[~str],
+    morestack: bool
+};
+
+/// Whether the argument of `--target` names a target specification file
+/// rather than a target triple
+pub fn is_target_spec(target: &str) -> bool {
+    str::ends_with(target, ".json")
+}
+
+pub fn read_target_spec(path: &Path) -> Result<spec, ~str> {
+    match io::read_whole_file_str(path) {
+      Ok(move src) => parse_target_spec(src),
+      Err(move e) => Err(e)
+    }
+}
+
+pub fn parse_target_spec(src: &str) -> Result<spec, ~str> {
+    let obj = match json::from_str(src) {
+      Ok(json::Object(move obj)) => obj,
+      Ok(_) => return Err(~"expected a JSON object"),
+      Err(e) => return Err(fmt!("%u:%u: %s", e.line, e.col, *e.msg))
+    };
+    for obj.each_key |key| {
+        if !vec::contains(known_keys(), key) {
+            return Err(fmt!("unknown key `%s`", *key));
+        }
+    }
+
+    let target_triple = match get_str(obj, ~"target-triple") {
+      Ok(Some(move s)) => s,
+      Ok(None) => return Err(~"missing `target-triple`"),
+      Err(move e) => return Err(e)
+    };
+    let data_layout = match get_str(obj, ~"data-layout") {
+      Ok(Some(move s)) => s,
+      Ok(None) => return Err(~"missing `data-layout`"),
+      Err(move e) => return Err(e)
+    };
+    let pointer_width = match obj.find(&~"target-pointer-width") {
+      Some(&json::Number(n)) if n == 32f => 32u,
+      Some(&json::Number(n)) if n == 64f => 64u,
+      Some(_) => return Err(~"`target-pointer-width` must be 32 or 64"),
+      None => return Err(~"missing `target-pointer-width`")
+    };
+    let os = match get_str(obj, ~"os") {
+      Ok(Some(move s)) => match os_from_str(copy s) {
+        Some(os) => os,
+        None => return Err(fmt!("unknown os `%s`", s))
+      },
+      Ok(None) => match get_os(copy target_triple) {
+        Some(os) => os,
+        None => return Err(~"missing `os`, which the triple doesn't name")
+      },
+      Err(move e) => return Err(e)
+    };
+    let arch = match get_str(obj, ~"arch") {
+      Ok(Some(move s)) => match arch_from_str(copy s) {
+        Some(arch) => arch,
+        None => return Err(fmt!("unknown arch `%s`", s))
+      },
+      Ok(None) => match get_arch(copy target_triple) {
+        Some(arch) => arch,
+        None => return Err(~"missing `arch`, which the triple doesn't name")
+      },
+      Err(move e) => return Err(e)
+    };
+    let target_family = match get_str(obj, ~"target-family") {
+      Ok(Some(move s)) => s,
+      Ok(None) => default_target_family(os),
+      Err(move e) => return Err(e)
+    };
+    let linker = match get_str(obj, ~"linker") {
+      Ok(move linker) => linker,
+      Err(move e) => return Err(e)
+    };
+    let link_args = match obj.find(&~"link-args") {
+      Some(&json::List(ref args)) => {
+        let mut link_args = ~[];
+        for args.each |arg| {
+            match *arg {
+              json::String(ref s) => link_args.push(copy *s),
+              _ => return Err(~"`link-args` must be a list of strings")
+            }
+        }
+        link_args
+      }
+      Some(_) => return Err(~"`link-args` must be a list of strings"),
+      None => ~[]
+    };
+    let morestack = match obj.find(&~"morestack") {
+      Some(&json::Boolean(b)) => b,
+      Some(_) => return Err(~"`morestack` must be true or false"),
+      None => true
+    };
+
+    Ok({target_triple: target_triple,
+        data_layout: data_layout,
+        pointer_width: pointer_width,
+        os: os,
+        arch: arch,
+        target_family: target_family,
+        linker: linker,
+        link_args: link_args,
+        morestack: morestack})
+}
+
+/// The configuration of a session that compiles for the target `spec`
+pub fn target_config(spec: &spec) -> @session::config {
+    let (int_type, uint_type) = if spec.pointer_width == 64u {
+        (ast::ty_i64, ast::ty_u64)
+    } else {
+        (ast::ty_i32, ast::ty_u32)
+    };
+    let target_strs: target_strs::t = {
+        module_asm: ~"",
+        meta_sect_name: meta_section_name(sess_os_to_meta_os(spec.os)),
+        data_layout: copy spec.data_layout,
+        target_triple: copy spec.target_triple,
+        cc_args: copy spec.link_args
+    };
+    @{os: spec.os, arch: spec.arch, target_strs: target_strs,
+      int_type: int_type, uint_type: uint_type, float_type: ast::ty_f64,
+      target_family: copy spec.target_family, linker: copy spec.linker,
+      morestack: spec.morestack}
+}
+
+/// The value of `target_family` for a target that doesn't say
+pub fn default_target_family(os: session::os) -> ~str {
+    match os {
+      session::os_win32 => ~"windows",
+      _ => ~"unix"
+    }
+}
+
+fn known_keys() -> ~[~str] {
+    ~[~"target-triple", ~"data-layout", ~"target-pointer-width", ~"os",
+      ~"arch", ~"target-family", ~"linker", ~"link-args", ~"morestack"]
+}
+
+fn get_str(obj: &json::Object, key: ~str) -> Result<Option<~str>, ~str> {
+    match obj.find(&key) {
+      Some(&json::String(ref s)) => Ok(Some(copy *s)),
+      Some(_) => Err(fmt!("`%s` must be a string", key)),
+      None => Ok(None)
+    }
+}
+
+fn os_from_str(s: ~str) -> Option<session::os> {
+    match s {
+      ~"win32" => Some(session::os_win32),
+      ~"macos" => Some(session::os_macos),
+      ~"linux" => Some(session::os_linux),
+      ~"android" => Some(session::os_android),
+      ~"freebsd" => Some(session::os_freebsd),
+      _ => None
+    }
+}
+
+fn arch_from_str(s: ~str) -> Option<session::arch> {
+    match s {
+      ~"x86" => Some(session::arch_x86),
+      ~"x86_64" => Some(session::arch_x86_64),
+      ~"arm" => Some(session::arch_arm),
+      _ => None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use core::prelude::*;
+
+    use back::target_spec::parse_target_spec;
+    use driver::session;
+
+    #[test]
+    fn test_minimal_spec() {
+        let spec = parse_target_spec(
+            "{\"target-triple\": \"arm-unknown-linux\",
+              \"data-layout\": \"e-p:32:32:32-n32\",
+              \"target-pointer-width\": 32}").get();
+        assert spec.os == session::os_linux;
+        assert spec.arch == session::arch_arm;
+        assert spec.target_family == ~"unix";
+        assert spec.linker.is_none();
+        assert spec.link_args.is_empty();
+        assert spec.morestack;
+    }
+
+    #[test]
+    fn test_full_spec() {
+        let spec = parse_target_spec(
+            "{\"target-triple\": \"arm-none-eabi\",
+              \"data-layout\": \"e-p:32:32:32-n32\",
+              \"target-pointer-width\": 32,
+              \"os\": \"linux\",
+              \"arch\": \"arm\",
+              \"linker\": \"arm-none-eabi-gcc\",
+              \"link-args\": [\"-mfloat-abi=soft\"],
+              \"morestack\": false}").get();
+        assert spec.os == session::os_linux;
+        assert spec.linker == Some(~"arm-none-eabi-gcc");
+        assert spec.link_args == ~[~"-mfloat-abi=soft"];
+        assert !spec.morestack;
+    }
+
+    #[test]
+    fn test_bad_specs() {
+        // The OS can't be guessed from this triple
+        assert parse_target_spec(
+            "{\"target-triple\": \"arm-none-eabi\",
+              \"data-layout\": \"e\",
+              \"target-pointer-width\": 32}").is_err();
+        assert parse_target_spec(
+            "{\"target-triple\": \"x86_64-unknown-linux-gnu\",
+              \"data-layout\": \"e\",
+              \"target-pointer-width\": 48}").is_err();
+        assert parse_target_spec(
+            "{\"target-triple\": \"x86_64-unknown-linux-gnu\",
+              \"data-layout\": \"e\",
+              \"target-pointer-width\": 64,
+              \"stack-size\": 4096}").is_err();
+        assert parse_target_spec("[]").is_err();
+    }
+}
@@ -11,8 +11,9 @@
 use core::prelude::*;
 
 use back::link;
-use back::{arm, x86, x86_64};
+use back::{arm, target_spec, x86, x86_64};
 use front;
+use lib::dl;
 use lib::llvm::llvm;
 use metadata::{creader, cstore, filesearch};
 use metadata;
@@ -22,14 +23,19 @@ use session::{Session, Session_, OptLevel, No, Less, Default, Aggressive};
 use session;
 use util::ppaux;
 
+use core::cast;
 use core::cmp;
 use core::int;
 use core::io::WriterUtil;
 use core::io;
+use core::libc::c_void;
+use core::managed;
 use core::option;
 use core::os;
+use core::ptr;
 use core::result::{Ok, Err};
 use core::str;
+use core::uint;
 use core::vec;
 use std::getopts::groups::{optopt, optmulti, optflag, optflagopt, getopts};
 use std::getopts::groups;
@@ -42,6 +48,8 @@ use syntax::ast_map;
 use syntax::attr;
 use syntax::codemap;
 use syntax::diagnostic;
+use syntax::ext::base::{ext_ctxt, MacroDef, SyntaxExtensionRegistrar};
+use syntax::ext::expand;
 use syntax::parse;
 use syntax::print::{pp, pprust};
 use syntax;
@@ -88,16 +96,21 @@ pub fn default_configuration(sess: Session, +argv0: ~str, input: input) ->
 
     let mk = attr::mk_name_value_item_str;
 
-    let (arch,wordsz) = match sess.targ_cfg.arch {
-      session::arch_x86 => (~"x86",~"32"),
-      session::arch_x86_64 => (~"x86_64",~"64"),
-      session::arch_arm => (~"arm",~"32")
+    let arch = match sess.targ_cfg.arch {
+      session::arch_x86 => ~"x86",
+      session::arch_x86_64 => ~"x86_64",
+      session::arch_arm => ~"arm"
     };
+    let wordsz = match sess.targ_cfg.int_type {
+      ast::ty_i64 => ~"64",
+      _ => ~"32"
+    };
+    let family = copy sess.targ_cfg.target_family;
 
     return ~[ // Target bindings.
-         attr::mk_word_item(str::from_slice(os::FAMILY)),
+         attr::mk_word_item(copy family),
          mk(~"target_os", tos),
-         mk(~"target_family", str::from_slice(os::FAMILY)),
+         mk(~"target_family", family),
          mk(~"target_arch", arch),
          mk(~"target_word_size", wordsz),
          mk(~"target_libc", libc),
@@ -190,6 +203,55 @@ pub impl compile_upto : cmp::Eq {
     pure fn ne(&self, other: &compile_upto) -> bool { !(*self).eq(other) }
 }
 
+// Collects the macros, and for a #[syntax_extension] crate the syntax
+// extensions, that the crate named by an `extern mod` provides to the
+// expander.
+pub fn load_extern_syntax(sess: Session, cx: ext_ctxt,
+                          vi: @ast::view_item) -> ~[MacroDef] {
+    let mc = match creader::read_macro_crate(
+        sess.diagnostic(), sess.filesearch,
+        session::sess_os_to_meta_os(sess.targ_cfg.os),
+        sess.opts.static, sess.parse_sess.interner, vi) {
+        Some(mc) => mc,
+        None => return ~[]
+    };
+
+    let mut defs = do mc.exported_macros.map |m| {
+        let (name, tts) = /*bad*/copy *m;
+        expand::load_exported_macro(cx, vi.span, name, tts)
+    };
+
+    match copy mc.registrar {
+      Some(sym) => {
+        let lib = match dl::open(&mc.path) {
+          Ok(lib) => lib,
+          Err(e) => sess.span_fatal(vi.span,
+                                    fmt!("couldn't load syntax extension \
+                                          crate `%s`: %s",
+                                         mc.path.to_str(), e))
+        };
+        let code = match dl::symbol(lib, sym) {
+          Ok(code) => code,
+          Err(e) => sess.span_fatal(vi.span,
+                                    fmt!("couldn't find the registrar of \
+                                          syntax extension crate `%s`: %s",
+                                         mc.path.to_str(), e))
+        };
+        // The registrar is a bare Rust function, so it can be called
+        // through a closure whose environment is null.
+        let registrar: SyntaxExtensionRegistrar = unsafe {
+            cast::transmute((code, ptr::null::<c_void>()))
+        };
+        let registered = @mut ~[];
+        registrar(|name, ext| registered.push(MacroDef {name: name,
+                                                        ext: ext}));
+        defs.push_all_move(/*bad*/copy *registered);
+      }
+      None => ()
+    }
+    defs
+}
+
 pub fn compile_upto(sess: Session, cfg: ast::crate_cfg,
                     input: input, upto: compile_upto,
                     outputs: Option<output_filenames>)
@@ -210,6 +272,9 @@ pub fn compile_upto(sess: Session, cfg: ast::crate_cfg,
 
     crate = time(time_passes, ~"expansion", ||
         syntax::ext::expand::expand_crate(sess.parse_sess, copy cfg,
+                                          |cx, vi| load_extern_syntax(sess,
+                                                                      cx, vi),
+                                          sess.trace_expansion(),
                                           crate));
 
     if upto == cu_expand { return {crate: crate, tcx: None}; }
@@ -242,6 +307,21 @@ pub fn compile_upto(sess: Session, cfg: ast::crate_cfg,
         time(time_passes, ~"resolution", ||
              middle::resolve::resolve_crate(sess, lang_items, crate));
 
+    let incremental = time(time_passes, ~"incremental cache lookup", ||
+        middle::incremental::load(sess, crate, def_map));
+
+    // Stubbing out the reused functions changes the crate, so the AST map
+    // has to be built again. The stubs keep the IDs of the functions they
+    // replace, and with them their lint settings.
+    let ast_map = match incremental {
+        Some(cache) if cache.has_reused_items() => {
+            crate = time(time_passes, ~"stubbing reused functions", ||
+                middle::incremental::stub_reused_items(cache, sess, crate));
+            syntax::ast_map::map_crate(sess.diagnostic(), *crate)
+        }
+        _ => ast_map
+    };
+
     let freevars = time(time_passes, ~"freevar finding", ||
         freevars::annotate_freevars(def_map, crate));
 
@@ -265,6 +345,14 @@ pub fn compile_upto(sess: Session, cfg: ast::crate_cfg,
                                      trait_map,
                                      crate));
 
+        match incremental {
+            Some(cache) => {
+                middle::incremental::record_method_deps(cache, method_map,
+                                                        vtable_map);
+            }
+            None => {}
+        }
+
         // These next two const passes can probably be merged
         time(time_passes, ~"const marking", ||
              middle::const_eval::process_crate(crate, def_map, ty_cx));
@@ -306,7 +394,17 @@ pub fn compile_upto(sess: Session, cfg: ast::crate_cfg,
              kind::check_crate(ty_cx, method_map, last_use_map, crate));
 
         time(time_passes, ~"lint checking", ||
-             lint::check_crate(ty_cx, crate));
+             lint::check_crate(ty_cx, method_map, crate));
+
+        // The stubs of reused functions no longer refer to anything, so
+        // the cache says what they did
+        let stubbed_uses = match incremental {
+            Some(cache) => middle::incremental::stubbed_uses(cache),
+            None => HashMap()
+        };
+        time(time_passes, ~"dead code checking", ||
+             middle::dead::check_crate(ty_cx, method_map, exp_map2,
+                                       stubbed_uses, crate));
 
         if upto == cu_no_trans { return {crate: crate, tcx: Some(ty_cx)}; }
 
@@ -324,30 +422,95 @@ pub fn compile_upto(sess: Session, cfg: ast::crate_cfg,
         time(time_passes, ~"translation", ||
              trans::base::trans_crate(sess, crate, ty_cx,
                                       &outputs.obj_filename,
-                                      exp_map2, maps))
+                                      exp_map2, maps, incremental))
 
     };
 
 
     time(time_passes, ~"LLVM passes", ||
-        link::write::run_passes(sess, llmod,
-                                &outputs.obj_filename));
+        if sess.opts.codegen_units > 1u {
+            link::write::run_passes_in_units(sess, llmod,
+                                             &outputs.obj_filename,
+                                             link_meta.extras_hash)
+        } else {
+            link::write::run_passes(sess, llmod, &outputs.obj_filename)
+        });
 
     let stop_after_codegen =
         sess.opts.output_type != link::output_type_exe ||
-        (sess.opts.static && *sess.building_library)   ||
+        (sess.opts.static && *sess.building_library &&
+         !sess.building_staticlib()) ||
         sess.opts.jit;
 
-    if stop_after_codegen { return {crate: crate, tcx: None}; }
+    if stop_after_codegen {
+        write_dep_info(sess, outputs, &outputs.obj_filename);
+        return {crate: crate, tcx: None};
+    }
 
     time(time_passes, ~"linking", ||
          link::link_binary(sess,
                            &outputs.obj_filename,
                            &outputs.out_filename, link_meta));
 
+    write_dep_info(sess, outputs,
+                   &link::linked_filename(sess, &outputs.out_filename,
+                                          link_meta));
     return {crate: crate, tcx: None};
 }
 
+// Writes, if asked to, a Makefile rule saying that `output` depends on
+// every source file the crate was read from and on every crate it links
+// against.
+fn write_dep_info(sess: Session, outputs: output_filenames, output: &Path) {
+    let deps_filename = match sess.opts.write_dependency_info {
+        (false, _) => return,
+        (true, Some(ref path)) => copy *path,
+        (true, None) => outputs.out_filename.with_filetype("d")
+    };
+
+    let mut deps = ~[];
+    for sess.codemap.files.get().each |fm| {
+        // Skip sources that don't come from a file, such as quotes
+        match fm.substr {
+            codemap::FssNone if !fm.name.starts_with("<") => {
+                if !deps.contains(&fm.name) { deps.push(copy fm.name); }
+            }
+            _ => ()
+        }
+    }
+    // Files read by include_str! and the like
+    for sess.parse_sess.included_files.each |path| {
+        let path = path.to_str();
+        if !deps.contains(&path) { deps.push(path); }
+    }
+    for cstore::get_used_crate_files(sess.cstore).each |path| {
+        let path = path.to_str();
+        if !deps.contains(&path) { deps.push(path); }
+    }
+
+    // Make splits words on spaces, expands variables starting with `$` and
+    // treats `#` as the start of a comment
+    fn escape(s: &str) -> ~str {
+        let s = str::replace(s, "$", "$$");
+        let s = str::replace(s, "#", "\\#");
+        str::replace(s, " ", "\\ ")
+    }
+
+    match io::file_writer(&deps_filename, ~[io::Create, io::Truncate]) {
+        Ok(writer) => {
+            writer.write_str(fmt!("%s:", escape(output.to_str())));
+            for deps.each |dep| {
+                writer.write_str(fmt!(" \\\n    %s", escape(*dep)));
+            }
+            writer.write_str("\n");
+        }
+        Err(e) => {
+            sess.fatal(fmt!("error writing dependency info to `%s`: %s",
+                            deps_filename.to_str(), e));
+        }
+    }
+}
+
 pub fn compile_input(sess: Session, +cfg: ast::crate_cfg, input: input,
                      outdir: &Option<Path>, output: &Option<Path>) {
 
@@ -378,6 +541,39 @@ pub fn pretty_print_input(sess: Session, +cfg: ast::crate_cfg, input: input,
           _ => ()
         }
     }
+    // The expansions that produced the nodes being printed, outermost
+    // first, so that a node is only annotated where it differs from its
+    // parent's
+    fn ann_expansion_pre(stack: @mut ~[Option<@codemap::ExpnInfo>],
+                         node: pprust::ann_node) {
+        let (_, sp) = ann_node_span(node);
+        stack.push(sp.expn_info);
+    }
+    fn ann_expansion_post(cm: @codemap::CodeMap,
+                          stack: @mut ~[Option<@codemap::ExpnInfo>],
+                          node: pprust::ann_node) {
+        let (s, sp) = ann_node_span(node);
+        stack.pop();
+        let parent = if stack.is_empty() { None } else { stack.last() };
+        match (sp.expn_info, parent) {
+          (Some(ei), Some(pei)) if managed::ptr_eq(ei, pei) => (),
+          (Some(ei), _) => {
+            pp::space(s.s);
+            pprust::synth_comment(s, ~"expanded from " +
+                                     cm.expn_info_to_str(ei));
+          }
+          (None, _) => ()
+        }
+    }
+    fn ann_node_span(node: pprust::ann_node)
+                  -> (@pprust::ps, codemap::span) {
+        match node {
+          pprust::node_item(s, item) => (s, item.span),
+          pprust::node_block(s, ref blk) => (s, (*blk).span),
+          pprust::node_expr(s, expr) => (s, expr.span),
+          pprust::node_pat(s, pat) => (s, pat.span)
+        }
+    }
     fn ann_identified_post(node: pprust::ann_node) {
         match node {
           pprust::node_item(s, item) => {
@@ -417,10 +613,24 @@ pub fn pretty_print_input(sess: Session, +cfg: ast::crate_cfg, input: input,
           pprust::pp_ann {pre: ann_paren_for_expr,
                           post: |a| ann_typed_post(tcx.get(), a) }
       }
-      ppm_identified | ppm_expanded_identified => {
+      ppm_identified => {
           pprust::pp_ann {pre: ann_paren_for_expr,
                           post: ann_identified_post}
       }
+      ppm_expanded_identified => {
+          let cm = sess.codemap;
+          let stack = @mut ~[];
+          pprust::pp_ann {
+              pre: |a| {
+                  ann_paren_for_expr(a);
+                  ann_expansion_pre(stack, a);
+              },
+              post: |a| {
+                  ann_expansion_post(cm, stack, a);
+                  ann_identified_post(a);
+              }
+          }
+      }
       ppm_expanded | ppm_normal => {
           pprust::no_ann()
       }
@@ -468,6 +678,10 @@ pub fn get_arch(triple: ~str) -> Option<session::arch> {
 pub fn build_target_config(sopts: @session::options,
                            demitter: diagnostic::Emitter)
                         -> @session::config {
+    match sopts.target_spec {
+      Some(ref spec) => return target_spec::target_config(spec),
+      None => ()
+    }
     let os = match get_os(sopts.target_triple) {
       Some(os) => os,
       None => early_error(demitter, ~"unknown operating system")
@@ -489,7 +703,9 @@ pub fn build_target_config(sopts: @session::options,
     };
     let target_cfg: @session::config =
         @{os: os, arch: arch, target_strs: target_strs, int_type: int_type,
-          uint_type: uint_type, float_type: float_type};
+          uint_type: uint_type, float_type: float_type,
+          target_family: target_spec::default_target_family(os),
+          linker: None, morestack: true};
     return target_cfg;
 }
 
@@ -514,8 +730,14 @@ pub fn build_session_options(+binary: ~str,
                              matches: &getopts::Matches,
                              demitter: diagnostic::Emitter)
                           -> @session::options {
+    if opt_present(matches, ~"lib") && opt_present(matches, ~"staticlib") {
+        early_error(demitter, ~"--lib and --staticlib can't be used \
+                                together");
+    }
     let crate_type = if opt_present(matches, ~"lib") {
         session::lib_crate
+    } else if opt_present(matches, ~"staticlib") {
+        session::staticlib_crate
     } else if opt_present(matches, ~"bin") {
         session::bin_crate
     } else {
@@ -528,6 +750,35 @@ pub fn build_session_options(+binary: ~str,
                        lint::deny, lint::forbid];
     let mut lint_opts = ~[];
     let lint_dict = lint::get_lint_dict();
+    let lint_groups = lint::get_lint_groups();
+
+    // The levels from a lint config file come first, so that the command
+    // line overrides them
+    match getopts::opt_maybe_str(matches, ~"lint-config") {
+      Some(file) => {
+        match lint::read_lint_config(&Path(file)) {
+          Ok(levels) => {
+            for levels.each |&(ref lint_name, level)| {
+                match lint::find_lints(lint_dict, lint_groups, *lint_name) {
+                  None => {
+                    early_error(demitter, fmt!("unknown lint in `%s`: %s",
+                                               file, *lint_name));
+                  }
+                  Some(lints) => {
+                    for lints.each |&lint| { lint_opts.push((lint, level)); }
+                  }
+                }
+            }
+          }
+          Err(e) => {
+            early_error(demitter, fmt!("error reading lint config `%s`: %s",
+                                       file, e));
+          }
+        }
+      }
+      None => ()
+    }
+
     for lint_levels.each |level| {
         let level_name = lint::level_to_str(*level);
         let level_short = level_name.substr(0,1).to_upper();
@@ -535,13 +786,13 @@ pub fn build_session_options(+binary: ~str,
                                 getopts::opt_strs(matches, level_name));
         for flags.each |lint_name| {
             let lint_name = str::replace(*lint_name, ~"-", ~"_");
-            match lint_dict.find(&lint_name) {
+            match lint::find_lints(lint_dict, lint_groups, lint_name) {
               None => {
                 early_error(demitter, fmt!("unknown %s flag: %s",
                                            level_name, lint_name));
               }
-              Some(lint) => {
-                lint_opts.push((lint.lint, *level));
+              Some(lints) => {
+                for lints.each |&lint| { lint_opts.push((lint, *level)); }
               }
             }
         }
@@ -610,16 +861,41 @@ pub fn build_session_options(+binary: ~str,
             }
         } else { No }
     };
+    let codegen_units = match getopts::opt_maybe_str(matches,
+                                                     ~"codegen-units") {
+      None => 1u,
+      Some(n) => match uint::from_str(n) {
+        Some(n) if n > 0u => n,
+        _ => {
+            early_error(demitter, ~"argument to `--codegen-units` must be \
+                                    a positive integer")
+        }
+      }
+    };
     let gc = debugging_opts & session::gc != 0;
     let jit = debugging_opts & session::jit != 0;
-    let extra_debuginfo = debugging_opts & session::extra_debug_info != 0;
+    // -g describes everything that the debug info can describe
+    let extra_debuginfo = debugging_opts & session::extra_debug_info != 0 ||
+        opt_present(matches, ~"g");
     let debuginfo = debugging_opts & session::debug_info != 0 ||
         extra_debuginfo;
-    let static = debugging_opts & session::static != 0;
-    let target =
+    // A static library can only be made from crates that are static too
+    let static = debugging_opts & session::static != 0 ||
+        crate_type == session::staticlib_crate;
+    // A target given by a file is described by it, triple and all
+    let (target, target_spec) =
         match target_opt {
-            None => host_triple(),
-            Some(ref s) => (/*bad*/copy *s)
+            None => (host_triple(), None),
+            Some(ref s) if target_spec::is_target_spec(*s) => {
+                match target_spec::read_target_spec(&Path(*s)) {
+                  Ok(spec) => (copy spec.target_triple, Some(spec)),
+                  Err(e) => {
+                    early_error(demitter, fmt!("can't load target `%s`: %s",
+                                               *s, e))
+                  }
+                }
+            }
+            Some(ref s) => ((/*bad*/copy *s), None)
         };
 
     let addl_lib_search_paths =
@@ -627,6 +903,22 @@ pub fn build_session_options(+binary: ~str,
         .map(|s| Path(*s));
     let cfg = parse_cfgspecs(getopts::opt_strs(matches, ~"cfg"));
     let test = opt_present(matches, ~"test");
+    let incremental = getopts::opt_maybe_str(matches, ~"incremental")
+        .map(|s| Path(*s));
+    // A library's bitcode is taken before the cached code is linked in
+    if incremental.is_some() && debugging_opts & session::lto != 0 {
+        early_error(demitter, ~"-Z lto can't be used with --incremental");
+    }
+    let write_dependency_info =
+        (opt_present(matches, ~"dep-info"),
+         getopts::opt_maybe_str(matches, ~"dep-info").map(|s| Path(*s)));
+    let error_format = match parse_error_format(matches) {
+      Some(format) => format,
+      None => {
+        early_error(demitter, ~"argument to `error-format` must be one of \
+                               `human` or `json`")
+      }
+    };
     let sopts: @session::options =
         @{crate_type: crate_type,
           static: static,
@@ -641,15 +933,30 @@ pub fn build_session_options(+binary: ~str,
           addl_lib_search_paths: addl_lib_search_paths,
           maybe_sysroot: sysroot_opt,
           target_triple: target,
+          target_spec: target_spec,
           cfg: cfg,
           binary: binary,
           test: test,
           parse_only: parse_only,
           no_trans: no_trans,
-          debugging_opts: debugging_opts};
+          debugging_opts: debugging_opts,
+          error_format: error_format,
+          incremental: incremental,
+          codegen_units: codegen_units,
+          write_dependency_info: write_dependency_info};
     return sopts;
 }
 
+/// Reads `--error-format`, returning None if its argument is not recognized
+pub fn parse_error_format(matches: &getopts::Matches)
+                       -> Option<diagnostic::ErrorFormat> {
+    match getopts::opt_maybe_str(matches, ~"error-format") {
+      None | Some(~"human") => Some(diagnostic::HumanReadable),
+      Some(~"json") => Some(diagnostic::Json),
+      Some(_) => None
+    }
+}
+
 pub fn build_session(sopts: @session::options,
                      demitter: diagnostic::Emitter) -> Session {
     let codemap = @codemap::CodeMap::new();
@@ -712,12 +1019,32 @@ pub fn optgroups() -> ~[getopts::groups::OptGroup] {
   optflag(~"c", ~"",    ~"Compile and assemble, but do not link"),
   optmulti(~"", ~"cfg", ~"Configure the compilation
                           environment", ~"SPEC"),
+  optopt(~"", ~"codegen-units",
+                        ~"Optimize and emit code in N parallel units", ~"N"),
+  optflagopt(~"", ~"dep-info",
+                        ~"Output dependency info to <filename> after
+                          compiling, in a format suitable for use by
+                          Makefiles",
+                          ~"FILENAME"),
   optflag(~"",  ~"emit-llvm",
                         ~"Produce an LLVM bitcode file"),
+  optopt(~"", ~"error-format",
+                        ~"How errors and other messages are produced;
+                          valid formats are: human (the default)
+                          or json (one JSON object per line)", ~"FORMAT"),
+  optflag(~"g", ~"",    ~"Produce debug info for debuggers like gdb"),
   optflag(~"h", ~"help",~"Display this message"),
+  optopt(~"", ~"incremental",
+                        ~"Reuse unchanged functions from, and save the
+                          build to, the cache in <dir> (lints and warnings
+                          are not reported again for reused functions)",
+                          ~"DIR"),
   optmulti(~"L", ~"",   ~"Add a directory to the library search path",
                               ~"PATH"),
   optflag(~"",  ~"lib", ~"Compile a library crate"),
+  optopt(~"", ~"lint-config",
+                        ~"Read lint levels from a JSON or `name = level`
+                          file; command-line flags override it", ~"FILE"),
   optflag(~"",  ~"ls",  ~"List the symbols defined by a library crate"),
   optflag(~"", ~"no-trans",
                         ~"Run all passes except translation; no output"),
@@ -735,12 +1062,18 @@ pub fn optgroups() -> ~[getopts::groups::OptGroup] {
                           valid types are: normal (un-annotated source),
                           expanded (crates expanded),
                           typed (crates expanded, with type annotations),
-                          or identified (fully parenthesized,
-                          AST nodes and blocks with IDs)", ~"TYPE"),
+                          identified (fully parenthesized,
+                          AST nodes and blocks with IDs),
+                          or expanded,identified (identified after
+                          expansion, with the macro call each expanded
+                          node came from)", ~"TYPE"),
   optflag(~"S", ~"",    ~"Compile only; do not assemble or link"),
   optflag(~"", ~"save-temps",
                         ~"Write intermediate files (.bc, .opt.bc, .o)
                           in addition to normal output"),
+  optflag(~"", ~"staticlib",
+                        ~"Compile a static library (.a) that includes the
+                          crates it uses, for linking into C programs"),
   optopt(~"", ~"sysroot",
                         ~"Override the system root", ~"PATH"),
   optflag(~"", ~"test", ~"Build a test harness"),
@@ -748,7 +1081,8 @@ pub fn optgroups() -> ~[getopts::groups::OptGroup] {
                         ~"Target triple cpu-manufacturer-kernel[-os]
                           to compile for (see
          http://sources.redhat.com/autobook/autobook/autobook_17.html
-                          for detail)", ~"TRIPLE"),
+                          for detail), or a JSON file describing the
+                          target", ~"TRIPLE"),
   optmulti(~"W", ~"warn",
                         ~"Set lint warnings", ~"OPT"),
   optmulti(~"A", ~"allow",
@@ -775,7 +1109,8 @@ pub fn build_output_filenames(input: input,
     let sopts = sess.opts;
     let stop_after_codegen =
         sopts.output_type != link::output_type_exe ||
-            sopts.static && *sess.building_library;
+            sopts.static && *sess.building_library &&
+            !sess.building_staticlib();
 
 
     let obj_suffix =
@@ -806,7 +1141,10 @@ pub fn build_output_filenames(input: input,
           str_input(_) => ~"rust_out"
         };
 
-        if *sess.building_library {
+        if sess.building_staticlib() {
+            out_path = dirpath.push(link::staticlib_filename(stem));
+            obj_path = dirpath.push(stem).with_filetype(obj_suffix);
+        } else if *sess.building_library {
             out_path = dirpath.push(os::dll_filename(stem));
             obj_path = dirpath.push(stem).with_filetype(obj_suffix);
         } else {
@@ -840,7 +1178,7 @@ pub fn build_output_filenames(input: input,
 }
 
 pub fn early_error(emitter: diagnostic::Emitter, msg: ~str) -> ! {
-    emitter(None, msg, diagnostic::fatal);
+    emitter(None, &diagnostic::Diagnostic::new(diagnostic::fatal, msg));
     die!();
 }
 
@@ -879,6 +1217,31 @@ pub mod test {
         assert (attr::contains_name(cfg, ~"test"));
     }
 
+    #[test]
+    pub fn test_error_format_json() {
+        let matches =
+            &match getopts(~[~"--error-format=json"], optgroups()) {
+              Ok(copy m) => m,
+              Err(copy f) => die!(~"test_error_format_json: " +
+                             getopts::fail_str(f))
+            };
+        let sessopts = build_session_options(
+            ~"rustc", matches, diagnostic::emit);
+        assert sessopts.error_format == diagnostic::Json;
+    }
+
+    #[test]
+    #[should_fail]
+    pub fn test_lib_and_staticlib_conflict() {
+        let matches =
+            &match getopts(~[~"--lib", ~"--staticlib"], optgroups()) {
+              Ok(copy m) => m,
+              Err(copy f) => die!(~"test_lib_and_staticlib_conflict: " +
+                             getopts::fail_str(f))
+            };
+        build_session_options(~"rustc", matches, diagnostic::emit);
+    }
+
     // When the user supplies --test and --cfg test, don't implicitly add
     // another --cfg test
     #[test]
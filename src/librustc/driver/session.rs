@@ -11,6 +11,7 @@
 use core::prelude::*;
 
 use back::link;
+use back::target_spec;
 use back::target_strs;
 use back;
 use driver;
@@ -47,7 +48,14 @@ pub impl arch : cmp::Eq {
     pure fn ne(&self, other: &arch) -> bool { !(*self).eq(other) }
 }
 
-pub enum crate_type { bin_crate, lib_crate, unknown_crate, }
+pub enum crate_type { bin_crate, lib_crate, staticlib_crate, unknown_crate, }
+
+pub impl crate_type : cmp::Eq {
+    pure fn eq(&self, other: &crate_type) -> bool {
+        ((*self) as uint) == ((*other) as uint)
+    }
+    pure fn ne(&self, other: &crate_type) -> bool { !(*self).eq(other) }
+}
 
 pub type config =
     {os: os,
@@ -55,7 +63,13 @@ pub type config =
      target_strs: target_strs::t,
      int_type: int_ty,
      uint_type: uint_ty,
-     float_type: float_ty};
+     float_type: float_ty,
+     // `unix` or `windows`, for the `target_family` cfg
+     target_family: ~str,
+     // The program that links executables, if not the usual one
+     linker: Option<~str>,
+     // Whether stacks grow in segments, with `__morestack`
+     morestack: bool};
 
 pub const verbose: uint = 1 << 0;
 pub const time_passes: uint = 1 << 1;
@@ -80,6 +94,9 @@ pub const jit: uint = 1 << 19;
 pub const debug_info: uint = 1 << 20;
 pub const extra_debug_info: uint = 1 << 21;
 pub const static: uint = 1 << 22;
+pub const trace_expansion: uint = 1 << 23;
+pub const lto: uint = 1 << 24;
+pub const print_link_args: uint = 1 << 25;
 
 pub fn debugging_opts_map() -> ~[(~str, ~str, uint)] {
     ~[(~"verbose", ~"in general, enable more debug printouts", verbose),
@@ -113,7 +130,13 @@ pub fn debugging_opts_map() -> ~[(~str, ~str, uint)] {
       extra_debug_info),
      (~"debug-info", ~"Produce debug info (experimental)", debug_info),
      (~"static", ~"Use or produce static libraries or binaries " +
-      "(experimental)", static)
+      "(experimental)", static),
+     (~"trace-expansion", ~"log each macro and syntax extension expansion",
+      trace_expansion),
+     (~"lto", ~"put bitcode in libraries, and optimize executables along \
+               with the bitcode of the crates they use", lto),
+     (~"print-link-args", ~"print the arguments passed to the linker",
+      print_link_args)
     ]
 }
 
@@ -147,6 +170,8 @@ pub type options =
      addl_lib_search_paths: ~[Path],
      maybe_sysroot: Option<Path>,
      target_triple: ~str,
+     // The description of the target, if it was given by a file
+     target_spec: Option<target_spec::spec>,
      // User-specified cfg meta items. The compiler itself will add additional
      // items to the crate config, and during parsing the entire crate config
      // will be added to the crate AST node.  This should not be used for
@@ -157,6 +182,14 @@ pub type options =
      parse_only: bool,
      no_trans: bool,
      debugging_opts: uint,
+     error_format: diagnostic::ErrorFormat,
+     // The directory of the incremental compilation cache, if any
+     incremental: Option<Path>,
+     // The number of LLVM modules to optimize and emit in parallel
+     codegen_units: uint,
+     // Whether to write a Makefile of the crate's dependencies, and
+     // optionally where to
+     write_dependency_info: (bool, Option<Path>),
     };
 
 pub type crate_metadata = {name: ~str, data: ~[u8]};
@@ -206,6 +239,9 @@ pub impl Session {
     fn span_note(sp: span, msg: ~str) {
         self.span_diagnostic.span_note(sp, msg)
     }
+    fn emit_diagnostic(d: &diagnostic::Diagnostic) {
+        self.span_diagnostic.emit_diagnostic(d)
+    }
     fn note(msg: ~str) {
         self.span_diagnostic.handler().note(msg)
     }
@@ -221,12 +257,17 @@ pub impl Session {
     fn unimpl(msg: ~str) -> ! {
         self.span_diagnostic.handler().unimpl(msg)
     }
-    fn span_lint_level(level: lint::level, sp: span, +msg: ~str) {
+    fn span_lint_level(lint_mode: lint::lint, level: lint::level, sp: span,
+                       +msg: ~str) {
         match level {
           lint::allow => { },
-          lint::warn => self.span_warn(sp, msg),
+          lint::warn => {
+            self.span_diagnostic.span_lint(sp, msg, diagnostic::warning,
+                                           lint::lint_to_str(lint_mode))
+          }
           lint::deny | lint::forbid => {
-            self.span_err(sp, msg);
+            self.span_diagnostic.span_lint(sp, msg, diagnostic::error,
+                                           lint::lint_to_str(lint_mode));
           }
         }
     }
@@ -237,7 +278,7 @@ pub impl Session {
                  +msg: ~str) {
         let level = lint::get_lint_settings_level(
             self.lint_settings, lint_mode, expr_id, item_id);
-        self.span_lint_level(level, span, msg);
+        self.span_lint_level(lint_mode, level, span, msg);
     }
     fn next_node_id() -> ast::node_id {
         return syntax::parse::next_node_id(self.parse_sess);
@@ -263,6 +304,9 @@ pub impl Session {
     fn no_asm_comments() -> bool { self.debugging_opt(no_asm_comments) }
     fn no_verify() -> bool { self.debugging_opt(no_verify) }
     fn trace() -> bool { self.debugging_opt(trace) }
+    fn trace_expansion() -> bool { self.debugging_opt(trace_expansion) }
+    fn lto() -> bool { self.debugging_opt(lto) }
+    fn print_link_args() -> bool { self.debugging_opt(print_link_args) }
     fn coherence() -> bool { self.debugging_opt(coherence) }
     fn borrowck_stats() -> bool { self.debugging_opt(borrowck_stats) }
     fn borrowck_note_pure() -> bool { self.debugging_opt(borrowck_note_pure) }
@@ -270,6 +314,11 @@ pub impl Session {
     fn no_monomorphic_collapse() -> bool {
         self.debugging_opt(no_monomorphic_collapse)
     }
+    // A static library is built like any other library, and then archived
+    // along with the crates it uses
+    fn building_staticlib() -> bool {
+        self.opts.crate_type == staticlib_crate
+    }
 
     fn str_of(id: ast::ident) -> ~str {
         /*bad*/copy *self.parse_sess.interner.get(id)
@@ -298,12 +347,17 @@ pub fn basic_options() -> @options {
         addl_lib_search_paths: ~[],
         maybe_sysroot: None,
         target_triple: driver::host_triple(),
+        target_spec: None,
         cfg: ~[],
         binary: ~"rustc",
         test: false,
         parse_only: false,
         no_trans: false,
-        debugging_opts: 0u
+        debugging_opts: 0u,
+        error_format: diagnostic::HumanReadable,
+        incremental: None,
+        codegen_units: 1u,
+        write_dependency_info: (false, None)
     }
 }
 
@@ -320,7 +374,7 @@ pub fn building_library(req_crate_type: crate_type,
                         testing: bool) -> bool {
     match req_crate_type {
       bin_crate => false,
-      lib_crate => true,
+      lib_crate | staticlib_crate => true,
       unknown_crate => {
         if testing {
             false
@@ -353,7 +407,7 @@ pub mod test {
     use core::prelude::*;
 
     use driver::session::{bin_crate, building_library, lib_crate};
-    use driver::session::{unknown_crate};
+    use driver::session::{staticlib_crate, unknown_crate};
 
     use syntax::ast;
     use syntax::ast_util;
@@ -378,7 +432,8 @@ pub mod test {
         @codemap::respan(codemap::dummy_sp(), ast::crate_ {
             module: ast::_mod { view_items: ~[], items: ~[] },
             attrs: attrs,
-            config: ~[]
+            config: ~[],
+            exported_macros: ~[]
         })
     }
 
@@ -423,6 +478,12 @@ pub mod test {
         let crate = make_crate(false, false);
         assert building_library(lib_crate, crate, true);
     }
+
+    #[test]
+    pub fn staticlib_option_overrides_bin_crate_type() {
+        let crate = make_crate(true, false);
+        assert building_library(staticlib_crate, crate, false);
+    }
 }
 
 // Local Variables:
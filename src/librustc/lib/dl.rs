@@ -0,0 +1,113 @@
+// Copyright 2013 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Loading of dynamic libraries into the running compiler, which is how
+//! syntax extension crates are brought in at expansion time.
+//!
+//! Libraries are never unloaded: the expanders they register are called
+//! for the rest of the compilation.
+
+use core::prelude::*;
+
+use core::libc::c_void;
+
+pub type Library = *c_void;
+
+/// Loads the library at `path`, returning the reason on failure
+pub fn open(path: &Path) -> Result<Library, ~str> {
+    unsafe { imp::open(path) }
+}
+
+/// Looks up the address of the symbol `name` in a loaded library
+pub fn symbol(lib: Library, name: &str) -> Result<*c_void, ~str> {
+    unsafe { imp::symbol(lib, name) }
+}
+
+#[cfg(unix)]
+mod imp {
+    use core::prelude::*;
+
+    use core::libc::{c_char, c_int, c_void};
+    use core::ptr;
+    use core::str;
+
+    const RTLD_NOW: c_int = 2;
+
+    #[nolink]
+    #[abi = "cdecl"]
+    extern mod dl {
+        unsafe fn dlopen(filename: *c_char, flag: c_int) -> *c_void;
+        unsafe fn dlsym(handle: *c_void, symbol: *c_char) -> *c_void;
+        unsafe fn dlerror() -> *c_char;
+    }
+
+    unsafe fn last_error() -> ~str {
+        let err = dl::dlerror();
+        if ptr::is_null(err) { ~"unknown error" }
+        else { str::raw::from_c_str(err) }
+    }
+
+    pub unsafe fn open(path: &Path) -> Result<*c_void, ~str> {
+        let handle = do str::as_c_str(path.to_str()) |p| {
+            dl::dlopen(p, RTLD_NOW)
+        };
+        if ptr::is_null(handle) { Err(last_error()) } else { Ok(handle) }
+    }
+
+    pub unsafe fn symbol(lib: *c_void, name: &str)
+                      -> Result<*c_void, ~str> {
+        // Clear any stale error; a null symbol is not by itself a failure
+        dl::dlerror();
+        let sym = do str::as_c_str(name) |n| { dl::dlsym(lib, n) };
+        let err = dl::dlerror();
+        if ptr::is_null(err) { Ok(sym) }
+        else { Err(str::raw::from_c_str(err)) }
+    }
+}
+
+#[cfg(windows)]
+mod imp {
+    use core::prelude::*;
+
+    use core::libc::{c_char, c_void};
+    use core::libc::types::os::arch::extra::DWORD;
+    use core::ptr;
+    use core::str;
+
+    #[abi = "stdcall"]
+    extern mod kernel32 {
+        unsafe fn LoadLibraryA(filename: *c_char) -> *c_void;
+        unsafe fn GetProcAddress(handle: *c_void, name: *c_char) -> *c_void;
+        unsafe fn GetLastError() -> DWORD;
+    }
+
+    pub unsafe fn open(path: &Path) -> Result<*c_void, ~str> {
+        let handle = do str::as_c_str(path.to_str()) |p| {
+            kernel32::LoadLibraryA(p)
+        };
+        if ptr::is_null(handle) {
+            Err(fmt!("error %u", kernel32::GetLastError() as uint))
+        } else {
+            Ok(handle)
+        }
+    }
+
+    pub unsafe fn symbol(lib: *c_void, name: &str)
+                      -> Result<*c_void, ~str> {
+        let sym = do str::as_c_str(name) |n| {
+            kernel32::GetProcAddress(lib, n)
+        };
+        if ptr::is_null(sym) {
+            Err(fmt!("error %u", kernel32::GetLastError() as uint))
+        } else {
+            Ok(sym)
+        }
+    }
+}
@@ -259,6 +259,10 @@ pub extern mod llvm {
     pub unsafe fn LLVMContextCreate() -> ContextRef;
     pub unsafe fn LLVMGetGlobalContext() -> ContextRef;
     pub unsafe fn LLVMContextDispose(C: ContextRef);
+
+    /** Makes LLVM safe to use from several threads at once, one context
+        per thread. Returns false if LLVM was built without threads. */
+    pub unsafe fn LLVMStartMultithreaded() -> Bool;
     pub unsafe fn LLVMGetMDKindIDInContext(C: ContextRef,
                                        Name: *c_char,
                                        SLen: c_uint)
@@ -1141,6 +1145,10 @@ pub extern mod llvm {
     Includes ABI padding. */
     pub unsafe fn LLVMABISizeOfType(TD: TargetDataRef, Ty: TypeRef) -> c_uint;
 
+    /** Offset of an element of a struct type, in bytes. */
+    pub unsafe fn LLVMOffsetOfElement(TD: TargetDataRef, StructTy: TypeRef,
+                                      Element: c_uint) -> c_ulonglong;
+
     /** Returns the preferred alignment of a type. */
     pub unsafe fn LLVMPreferredAlignmentOfType(TD: TargetDataRef,
                                     Ty: TypeRef) -> c_uint;
@@ -1220,6 +1228,9 @@ pub extern mod llvm {
 
     pub unsafe fn LLVMPassManagerBuilderPopulateFunctionPassManager
         (PMB: PassManagerBuilderRef, PM: PassManagerRef);
+    pub unsafe fn LLVMPassManagerBuilderPopulateLTOPassManager
+        (PMB: PassManagerBuilderRef, PM: PassManagerRef, Internalize: Bool,
+         RunInliner: Bool);
 
     /** Destroys a memory buffer. */
     pub unsafe fn LLVMDisposeMemoryBuffer(MemBuf: MemoryBufferRef);
@@ -1285,6 +1296,21 @@ pub extern mod llvm {
     /** Parses the bitcode in the given memory buffer. */
     pub unsafe fn LLVMRustParseBitcode(MemBuf: MemoryBufferRef) -> ModuleRef;
 
+    /** Parses the bitcode in the given memory buffer into the given
+        context. */
+    pub unsafe fn LLVMRustParseBitcodeInContext(C: ContextRef,
+                                                MemBuf: MemoryBufferRef)
+                                             -> ModuleRef;
+
+    /** Turns a function or global variable definition into an external
+        declaration. */
+    pub unsafe fn LLVMRustMakeDeclaration(Global: ValueRef);
+
+    /** Links `Src` into `Dest`, destroying `Src`. Returns false on
+        failure. */
+    pub unsafe fn LLVMRustLinkModules(Dest: ModuleRef, Src: ModuleRef)
+                                   -> bool;
+
     /** Parses LLVM asm in the given file */
     pub unsafe fn LLVMRustParseAssemblyFile(Filename: *c_char) -> ModuleRef;
 
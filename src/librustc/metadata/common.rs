@@ -153,5 +153,23 @@ pub const tag_lang_items_item: uint = 0x73;
 pub const tag_lang_items_item_id: uint = 0x74;
 pub const tag_lang_items_item_node_id: uint = 0x75;
 
+// Macros marked #[macro_export] are likewise a top-level directory:
+//
+// tag_macro_defs
+// - tag_macro_def
+//   - tag_macro_def_name: the macro name as a string
+//   - tag_macro_def_tts: the body of the `macro_rules!`, as token trees
+
+pub const tag_macro_defs: uint = 0x76;
+pub const tag_macro_def: uint = 0x77;
+pub const tag_macro_def_name: uint = 0x78;
+pub const tag_macro_def_tts: uint = 0x79;
+
+// The symbol of the registrar function of a #[syntax_extension] crate
+pub const tag_syntax_registrar: uint = 0x7a;
+
+// The LLVM bitcode of a library built with -Z lto
+pub const tag_bitcode: uint = 0x7b;
+
 pub type link_meta = {name: @str, vers: @str, extras_hash: @str};
 
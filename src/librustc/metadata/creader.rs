@@ -60,6 +60,49 @@ pub fn read_crates(diag: span_handler,
     warn_if_multiple_versions(e, diag, e.crate_cache);
 }
 
+pub type macro_crate = {
+    // The library file the crate was found in
+    path: Path,
+    exported_macros: ~[(ast::ident, ~[ast::token_tree])],
+    // The symbol of its registrar, if this is a #[syntax_extension] crate
+    registrar: Option<~str>
+};
+
+// Reads what the crate that an `extern mod` names provides to the
+// expander. This runs during expansion, before `read_crates`, so the
+// library is only located and its metadata decoded; it is not entered in
+// the cstore.
+pub fn read_macro_crate(diag: span_handler,
+                        filesearch: FileSearch,
+                        os: loader::os,
+                        statik: bool,
+                        intr: @ident_interner,
+                        vi: @ast::view_item) -> Option<macro_crate> {
+    match /*bad*/copy vi.node {
+      ast::view_item_use(ident, meta_items, _) => {
+        let metas = metas_with_ident(/*bad*/copy *intr.get(ident),
+                                     meta_items);
+        let load_ctxt: loader::ctxt = {
+            diag: diag,
+            filesearch: filesearch,
+            span: vi.span,
+            ident: ident,
+            metas: metas,
+            hash: ~"",
+            os: os,
+            static: statik,
+            intr: intr
+        };
+        let cinfo = loader::load_library_crate(load_ctxt);
+        Some({path: Path(cinfo.ident),
+              exported_macros: decoder::get_exported_macros(intr,
+                                                            cinfo.data),
+              registrar: decoder::get_syntax_registrar(cinfo.data)})
+      }
+      _ => None
+    }
+}
+
 type cache_entry = {
     cnum: int,
     span: span,
@@ -263,7 +306,8 @@ fn resolve_crate(e: @mut Env,
               option::None => /*bad*/copy *e.intr.get(ident)
             };
         let cmeta = @{name: cname, data: cdata,
-                      cnum_map: cnum_map, cnum: cnum};
+                      cnum_map: cnum_map, cnum: cnum,
+                      path: copy cfilename};
 
         let cstore = e.cstore;
         cstore::set_crate_data(cstore, cnum, cmeta);
@@ -36,7 +36,9 @@ pub type cnum_map = oldmap::HashMap<ast::crate_num, ast::crate_num>;
 pub type crate_metadata = @{name: ~str,
                             data: @~[u8],
                             cnum_map: cnum_map,
-                            cnum: ast::crate_num};
+                            cnum: ast::crate_num,
+                            // The file the crate was loaded from
+                            path: Path};
 
 pub struct CStore {
     priv metas: oldmap::HashMap<ast::crate_num, crate_metadata>,
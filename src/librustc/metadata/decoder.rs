@@ -444,6 +444,43 @@ pub fn each_lang_item(cdata: cmd, f: &fn(ast::node_id, uint) -> bool) {
     }
 }
 
+/// Returns the name and token trees of each macro exported by the crate
+/// whose metadata is `data`, ready to be handed to `macro_rules!`.
+pub fn get_exported_macros(intr: @ident_interner, data: @~[u8])
+                        -> ~[(ast::ident, ~[ast::token_tree])] {
+    let mut macros = ~[];
+    let root = reader::Doc(data);
+    let macro_defs = match reader::maybe_get_doc(root, tag_macro_defs) {
+        Some(doc) => doc,
+        None => return macros
+    };
+    for reader::tagged_docs(macro_defs, tag_macro_def) |def_doc| {
+        let name_doc = reader::get_doc(def_doc, tag_macro_def_name);
+        let name = intr.intern(@str::from_bytes(reader::doc_data(name_doc)));
+        let tts_doc = reader::get_doc(def_doc, tag_macro_def_tts);
+        let tts: ~[ast::token_tree] =
+            Decodable::decode(&reader::Decoder(tts_doc));
+        macros.push((name, tts));
+    }
+    macros
+}
+
+/// Returns the symbol of the registrar function, if the crate is a
+/// #[syntax_extension] crate.
+pub fn get_syntax_registrar(data: @~[u8]) -> Option<~str> {
+    do reader::maybe_get_doc(reader::Doc(data),
+                             tag_syntax_registrar).map |doc| {
+        str::from_bytes(reader::doc_data(*doc))
+    }
+}
+
+/// The LLVM bitcode of a crate built with -Z lto
+pub fn get_bitcode(data: @~[u8]) -> Option<~[u8]> {
+    do reader::maybe_get_doc(reader::Doc(data), tag_bitcode).map |doc| {
+        reader::doc_data(*doc)
+    }
+}
+
 /// Iterates over all the paths in the given crate.
 pub fn each_path(intr: @ident_interner, cdata: cmd,
                  get_crate_data: GetCrateDataCb,
@@ -45,6 +45,7 @@ use syntax::ast;
 use syntax::ast_map;
 use syntax::ast_util::*;
 use syntax::attr;
+use syntax::codemap;
 use syntax::diagnostic::span_handler;
 use syntax::print::pprust;
 use syntax::{ast_util, visit};
@@ -68,7 +69,8 @@ pub type encode_parms = {
     discrim_symbols: HashMap<ast::node_id, ~str>,
     link_meta: link_meta,
     cstore: @mut cstore::CStore,
-    encode_inlined_item: encode_inlined_item
+    encode_inlined_item: encode_inlined_item,
+    bitcode: Option<~[u8]>
 };
 
 struct Stats {
@@ -76,6 +78,7 @@ struct Stats {
     attr_bytes: uint,
     dep_bytes: uint,
     lang_item_bytes: uint,
+    macro_bytes: uint,
     item_bytes: uint,
     index_bytes: uint,
     zero_bytes: uint,
@@ -504,6 +507,7 @@ fn encode_info_for_method(ecx: @encode_ctxt,
     encode_name(ecx, ebml_w, m.ident);
     encode_path(ecx, ebml_w, impl_path, ast_map::path_name(m.ident));
     encode_self_type(ebml_w, m.self_ty.node);
+    encode_attributes(ebml_w, m.attrs);
     if len > 0u || should_inline {
         (ecx.encode_inlined_item)(
            ecx, ebml_w, impl_path,
@@ -623,6 +627,7 @@ fn encode_info_for_item(ecx: @encode_ctxt, ebml_w: writer::Encoder,
             encode_type_param_bounds(ebml_w, ecx, *tps);
             encode_type(ecx, ebml_w, node_id_to_type(tcx, item.id));
             encode_name(ecx, ebml_w, item.ident);
+            encode_attributes(ebml_w, item.attrs);
             for (*enum_definition).variants.each |v| {
                 encode_variant_id(ebml_w, local_def(v.node.id));
             }
@@ -675,6 +680,7 @@ fn encode_info_for_item(ecx: @encode_ctxt, ebml_w: writer::Encoder,
         encode_type_param_bounds(ebml_w, ecx, tps);
         encode_type(ecx, ebml_w, node_id_to_type(tcx, item.id));
         encode_name(ecx, ebml_w, item.ident);
+        encode_attributes(ebml_w, item.attrs);
         encode_path(ecx, ebml_w, path, ast_map::path_name(item.ident));
         encode_region_param(ecx, ebml_w, item);
         /* Encode the dtor */
@@ -1137,6 +1143,73 @@ fn encode_lang_items(ecx: @encode_ctxt, ebml_w: writer::Encoder) {
     ebml_w.end_tag();   // tag_lang_items
 }
 
+fn encode_macro_defs(ecx: @encode_ctxt, ebml_w: writer::Encoder,
+                     crate: &crate) {
+    ebml_w.start_tag(tag_macro_defs);
+
+    for crate.node.exported_macros.each |it| {
+        match it.node {
+          item_mac(codemap::spanned {
+              node: mac_invoc_tt(_, ref tts), _
+          }) => {
+            ebml_w.start_tag(tag_macro_def);
+
+            ebml_w.start_tag(tag_macro_def_name);
+            let name = ecx.tcx.sess.str_of(it.ident);
+            ebml_w.writer.write(str::to_bytes(name));
+            ebml_w.end_tag();   // tag_macro_def_name
+
+            ebml_w.start_tag(tag_macro_def_tts);
+            (*tts).encode(&ebml_w);
+            ebml_w.end_tag();   // tag_macro_def_tts
+
+            ebml_w.end_tag();   // tag_macro_def
+          }
+          _ => ecx.diag.span_bug(it.span, ~"exported macro is not a \
+                                             macro invocation")
+        }
+    }
+
+    ebml_w.end_tag();   // tag_macro_defs
+}
+
+// A #[syntax_extension] crate records the symbol of its top-level
+// `register_syntax_extensions` function, which the driver looks up after
+// loading the library into the compiler.
+fn encode_syntax_registrar(ecx: @encode_ctxt, ebml_w: writer::Encoder,
+                           crate: &crate) {
+    if !attr::contains_name(attr::attr_metas(/*bad*/copy crate.node.attrs),
+                            ~"syntax_extension") {
+        return;
+    }
+
+    for crate.node.module.items.each |it| {
+        match it.node {
+          item_fn(*) if ecx.tcx.sess.str_of(it.ident) ==
+                        ~"register_syntax_extensions" => {
+            if it.vis != public {
+                ecx.diag.span_err(it.span, ~"the registrar of a \
+                                             #[syntax_extension] crate \
+                                             must be public");
+            }
+            let sym = match ecx.item_symbols.find(&it.id) {
+                Some(ref sym) => /*bad*/copy *sym,
+                None => ecx.diag.span_bug(it.span,
+                                          ~"registrar has no symbol")
+            };
+            ebml_w.start_tag(tag_syntax_registrar);
+            ebml_w.writer.write(str::to_bytes(sym));
+            ebml_w.end_tag();
+            return;
+          }
+          _ => ()
+        }
+    }
+
+    ecx.diag.handler().err(~"#[syntax_extension] crate does not define \
+                             a `register_syntax_extensions` function");
+}
+
 fn encode_crate_dep(ecx: @encode_ctxt, ebml_w: writer::Encoder,
                     dep: decoder::crate_dep) {
     ebml_w.start_tag(tag_crate_dep);
@@ -1172,6 +1245,7 @@ pub fn encode_metadata(parms: encode_parms, crate: &crate) -> ~[u8] {
         attr_bytes: 0,
         dep_bytes: 0,
         lang_item_bytes: 0,
+        macro_bytes: 0,
         item_bytes: 0,
         index_bytes: 0,
         zero_bytes: 0,
@@ -1210,6 +1284,21 @@ pub fn encode_metadata(parms: encode_parms, crate: &crate) -> ~[u8] {
     encode_lang_items(ecx, ebml_w);
     ecx.stats.lang_item_bytes = wr.pos - i;
 
+    // Encode the macros this crate exports.
+    i = wr.pos;
+    encode_macro_defs(ecx, ebml_w, crate);
+    encode_syntax_registrar(ecx, ebml_w, crate);
+    ecx.stats.macro_bytes = wr.pos - i;
+
+    match parms.bitcode {
+      Some(ref bitcode) => {
+        ebml_w.start_tag(tag_bitcode);
+        ebml_w.writer.write(*bitcode);
+        ebml_w.end_tag();
+      }
+      None => ()
+    }
+
     // Encode and index the items.
     ebml_w.start_tag(tag_items);
     i = wr.pos;
@@ -1240,6 +1329,7 @@ pub fn encode_metadata(parms: encode_parms, crate: &crate) -> ~[u8] {
         io::println(fmt!(" attribute bytes: %u", ecx.stats.attr_bytes));
         io::println(fmt!("       dep bytes: %u", ecx.stats.dep_bytes));
         io::println(fmt!(" lang item bytes: %u", ecx.stats.lang_item_bytes));
+        io::println(fmt!("     macro bytes: %u", ecx.stats.macro_bytes));
         io::println(fmt!("      item bytes: %u", ecx.stats.item_bytes));
         io::println(fmt!("     index bytes: %u", ecx.stats.index_bytes));
         io::println(fmt!("      zero bytes: %u", ecx.stats.zero_bytes));
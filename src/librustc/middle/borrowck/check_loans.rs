@@ -40,6 +40,7 @@ use syntax::ast::{m_const, m_imm, m_mutbl};
 use syntax::ast;
 use syntax::ast_util;
 use syntax::codemap::span;
+use syntax::diagnostic;
 use syntax::print::pprust;
 use syntax::visit;
 
@@ -311,16 +312,18 @@ impl CheckLoanCtxt {
             }
 
             (m_mutbl, m_mutbl) | (m_mutbl, m_imm) | (m_imm, m_mutbl) => {
-                self.bccx.span_err(
+                let mut d = diagnostic::Diagnostic::spanned(
+                    diagnostic::error,
                     new_loan.cmt.span,
                     fmt!("loan of %s as %s \
                           conflicts with prior loan",
                          self.bccx.cmt_to_str(new_loan.cmt),
                          self.bccx.mut_to_str(new_loan.mutbl)));
-                self.bccx.span_note(
-                    old_loan.cmt.span,
-                    fmt!("prior loan as %s granted here",
-                         self.bccx.mut_to_str(old_loan.mutbl)));
+                d.span_label(new_loan.cmt.span, ~"second borrow here");
+                d.span_label(old_loan.cmt.span,
+                             fmt!("prior loan as %s granted here",
+                                  self.bccx.mut_to_str(old_loan.mutbl)));
+                self.bccx.emit_diagnostic(&d);
             }
         }
     }
@@ -431,14 +434,15 @@ impl CheckLoanCtxt {
             match loan.mutbl {
               m_const => { /*ok*/ }
               m_mutbl | m_imm => {
-                self.bccx.span_err(
+                let mut d = diagnostic::Diagnostic::spanned(
+                    diagnostic::error,
                     ex.span,
                     fmt!("%s prohibited due to outstanding loan",
                          at.ing_form(self.bccx.cmt_to_str(cmt))));
-                self.bccx.span_note(
-                    loan.cmt.span,
-                    fmt!("loan of %s granted here",
-                         self.bccx.cmt_to_str(loan.cmt)));
+                d.span_label(loan.cmt.span,
+                             fmt!("loan of %s granted here",
+                                  self.bccx.cmt_to_str(loan.cmt)));
+                self.bccx.emit_diagnostic(&d);
                 return;
               }
             }
@@ -501,15 +505,17 @@ impl CheckLoanCtxt {
                                  self.bccx.cmt_to_str(cmt)));
                     }
                     MoveWhileBorrowed(_, loan_cmt) => {
-                        self.bccx.span_err(
+                        let mut d = diagnostic::Diagnostic::spanned(
+                            diagnostic::error,
                             cmt.span,
                             fmt!("moving out of %s prohibited \
                                   due to outstanding loan",
                                  self.bccx.cmt_to_str(cmt)));
-                        self.bccx.span_note(
-                            loan_cmt.span,
-                            fmt!("loan of %s granted here",
-                                 self.bccx.cmt_to_str(loan_cmt)));
+                        d.span_label(cmt.span, ~"move here");
+                        d.span_label(loan_cmt.span,
+                                     fmt!("loan of %s granted here",
+                                          self.bccx.cmt_to_str(loan_cmt)));
+                        self.bccx.emit_diagnostic(&d);
                     }
                 }
             }
@@ -656,15 +662,18 @@ fn check_loans_in_fn(fk: visit::fn_kind,
                                      self.bccx.cmt_to_str(move_cmt)));
                         }
                         MoveWhileBorrowed(move_cmt, loan_cmt) => {
-                            self.bccx.span_err(
+                            let mut d = diagnostic::Diagnostic::spanned(
+                                diagnostic::error,
                                 cap_var.span,
                                 fmt!("by-move capture of %s prohibited \
                                       due to outstanding loan",
                                      self.bccx.cmt_to_str(move_cmt)));
-                            self.bccx.span_note(
+                            d.span_label(cap_var.span, ~"capture here");
+                            d.span_label(
                                 loan_cmt.span,
                                 fmt!("loan of %s granted here",
                                      self.bccx.cmt_to_str(loan_cmt)));
+                            self.bccx.emit_diagnostic(&d);
                         }
                     }
                 }
@@ -247,6 +247,7 @@ use syntax::ast;
 use syntax::ast_map;
 use syntax::ast_util;
 use syntax::codemap::span;
+use syntax::diagnostic;
 use syntax::print::pprust;
 use syntax::visit;
 
@@ -506,6 +507,10 @@ pub impl BorrowckCtxt {
         self.tcx.sess.span_note(s, m);
     }
 
+    fn emit_diagnostic(&self, d: &diagnostic::Diagnostic) {
+        self.tcx.sess.emit_diagnostic(d);
+    }
+
     fn add_to_mutbl_map(&self, cmt: cmt) {
         match cmt.cat {
           cat_local(id) | cat_arg(id) => {
@@ -26,6 +26,7 @@ use core::vec;
 use std::oldmap::HashMap;
 use std::sort;
 use syntax::ast::*;
+use syntax::ast_map;
 use syntax::ast_util::{variant_def_ids, unguarded_pat, walk_pat};
 use syntax::codemap::{span, dummy_sp, spanned};
 use syntax::print::pprust::pat_to_str;
@@ -110,16 +111,46 @@ pub fn check_expr(cx: @MatchCheckCtxt, ex: @expr, &&s: (), v: visit::vt<()>) {
 // Check for unreachable patterns
 pub fn check_arms(cx: @MatchCheckCtxt, arms: ~[arm]) {
     let mut seen = ~[];
-    for arms.each |arm| {
-        for arm.pats.each |pat| {
+    // The arm each row of `seen` came from
+    let mut seen_arms = ~[];
+    for arms.eachi |i, arm| {
+        for arm.pats.eachi |j, pat| {
             let v = ~[*pat];
             match is_useful(cx, copy seen, v) {
               not_useful => {
-                cx.tcx.sess.span_err(pat.span, ~"unreachable pattern");
+                let msg = if arm.pats.len() > 1 {
+                    fmt!("unreachable pattern: alternative %u of arm %u is \
+                          never matched", j + 1, i + 1)
+                } else {
+                    fmt!("unreachable pattern: arm %u is never matched",
+                         i + 1)
+                };
+                cx.tcx.sess.span_err(pat.span, msg);
+                note_covering_arm(cx, seen, seen_arms, v);
               }
               _ => ()
             }
-            if arm.guard.is_none() { seen.push(v); }
+            if arm.guard.is_none() {
+                seen.push(v);
+                seen_arms.push(i);
+            }
+        }
+    }
+}
+
+// If a single earlier pattern already matches everything `v` does, point
+// at it
+fn note_covering_arm(cx: @MatchCheckCtxt, seen: &[~[@pat]],
+                     seen_arms: &[uint], v: &[@pat]) {
+    for seen.eachi |k, row| {
+        match is_useful(cx, ~[copy *row], v) {
+          not_useful => {
+            cx.tcx.sess.span_note(row[0].span,
+                                  fmt!("already matched by arm %u",
+                                       seen_arms[k] + 1));
+            return;
+          }
+          _ => ()
         }
     }
 }
@@ -133,47 +164,23 @@ pub fn raw_pat(p: @pat) -> @pat {
 
 pub fn check_exhaustive(cx: @MatchCheckCtxt, sp: span, pats: ~[@pat]) {
     assert(!pats.is_empty());
-    let ext = match is_useful(cx, vec::map(pats, |p| ~[*p]), ~[wild()]) {
-      not_useful => return, // This is good, wildcard pattern isn't reachable
-      useful_ => None,
-      useful(ty, ref ctor) => {
-        match ty::get(ty).sty {
-          ty::ty_bool => {
-            match (*ctor) {
-              val(const_bool(true)) => Some(~"true"),
-              val(const_bool(false)) => Some(~"false"),
-              _ => None
-            }
-          }
-          ty::ty_enum(id, _) => {
-              let vid = match (*ctor) { variant(id) => id,
-              _ => die!(~"check_exhaustive: non-variant ctor") };
-            match vec::find(*ty::enum_variants(cx.tcx, id),
-                                |v| v.id == vid) {
-                Some(v) => Some(cx.tcx.sess.str_of(v.name)),
-              None => die!(~"check_exhaustive: bad variant in ctor")
-            }
-          }
-          ty::ty_unboxed_vec(*) | ty::ty_evec(*) => {
-            match (*ctor) {
-              vec(n) => Some(fmt!("vectors of length %u", n)),
-              _ => None
-            }
-          }
-          _ => None
-        }
+    match is_useful(cx, vec::map(pats, |p| ~[*p]), ~[wild()]) {
+      not_useful => (), // This is good, wildcard pattern isn't reachable
+      useful(ref witness) => {
+        cx.tcx.sess.span_err(sp, fmt!("non-exhaustive patterns: `%s` not \
+                                       covered",
+                                      pat_to_str(witness[0],
+                                                 cx.tcx.sess.intr())));
       }
-    };
-    let msg = ~"non-exhaustive patterns" + match ext {
-      Some(ref s) => ~": " + (*s) + ~" not covered",
-      None => ~""
-    };
-    cx.tcx.sess.span_err(sp, msg);
+    }
 }
 
 pub type matrix = ~[~[@pat]];
 
-pub enum useful { useful(ty::t, ctor), useful_, not_useful }
+// When a pattern vector is useful, `useful` carries a witness: a vector of
+// patterns, one per column, describing values that the vector matches and
+// the matrix does not.
+pub enum useful { useful(~[@pat]), not_useful }
 
 #[deriving_eq]
 pub enum ctor {
@@ -198,7 +205,7 @@ pub enum ctor {
 // Note: is_useful doesn't work on empty types, as the paper notes.
 // So it assumes that v is non-empty.
 pub fn is_useful(cx: @MatchCheckCtxt, +m: matrix, +v: &[@pat]) -> useful {
-    if m.len() == 0u { return useful_; }
+    if m.len() == 0u { return useful(vec::from_elem(v.len(), wild())); }
     if m[0].len() == 0u { return not_useful; }
     let real_pat = match vec::find(m, |r| r[0].id != 0) {
       Some(r) => r[0], None => v[0]
@@ -258,8 +265,14 @@ pub fn is_useful(cx: @MatchCheckCtxt, +m: matrix, +v: &[@pat]) -> useful {
             match is_useful(cx,
                             vec::filter_map(m, |r| default(cx, r)),
                             vec::tail(v)) {
-              useful_ => useful(left_ty, (/*bad*/copy *ctor)),
-              ref u => (/*bad*/copy *u)
+              useful(ref witness) => {
+                let arity = ctor_arity(cx, (*ctor), left_ty);
+                let args = vec::from_elem(arity, wild());
+                let pat = construct_witness(cx, /*bad*/copy *ctor, args,
+                                            left_ty);
+                useful(vec::append(~[pat], *witness))
+              }
+              not_useful => not_useful
             }
           }
         }
@@ -282,11 +295,83 @@ pub fn is_useful_specialized(cx: @MatchCheckCtxt,
     let could_be_useful = is_useful(
         cx, ms, specialize(cx, v, ctor, arity, lty).get());
     match could_be_useful {
-      useful_ => useful(lty, ctor),
-      ref u => (/*bad*/copy *u)
+      // The first `arity` columns of the witness are the arguments of `ctor`
+      useful(ref witness) => {
+        let args = vec::slice(*witness, 0u, arity);
+        let rest = vec::slice(*witness, arity, witness.len());
+        useful(vec::append(~[construct_witness(cx, ctor, args, lty)], rest))
+      }
+      not_useful => not_useful
     }
 }
 
+// Builds the pattern for the constructor `ctor` of type `ty` applied to
+// `args`, for use in error messages. Values the checker can't name, such as
+// the integers a match leaves out, become `_`.
+pub fn construct_witness(cx: @MatchCheckCtxt, ctor: ctor, +args: ~[@pat],
+                         ty: ty::t) -> @pat {
+    fn path_of(ident: ident) -> @path {
+        @path {span: dummy_sp(), global: false, idents: ~[ident],
+               rp: None, types: ~[]}
+    }
+    fn field_pats(idents: ~[ident], args: ~[@pat]) -> ~[field_pat] {
+        do vec::map2(idents, args) |ident, arg| {
+            field_pat {ident: *ident, pat: *arg}
+        }
+    }
+
+    let node = match /*bad*/copy ty::get(ty).sty {
+      ty::ty_enum(eid, _) => {
+        let vid = match ctor { variant(id) => id,
+          _ => die!(~"construct_witness: non-variant ctor") };
+        match vec::find(*ty::enum_variants(cx.tcx, eid), |v| v.id == vid) {
+          Some(v) => {
+            if args.is_empty() {
+                pat_ident(bind_infer, path_of(v.name), None)
+            } else {
+                pat_enum(path_of(v.name), Some(args))
+            }
+          }
+          None => die!(~"construct_witness: bad variant in ctor")
+        }
+      }
+      ty::ty_bool => {
+        match ctor {
+          val(const_bool(b)) => {
+            pat_lit(@expr {id: 0, callee_id: 0,
+                           node: expr_lit(@spanned {node: lit_bool(b),
+                                                    span: dummy_sp()}),
+                           span: dummy_sp()})
+          }
+          _ => pat_wild
+        }
+      }
+      ty::ty_tup(_) => pat_tup(args),
+      ty::ty_rec(fields) => {
+        pat_rec(field_pats(fields.map(|f| f.ident), args), false)
+      }
+      ty::ty_struct(cid, _) => {
+        let fields = ty::lookup_struct_fields(cx.tcx, cid);
+        let name = match vec::last(ty::item_path(cx.tcx, cid)) {
+          ast_map::path_mod(ident) | ast_map::path_name(ident) => ident
+        };
+        pat_struct(path_of(name), field_pats(fields.map(|f| f.ident), args),
+                   false)
+      }
+      ty::ty_box(_) => pat_box(args[0]),
+      ty::ty_uniq(_) => pat_uniq(args[0]),
+      ty::ty_rptr(*) => pat_region(args[0]),
+      ty::ty_unboxed_vec(*) | ty::ty_evec(*) => {
+        match ctor {
+          vec(_) => pat_vec(args, None),
+          _ => pat_wild
+        }
+      }
+      _ => pat_wild
+    };
+    @pat {id: 0, node: node, span: dummy_sp()}
+}
+
 pub fn pat_ctor_id(cx: @MatchCheckCtxt, p: @pat) -> Option<ctor> {
     let pat = raw_pat(p);
     match /*bad*/copy pat.node {
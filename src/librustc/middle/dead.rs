@@ -0,0 +1,273 @@
+// Copyright 2013 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// The dead_code lint. Starting from the entry points of the crate -- main,
+// public items, #[test] functions, lang items, extern functions and
+// everything `trans::reachable` finds through the exports -- marks every
+// item that the def map and the method map say is referenced, and warns
+// about the functions, constants, structs, enums, variants and methods
+// that were never marked.
+//
+// Traits and the impls of traits are always live, since the compiler can
+// call their methods without any path naming them.
+//
+// The bodies of functions reused by incremental compilation are stubs, so
+// what they referred to is passed in as `stubbed_uses`.
+
+use core::prelude::*;
+
+use middle::lint::dead_code;
+use middle::resolve;
+use middle::trans::reachable;
+use middle::ty;
+use middle::typeck;
+
+use std::oldmap::HashMap;
+use syntax::ast::*;
+use syntax::ast_util::def_id_of_def;
+use syntax::attr;
+use syntax::codemap::span;
+use syntax::{visit, ast_map};
+
+struct ctx {
+    tcx: ty::ctxt,
+    method_map: typeck::method_map,
+    stubbed_uses: HashMap<node_id, ~[node_id]>,
+    live: HashMap<node_id, ()>,
+    mut worklist: ~[node_id]
+}
+
+pub fn check_crate(tcx: ty::ctxt, method_map: typeck::method_map,
+                   exp_map2: resolve::ExportMap2,
+                   stubbed_uses: HashMap<node_id, ~[node_id]>,
+                   crate: @crate) {
+    let cx = @ctx {
+        tcx: tcx,
+        method_map: method_map,
+        stubbed_uses: stubbed_uses,
+        live: HashMap(),
+        worklist: ~[]
+    };
+
+    match *tcx.sess.main_fn {
+      Some((id, _)) => mark(cx, id),
+      None => ()
+    }
+    let reachable = reachable::find_reachable(crate.node.module, exp_map2,
+                                              tcx, method_map);
+    for reachable.each_key_ref |&id| {
+        mark(cx, id);
+    }
+    find_roots(cx, crate);
+
+    while !cx.worklist.is_empty() {
+        let id = cx.worklist.pop();
+        visit_live_node(cx, id);
+    }
+
+    warn_about_dead_items(cx, crate);
+}
+
+fn mark(cx: @ctx, id: node_id) {
+    if !cx.live.contains_key_ref(&id) {
+        cx.live.insert(id, ());
+        cx.worklist.push(id);
+    }
+}
+
+fn mark_def(cx: @ctx, def: def) {
+    match def {
+      def_prim_ty(_) | def_local(*) | def_arg(*) | def_self(*) |
+      def_upvar(*) | def_binding(*) | def_ty_param(*) => return,
+      def_variant(enum_id, variant_id) => {
+        if enum_id.crate == local_crate { mark(cx, enum_id.node); }
+        if variant_id.crate == local_crate { mark(cx, variant_id.node); }
+      }
+      _ => {
+        let def_id = def_id_of_def(def);
+        if def_id.crate == local_crate { mark(cx, def_id.node); }
+      }
+    }
+}
+
+// Items that are live whether or not anything refers to them
+fn find_roots(cx: @ctx, crate: @crate) {
+    visit::visit_crate(*crate, cx, visit::mk_vt(@visit::Visitor {
+        visit_item: |item, cx, v| {
+            let root = match item.node {
+              item_fn(_, extern_fn, _, _) | item_trait(*) |
+              item_impl(_, Some(_), _, _) => true,
+              item_impl(_, None, _, ref methods) => {
+                for methods.each |method| {
+                    if method.vis == public { mark(cx, method.id); }
+                }
+                false
+              }
+              _ => {
+                item.vis == public ||
+                attr::attrs_contains_name(item.attrs, "test") ||
+                attr::attrs_contains_name(item.attrs, "lang")
+              }
+            };
+            if root { mark(cx, item.id); }
+            visit::visit_item(item, cx, v);
+        },
+        .. *visit::default_visitor()
+    }));
+}
+
+// Marks everything referred to by the live node `id`
+fn visit_live_node(cx: @ctx, id: node_id) {
+    // Nested items are live only if something refers to them
+    let v = visit::mk_vt(@visit::Visitor {
+        visit_item: |_i, _cx, _v| { },
+        visit_expr: visit_expr,
+        visit_pat: visit_pat,
+        visit_ty: visit_ty,
+        .. *visit::default_visitor()
+    });
+
+    match cx.tcx.items.find(&id) {
+      Some(ast_map::node_item(item, _)) => {
+        match item.node {
+          // The methods of an inherent impl are only live when called
+          item_impl(_, None, self_ty, _) => (v.visit_ty)(self_ty, cx, v),
+          // nor are the variants of an enum
+          item_enum(*) | item_mod(*) => (),
+          _ => visit::visit_item(item, cx, v)
+        }
+      }
+      Some(ast_map::node_method(method, _, _)) => {
+        visit::visit_method_helper(method, cx, v);
+      }
+      Some(ast_map::node_variant(ref variant, enum_item, _)) => {
+        mark(cx, enum_item.id);
+        match variant.node.kind {
+          tuple_variant_kind(ref args) => {
+            for args.each |arg| { (v.visit_ty)(arg.ty, cx, v); }
+          }
+          struct_variant_kind(struct_def) => {
+            for struct_def.fields.each |field| {
+                (v.visit_ty)(field.node.ty, cx, v);
+            }
+          }
+          enum_variant_kind(_) => ()
+        }
+        match variant.node.disr_expr {
+          Some(expr) => (v.visit_expr)(expr, cx, v),
+          None => ()
+        }
+      }
+      Some(ast_map::node_struct_ctor(_, struct_item, _)) => {
+        mark(cx, struct_item.id);
+      }
+      _ => ()
+    }
+
+    match cx.stubbed_uses.find(&id) {
+      Some(uses) => {
+        for uses.each |&use_id| { mark(cx, use_id); }
+      }
+      None => ()
+    }
+}
+
+fn visit_expr(e: @expr, cx: @ctx, v: visit::vt<@ctx>) {
+    match cx.tcx.def_map.find(&e.id) {
+      Some(def) => mark_def(cx, def),
+      None => ()
+    }
+    match cx.method_map.find(&e.id) {
+      Some(typeck::method_map_entry {
+          origin: typeck::method_static(did),
+          _
+        }) => {
+        if did.crate == local_crate { mark(cx, did.node); }
+      }
+      _ => ()
+    }
+    visit::visit_expr(e, cx, v);
+}
+
+fn visit_pat(p: @pat, cx: @ctx, v: visit::vt<@ctx>) {
+    match cx.tcx.def_map.find(&p.id) {
+      Some(def) => mark_def(cx, def),
+      None => ()
+    }
+    visit::visit_pat(p, cx, v);
+}
+
+fn visit_ty(t: @Ty, cx: @ctx, v: visit::vt<@ctx>) {
+    match t.node {
+      ty_path(_, id) => {
+        match cx.tcx.def_map.find(&id) {
+          Some(def) => mark_def(cx, def),
+          None => ()
+        }
+      }
+      _ => ()
+    }
+    visit::visit_ty(t, cx, v);
+}
+
+// Code that the compiler injects, such as the intrinsic module, comes
+// from pseudo-files named in angle brackets
+fn is_injected(cx: @ctx, sp: span) -> bool {
+    cx.tcx.sess.codemap.span_to_filename(sp).starts_with("<")
+}
+
+fn warn_dead(cx: @ctx, id: node_id, lint_id: node_id, sp: span,
+             kind: &str, name: ident) {
+    if cx.live.contains_key_ref(&id) || is_injected(cx, sp) { return; }
+    cx.tcx.sess.span_lint(dead_code, lint_id, lint_id, sp,
+                          fmt!("%s is never used: `%s`",
+                               kind, cx.tcx.sess.str_of(name)));
+}
+
+fn warn_about_dead_items(cx: @ctx, crate: @crate) {
+    visit::visit_crate(*crate, cx, visit::mk_vt(@visit::Visitor {
+        visit_item: |item, cx, v| {
+            match item.node {
+              item_fn(*) => {
+                warn_dead(cx, item.id, item.id, item.span, "function",
+                          item.ident);
+              }
+              item_const(*) => {
+                warn_dead(cx, item.id, item.id, item.span, "constant",
+                          item.ident);
+              }
+              item_struct(*) => {
+                warn_dead(cx, item.id, item.id, item.span, "struct",
+                          item.ident);
+              }
+              item_enum(ref enum_def, _) => {
+                if cx.live.contains_key_ref(&item.id) {
+                    for enum_def.variants.each |variant| {
+                        warn_dead(cx, variant.node.id, item.id, variant.span,
+                                  "variant", variant.node.name);
+                    }
+                } else {
+                    warn_dead(cx, item.id, item.id, item.span, "enum",
+                              item.ident);
+                }
+              }
+              item_impl(_, None, _, ref methods) => {
+                for methods.each |method| {
+                    warn_dead(cx, method.id, item.id, method.span, "method",
+                              method.ident);
+                }
+              }
+              _ => ()
+            }
+            visit::visit_item(item, cx, v);
+        },
+        .. *visit::default_visitor()
+    }));
+}
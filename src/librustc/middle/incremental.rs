@@ -0,0 +1,916 @@
+// Copyright 2013 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+/*!
+
+# Incremental compilation
+
+With `--incremental <dir>`, every build records in `dir` a hash of each
+item of the crate together with the items it depends on, and the LLVM
+module that it produced. The next build compares against that record to
+find the functions that need neither type checking nor translation again.
+
+## Items and their hashes
+
+The unit of reuse is the item: anything declared at module level. Each
+item gets a key that is stable across builds (its path, with a counter to
+tell apart impls and foreign modules) and a hash of its pretty-printed
+AST. Since macros have been expanded by then, a change that moves an
+item also changes the `line!()` expansions in it; it is otherwise
+insensitive to edits elsewhere in the file.
+
+## Dependencies
+
+An item depends on every local item that resolution binds one of its
+paths to, and on the items that type checking resolves its method calls
+and vtables to. The latter are only known after type checking, so for
+the functions we skip they are carried over from the previous build, in
+the manner of the "discovered inputs" of `std::workcache`.
+
+An item is *stale* if it is new, if its hash changed, or if anything it
+depends on is stale, transitively. Things which affect every item are
+folded into an environment hash that invalidates the whole cache: the
+options that influence code generation, the hashes of the crates we link
+against, the crate attributes, and the headers of all impls (adding an
+impl can change how any method call resolves, or give a type a
+destructor).
+
+## Reuse
+
+A non-generic function that is not stale and whose code is in the cached
+module is *reused*. Before type checking, its body is replaced with a
+stub that type-checks trivially; translation then only declares it, and
+`link_cached_code` links in its old definition. The old module keeps its
+definitions only where the new module has none, so its copies of
+everything we translated again are dropped.
+
+Since a reused function is not checked again, the lints and warnings that
+it would trigger are only reported by the build that translated it. The
+exception is dead_code, which looks at the whole crate: the cache records
+what each function refers to, under stable names, so that what a stub
+used to refer to stays live.
+
+Symbol names depend on type hashes, which in turn depend on node IDs, so
+a symbol may differ between builds even though its item did not change.
+The cache therefore records the symbol of every function under a stable
+name, and renames the old module's functions before linking.
+
+*/
+
+use core::prelude::*;
+
+use back::link;
+use driver::session::Session;
+use lib::llvm::{llvm, ModuleRef, ValueRef, True};
+use lib;
+use metadata::cstore;
+use middle::resolve;
+use middle::typeck::{method_map, method_param, method_self, method_static};
+use middle::typeck::{method_super, method_trait};
+use middle::typeck::{vtable_map, vtable_param, vtable_res, vtable_static};
+use middle::typeck::{vtable_trait};
+
+use core::dvec::DVec;
+use core::io;
+use core::libc::c_uint;
+use core::option;
+use core::os;
+use core::ptr;
+use core::result;
+use core::str;
+use core::vec;
+use std::json;
+use std::oldmap::HashMap;
+use std::serialize::{Decodable, Encodable};
+use std::sha1::sha1;
+use std::sort;
+use syntax::ast;
+use syntax::ast::{local_crate, node_id};
+use syntax::ast_util::{def_id_of_def, id_visitor};
+use syntax::attr;
+use syntax::codemap::spanned;
+use syntax::fold;
+use syntax::print::pprust;
+use syntax::visit;
+
+/// Bumped whenever the format of the cache changes.
+const cache_version: uint = 2;
+
+#[auto_encode]
+#[auto_decode]
+struct CachedSymbol {
+    name: ~str,
+    symbol: ~str
+}
+
+#[auto_encode]
+#[auto_decode]
+struct CachedItem {
+    key: ~str,
+    hash: ~str,
+    deps: ~[~str],
+    // The stable names of the local items, methods and variants that the
+    // item refers to, for the dead_code lint.
+    uses: ~[~str],
+    symbols: ~[CachedSymbol],
+    // Whether the cached module holds the code of this item.
+    has_code: bool
+}
+
+#[auto_encode]
+#[auto_decode]
+struct CacheIndex {
+    version: uint,
+    env_hash: ~str,
+    items: ~[CachedItem]
+}
+
+/// One item of the crate being compiled.
+struct Item {
+    key: ~str,
+    item: @ast::item,
+    hash: ~str,
+    // Indices of the items this one depends on.
+    deps: DVec<uint>,
+    // The local nodes this one refers to.
+    uses: DVec<node_id>,
+    // The functions defined by this item, under their stable names.
+    symbol_ids: ~[(node_id, ~str)],
+    mut stale: bool
+}
+
+pub struct Cache {
+    dir: Path,
+    env_hash: ~str,
+    previous: Option<CacheIndex>,
+    items: ~[@Item],
+    // Maps every node ID to the index of the item containing it.
+    owners: HashMap<node_id, uint>,
+    // The functions whose bodies were stubbed out.
+    reused: HashMap<node_id, ()>
+}
+
+pub impl Cache {
+    fn has_reused_items(&self) -> bool {
+        !self.reused.is_empty()
+    }
+
+    fn index_path(&self) -> Path { self.dir.push(~"index.json") }
+
+    fn module_path(&self) -> Path { self.dir.push(~"crate.bc") }
+}
+
+pure fn str_le(a: &~str, b: &~str) -> bool { *a <= *b }
+
+fn hash_str(s: &str) -> ~str {
+    let sha = sha1();
+    sha.input_str(s);
+    sha.result_str()
+}
+
+// Lists the items of a module, recursing into submodules, along with the
+// key of each.
+fn collect_items(sess: Session, module_: &ast::_mod, prefix: &str,
+                 keys: HashMap<~str, uint>,
+                 items: &mut ~[(~str, @ast::item)]) {
+    for module_.items.each |&item| {
+        let name = match item.node {
+            ast::item_mod(ref submodule) => {
+                let prefix = fmt!("%s%s::", prefix,
+                                  sess.str_of(item.ident));
+                collect_items(sess, submodule, prefix, keys, items);
+                loop;
+            }
+            ast::item_impl(_, ref trait_ref, self_ty, _) => {
+                let self_ty = pprust::ty_to_str(self_ty, sess.intr());
+                match *trait_ref {
+                    Some(trait_ref) => {
+                        fmt!("impl %s for %s",
+                             pprust::path_to_str(trait_ref.path,
+                                                 sess.intr()),
+                             self_ty)
+                    }
+                    None => fmt!("impl %s", self_ty)
+                }
+            }
+            ast::item_foreign_mod(*) => ~"extern",
+            _ => sess.str_of(item.ident)
+        };
+
+        let mut key = fmt!("%s%s", prefix, name);
+        match keys.find(&key) {
+            None => {
+                keys.insert(copy key, 0);
+            }
+            Some(n) => {
+                keys.insert(copy key, n + 1);
+                key = fmt!("%s#%u", key, n + 1);
+            }
+        }
+        items.push((key, item));
+    }
+}
+
+// Returns the node IDs of the functions an item defines, each with a name
+// that is stable across builds.
+fn symbol_ids(sess: Session, key: &str, item: @ast::item)
+           -> ~[(node_id, ~str)] {
+    let mut ids = ~[];
+    match item.node {
+        ast::item_fn(*) | ast::item_const(*) => {
+            ids.push((item.id, key.to_owned()));
+        }
+        ast::item_impl(_, _, _, ref methods) => {
+            for methods.each |method| {
+                ids.push((method.id,
+                          fmt!("%s::%s", key, sess.str_of(method.ident))));
+            }
+        }
+        ast::item_trait(_, _, ref methods) => {
+            for methods.each |method| {
+                match *method {
+                    ast::provided(method) => {
+                        ids.push((method.id,
+                                  fmt!("%s::%s", key,
+                                       sess.str_of(method.ident))));
+                    }
+                    ast::required(_) => {}
+                }
+            }
+        }
+        ast::item_enum(ref enum_definition, _) => {
+            for enum_definition.variants.each |variant| {
+                ids.push((variant.node.id,
+                          fmt!("%s::%s", key,
+                               sess.str_of(variant.node.name))));
+            }
+        }
+        ast::item_struct(struct_def, _) => {
+            match struct_def.ctor_id {
+                Some(ctor_id) => ids.push((ctor_id, fmt!("%s::()", key))),
+                None => {}
+            }
+            match struct_def.dtor {
+                Some(ref dtor) => {
+                    ids.push((dtor.node.id, fmt!("%s::drop", key)));
+                }
+                None => {}
+            }
+        }
+        _ => {}
+    }
+    return ids;
+}
+
+// Only plain functions are reused: generic and inline functions are
+// instantiated wherever they are used, and the bodies of methods are
+// translated along with their impl.
+fn is_reusable(item: @ast::item) -> bool {
+    match item.node {
+        ast::item_fn(_, purity, ref tps, _) => {
+            purity != ast::extern_fn && tps.is_empty() &&
+                attr::find_inline_attr(item.attrs) == attr::ia_none
+        }
+        _ => false
+    }
+}
+
+// Hashes everything that affects all items at once.
+fn environment_hash(sess: Session, crate: @ast::crate,
+                    items: &[(~str, @ast::item)]) -> ~str {
+    let sha = sha1();
+    sha.input_str(fmt!("%u", cache_version));
+    sha.input_str(fmt!("%? %? %? %? %? %? %u %u",
+                       sess.opts.crate_type, sess.opts.optimize,
+                       sess.opts.debuginfo, sess.opts.extra_debuginfo,
+                       sess.opts.test, sess.opts.static,
+                       sess.opts.debugging_opts, sess.opts.codegen_units));
+    // The target, which a target specification file describes in more
+    // detail than its triple
+    let targ_cfg = sess.targ_cfg;
+    sha.input_str(fmt!("%? %? %? %? %? %?",
+                       targ_cfg.os, targ_cfg.arch, targ_cfg.int_type,
+                       targ_cfg.target_family, targ_cfg.linker,
+                       targ_cfg.morestack));
+    sha.input_str(targ_cfg.target_strs.target_triple);
+    sha.input_str(targ_cfg.target_strs.data_layout);
+    for targ_cfg.target_strs.cc_args.each |arg| {
+        sha.input_str(*arg);
+    }
+    for sess.opts.cfg.each |meta_item| {
+        sha.input_str(pprust::meta_item_to_str(*meta_item, sess.intr()));
+    }
+    for crate.node.attrs.each |attr| {
+        sha.input_str(pprust::attribute_to_str(*attr, sess.intr()));
+    }
+    for cstore::get_dep_hashes(sess.cstore).each |hash| {
+        sha.input_str(*hash);
+    }
+    for items.each |keyed_item| {
+        let (key, item) = copy *keyed_item;
+        match item.node {
+            ast::item_impl(*) => sha.input_str(key),
+            _ => {}
+        }
+    }
+    sha.result_str()
+}
+
+fn load_index(path: &Path) -> Option<CacheIndex> {
+    if !os::path_exists(path) {
+        return None;
+    }
+    let s = match io::read_whole_file_str(path) {
+        result::Ok(move s) => s,
+        result::Err(_) => return None
+    };
+    match json::from_str(s) {
+        result::Ok(move j) => {
+            Some(Decodable::decode(&json::Decoder(move j)))
+        }
+        result::Err(_) => None
+    }
+}
+
+/**
+ * Hashes the items of the crate and compares them against the cache. Must
+ * run after resolution, whose def map gives us the dependencies. Returns
+ * None if incremental compilation is off.
+ */
+pub fn load(sess: Session, crate: @ast::crate, def_map: resolve::DefMap)
+         -> Option<@Cache> {
+    let dir = match sess.opts.incremental {
+        Some(copy dir) => dir,
+        None => return None
+    };
+
+    let mut keyed_items = ~[];
+    collect_items(sess, &crate.node.module, "", HashMap(),
+                  &mut keyed_items);
+
+    let owners = HashMap();
+    let mut items = ~[];
+    let mut item_ids = ~[];
+    for keyed_items.eachi |i, keyed_item| {
+        let (key, item) = copy *keyed_item;
+        let src = pprust::item_to_str(item, sess.intr());
+        items.push(@Item {
+            key: copy key,
+            item: item,
+            hash: hash_str(src),
+            deps: DVec(),
+            uses: DVec(),
+            symbol_ids: symbol_ids(sess, key, item),
+            stale: false
+        });
+
+        let ids = @DVec();
+        visit::visit_item(item, (), id_visitor(|id| {
+            owners.insert(id, i);
+            ids.push(id);
+        }));
+        item_ids.push(ids.get());
+    }
+
+    // Record the items that each item names.
+    for item_ids.eachi |i, ids| {
+        for ids.each |id| {
+            match def_map.find(id) {
+                Some(def) => {
+                    let def_id = def_id_of_def(def);
+                    if def_id.crate == local_crate {
+                        add_dep(items, owners, i, def_id.node);
+                        items[i].uses.push(def_id.node);
+                    }
+                }
+                None => {}
+            }
+        }
+    }
+
+    let env_hash = environment_hash(sess, crate, keyed_items);
+    let previous = match load_index(&dir.push(~"index.json")) {
+        Some(move index) => {
+            if index.version == cache_version && index.env_hash == env_hash {
+                Some(move index)
+            } else {
+                None
+            }
+        }
+        None => None
+    };
+
+    let cache = @Cache {
+        dir: dir,
+        env_hash: env_hash,
+        previous: previous,
+        items: items,
+        owners: owners,
+        reused: HashMap()
+    };
+    find_reused_items(cache);
+    if sess.verbose() {
+        for cache.items.each |item| {
+            if cache.reused.contains_key_ref(&item.item.id) {
+                sess.note(fmt!("reusing the code of `%s`", item.key));
+            }
+        }
+    }
+    return Some(cache);
+}
+
+// The stable name of every item of the crate, and of every function and
+// variant they define.
+fn stable_names(items: &[@Item]) -> ~[(node_id, ~str)] {
+    let mut names = ~[];
+    for items.each |item| {
+        names.push((item.item.id, copy item.key));
+        names.push_all(item.symbol_ids);
+    }
+    names
+}
+
+/**
+ * What the reused functions referred to before their bodies were stubbed
+ * out, for the dead_code lint.
+ */
+pub fn stubbed_uses(cache: @Cache) -> HashMap<node_id, ~[node_id]> {
+    let uses = HashMap();
+    for cache.items.each |item| {
+        if cache.reused.contains_key_ref(&item.item.id) {
+            uses.insert(item.item.id, item.uses.get());
+        }
+    }
+    uses
+}
+
+fn add_dep(items: &[@Item], owners: HashMap<node_id, uint>,
+           from: uint, to: node_id) {
+    match owners.find(&to) {
+        Some(to) if to != from && !items[from].deps.get().contains(&to) => {
+            items[from].deps.push(to);
+        }
+        _ => {}
+    }
+}
+
+// Marks the stale items, and decides which functions to reuse.
+fn find_reused_items(cache: @Cache) {
+    let previous = match cache.previous {
+        Some(ref index) => index,
+        None => {
+            for cache.items.each |item| { item.stale = true; }
+            return;
+        }
+    };
+
+    let indices = HashMap();
+    for cache.items.eachi |i, item| {
+        indices.insert(copy item.key, i);
+    }
+    let cached = HashMap();
+    for previous.items.eachi |k, cached_item| {
+        cached.insert(copy cached_item.key, k);
+    }
+    let ids = HashMap();
+    for stable_names(cache.items).each |&(id, ref name)| {
+        ids.insert(copy *name, id);
+    }
+
+    // The dependencies discovered by type checking last time. Deleted
+    // dependencies make an item stale.
+    let discovered = vec::from_fn(cache.items.len(), |_| DVec());
+    for cache.items.eachi |i, item| {
+        match cached.find(&item.key) {
+            Some(k) if previous.items[k].hash == item.hash => {
+                for previous.items[k].deps.each |dep| {
+                    match indices.find(dep) {
+                        Some(j) => discovered[i].push(j),
+                        None => item.stale = true
+                    }
+                }
+            }
+            _ => item.stale = true
+        }
+    }
+
+    // Propagate staleness to dependents until nothing changes.
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for cache.items.eachi |i, item| {
+            if item.stale {
+                loop;
+            }
+            let deps = vec::append(item.deps.get(), discovered[i].get());
+            if deps.any(|&j| cache.items[j].stale) {
+                item.stale = true;
+                changed = true;
+            }
+        }
+    }
+
+    for cache.items.eachi |i, item| {
+        let has_code = match cached.find(&item.key) {
+            Some(k) => previous.items[k].has_code,
+            None => false
+        };
+        if !item.stale && has_code && is_reusable(item.item) {
+            // Type checking won't discover anything in a stub, so keep
+            // what it found last time.
+            for discovered[i].get().each |&j| {
+                if !item.deps.get().contains(&j) {
+                    item.deps.push(j);
+                }
+            }
+            match cached.find(&item.key) {
+                Some(k) => {
+                    for previous.items[k].uses.each |name| {
+                        match ids.find(name) {
+                            Some(id) => item.uses.push(id),
+                            None => {}
+                        }
+                    }
+                }
+                None => {}
+            }
+            cache.reused.insert(item.item.id, ());
+        }
+    }
+}
+
+/**
+ * Replaces the bodies of the reused functions with `loop {}`, which
+ * type-checks whatever the function returns. The arguments of such a
+ * function are unused, so the variables they bind are renamed to start
+ * with `_`. Lints and warnings about the original body are therefore not
+ * reported again. The stubs keep the node IDs of the functions, and with
+ * them their lint settings.
+ */
+pub fn stub_reused_items(cache: @Cache, sess: Session, crate: @ast::crate)
+                      -> @ast::crate {
+    let precursor = @fold::AstFoldFns {
+        fold_item: |item, fld| {
+            if cache.reused.contains_key_ref(&item.id) {
+                Some(stub_item(sess, item))
+            } else {
+                fold::noop_fold_item(item, fld)
+            }
+        },
+        .. *fold::default_ast_fold()
+    };
+    let fold = fold::make_fold(precursor);
+    return @fold.fold_crate(*crate);
+}
+
+fn stub_item(sess: Session, item: @ast::item) -> @ast::item {
+    match item.node {
+        ast::item_fn(ref decl, purity, ref tps, ref body) => {
+            let sp = body.span;
+            let loop_body = spanned {
+                node: ast::blk_ {
+                    view_items: ~[],
+                    stmts: ~[],
+                    expr: None,
+                    id: sess.next_node_id(),
+                    rules: ast::default_blk
+                },
+                span: sp
+            };
+            let loop_expr = @ast::expr {
+                id: sess.next_node_id(),
+                callee_id: sess.next_node_id(),
+                node: ast::expr_loop(loop_body, None),
+                span: sp
+            };
+            let body = spanned {
+                node: ast::blk_ {
+                    view_items: ~[],
+                    stmts: ~[],
+                    expr: Some(loop_expr),
+                    id: body.node.id,
+                    rules: ast::default_blk
+                },
+                span: sp
+            };
+            @ast::item {
+                node: ast::item_fn(hide_unused_args(sess, decl), purity,
+                                   copy *tps, body),
+                .. copy *item
+            }
+        }
+        _ => sess.bug(~"stub_item: not a function")
+    }
+}
+
+// Renames the variables bound by the arguments of `decl` to start with `_`,
+// which keeps liveness from warning that they are unused.
+fn hide_unused_args(sess: Session, decl: &ast::fn_decl) -> ast::fn_decl {
+    let precursor = @fold::AstFoldFns {
+        fold_pat: |pat, span, fld| {
+            match pat {
+                ast::pat_ident(mode, path, sub)
+                        if !path.global && path.idents.len() == 1u => {
+                    let name = ~"_" + sess.str_of(path.idents[0]);
+                    let path = @ast::path {
+                        idents: ~[sess.ident_of(name)],
+                        .. copy *path
+                    };
+                    (ast::pat_ident(mode, path,
+                                    option::map(&sub,
+                                                |p| fld.fold_pat(*p))),
+                     span)
+                }
+                _ => (fold::noop_fold_pat(pat, fld), span)
+            }
+        },
+        .. *fold::default_ast_fold()
+    };
+    let fld = fold::make_fold(precursor);
+    ast::fn_decl {
+        inputs: decl.inputs.map(|arg| ast::arg {
+            pat: fld.fold_pat(arg.pat),
+            .. copy *arg
+        }),
+        .. copy *decl
+    }
+}
+
+/**
+ * Adds the dependencies that type checking found through method calls
+ * and vtables.
+ */
+pub fn record_method_deps(cache: @Cache,
+                          method_map: method_map,
+                          vtable_map: vtable_map) {
+    for method_map.each_ref |&id, &entry| {
+        match cache.owners.find(&id) {
+            Some(from) => {
+                let def_id = match entry.origin {
+                    method_static(def_id) |
+                    method_super(def_id, _) |
+                    method_trait(def_id, _, _) |
+                    method_self(def_id, _) => def_id,
+                    method_param(method_param { trait_id: def_id, _ }) => {
+                        def_id
+                    }
+                };
+                if def_id.crate == local_crate {
+                    add_dep(cache.items, cache.owners, from, def_id.node);
+                    cache.items[from].uses.push(def_id.node);
+                }
+            }
+            None => {}
+        }
+    }
+
+    for vtable_map.each_ref |&id, &vtables| {
+        match cache.owners.find(&id) {
+            Some(from) => record_vtable_deps(cache, from, vtables),
+            None => {}
+        }
+    }
+}
+
+fn record_vtable_deps(cache: @Cache, from: uint, vtables: vtable_res) {
+    for vec::each(*vtables) |vtable| {
+        match *vtable {
+            vtable_static(def_id, _, sub_vtables) => {
+                if def_id.crate == local_crate {
+                    add_dep(cache.items, cache.owners, from, def_id.node);
+                }
+                record_vtable_deps(cache, from, sub_vtables);
+            }
+            vtable_trait(def_id, _) => {
+                if def_id.crate == local_crate {
+                    add_dep(cache.items, cache.owners, from, def_id.node);
+                }
+            }
+            vtable_param(*) => {}
+        }
+    }
+}
+
+unsafe fn value_name(val: ValueRef) -> ~str {
+    str::raw::from_c_str(llvm::LLVMGetValueName(val))
+}
+
+unsafe fn is_definition(val: ValueRef) -> bool {
+    val != ptr::null() && llvm::LLVMIsDeclaration(val) != True
+}
+
+unsafe fn is_local(val: ValueRef) -> bool {
+    let linkage = llvm::LLVMGetLinkage(val);
+    linkage == lib::llvm::InternalLinkage as c_uint ||
+        linkage == lib::llvm::PrivateLinkage as c_uint
+}
+
+/**
+ * Links the code of the reused functions from the cached module into
+ * `llmod`. `item_symbols` are the symbols that translation gave to items.
+ */
+pub fn link_cached_code(cache: @Cache, sess: Session, llmod: ModuleRef,
+                        item_symbols: HashMap<node_id, ~str>) {
+    if !cache.has_reused_items() {
+        return;
+    }
+
+    unsafe {
+        let path = cache.module_path();
+        let llbuf = do str::as_c_str(path.to_str()) |buf| {
+            llvm::LLVMRustCreateMemoryBufferWithContentsOfFile(buf)
+        };
+        if llbuf == ptr::null() {
+            link::llvm_err(sess, fmt!("failed to read `%s`", path.to_str()));
+        }
+        let oldmod = llvm::LLVMRustParseBitcode(llbuf);
+        llvm::LLVMDisposeMemoryBuffer(llbuf);
+        if oldmod == ptr::null() {
+            link::llvm_err(sess, fmt!("failed to parse `%s`",
+                                      path.to_str()));
+        }
+
+        rename_old_symbols(cache, oldmod, item_symbols);
+
+        // The old module's metadata must not end up in the output.
+        let used = str::as_c_str(~"llvm.used", |buf| {
+            llvm::LLVMGetNamedGlobal(oldmod, buf)
+        });
+        if used != ptr::null() {
+            llvm::LLVMDeleteGlobal(used);
+        }
+
+        // Let the new module's definitions win. `available_externally`
+        // keeps the old ones visible to the optimizer, but the linker
+        // drops them.
+        let mut llfn = llvm::LLVMGetFirstFunction(oldmod);
+        while llfn != ptr::null() {
+            if is_definition(llfn) && !is_local(llfn) {
+                let newfn = str::as_c_str(value_name(llfn), |buf| {
+                    llvm::LLVMGetNamedFunction(llmod, buf)
+                });
+                if is_definition(newfn) {
+                    lib::llvm::SetLinkage(
+                        llfn, lib::llvm::AvailableExternallyLinkage);
+                }
+            }
+            llfn = llvm::LLVMGetNextFunction(llfn);
+        }
+        let mut llglobal = llvm::LLVMGetFirstGlobal(oldmod);
+        while llglobal != ptr::null() {
+            if is_definition(llglobal) && !is_local(llglobal) {
+                let newglobal = str::as_c_str(value_name(llglobal), |buf| {
+                    llvm::LLVMGetNamedGlobal(llmod, buf)
+                });
+                if is_definition(newglobal) {
+                    lib::llvm::SetLinkage(
+                        llglobal, lib::llvm::AvailableExternallyLinkage);
+                }
+            }
+            llglobal = llvm::LLVMGetNextGlobal(llglobal);
+        }
+
+        // Throw away whatever the reused functions don't need, so the
+        // cache doesn't grow from one build to the next.
+        let llpm = llvm::LLVMCreatePassManager();
+        llvm::LLVMAddGlobalDCEPass(llpm);
+        llvm::LLVMRunPassManager(llpm, oldmod);
+        llvm::LLVMDisposePassManager(llpm);
+
+        if !llvm::LLVMRustLinkModules(llmod, oldmod) {
+            link::llvm_err(sess, ~"failed to link the incremental cache");
+        }
+    }
+}
+
+// Gives the functions of the old module the symbols that their items have
+// now. The old names are cleared first, since one item may well have
+// taken over the symbol of another.
+unsafe fn rename_old_symbols(cache: @Cache, oldmod: ModuleRef,
+                             item_symbols: HashMap<node_id, ~str>) {
+    let old_symbols = HashMap();
+    match cache.previous {
+        Some(ref index) => {
+            for index.items.each |cached_item| {
+                for cached_item.symbols.each |symbol| {
+                    old_symbols.insert(copy symbol.name, copy symbol.symbol);
+                }
+            }
+        }
+        None => return
+    }
+
+    let mut renames = ~[];
+    for cache.items.each |item| {
+        for item.symbol_ids.each |&(id, ref name)| {
+            match (old_symbols.find(name), item_symbols.find(&id)) {
+                (Some(ref old), Some(ref new)) if *old != *new => {
+                    let llfn = str::as_c_str(*old, |buf| {
+                        llvm::LLVMGetNamedFunction(oldmod, buf)
+                    });
+                    if llfn != ptr::null() {
+                        renames.push((llfn, copy *new));
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    for renames.each |&(llfn, _)| {
+        str::as_c_str(~"", |buf| llvm::LLVMSetValueName(llfn, buf));
+    }
+    for renames.each |&(llfn, ref new)| {
+        str::as_c_str(*new, |buf| llvm::LLVMSetValueName(llfn, buf));
+    }
+}
+
+/**
+ * Writes the translated module and the index to the cache directory, for
+ * the next build to compare against.
+ */
+pub fn save(cache: @Cache, sess: Session, llmod: ModuleRef,
+            item_symbols: HashMap<node_id, ~str>) {
+    if !os::path_is_dir(&cache.dir) &&
+            !os::make_dir(&cache.dir, 0x1ed) { // 755
+        sess.fatal(fmt!("could not create incremental cache directory \
+                         `%s`", cache.dir.to_str()));
+    }
+
+    // Without its module, an index would send the next build looking for
+    // code that isn't there
+    let path = cache.module_path();
+    let written = unsafe {
+        do str::as_c_str(path.to_str()) |buf| {
+            llvm::LLVMWriteBitcodeToFile(llmod, buf) == 0
+        }
+    };
+    if !written {
+        sess.warn(fmt!("could not write `%s`; the next build won't reuse \
+                        anything", path.to_str()));
+        os::remove_file(&cache.index_path());
+        return;
+    }
+
+    let names = HashMap();
+    for stable_names(cache.items).each |&(id, ref name)| {
+        names.insert(id, copy *name);
+    }
+
+    let mut cached_items = ~[];
+    for cache.items.each |item| {
+        let deps = item.deps.get().map(|&j| copy cache.items[j].key);
+        let deps = sort::merge_sort(deps, str_le);
+        let mut uses = ~[];
+        for item.uses.each |id| {
+            match names.find(id) {
+                Some(name) => {
+                    if !uses.contains(&name) { uses.push(name); }
+                }
+                None => {}
+            }
+        }
+        let uses = sort::merge_sort(uses, str_le);
+        let mut symbols = ~[];
+        for item.symbol_ids.each |&(id, ref name)| {
+            match item_symbols.find(&id) {
+                Some(symbol) => {
+                    symbols.push(CachedSymbol {
+                        name: copy *name,
+                        symbol: symbol
+                    });
+                }
+                None => {}
+            }
+        }
+        cached_items.push(CachedItem {
+            key: copy item.key,
+            hash: copy item.hash,
+            deps: deps,
+            uses: uses,
+            has_code: is_reusable(item.item) &&
+                item_symbols.contains_key_ref(&item.item.id),
+            symbols: symbols
+        });
+    }
+
+    let index = CacheIndex {
+        version: cache_version,
+        env_hash: copy cache.env_hash,
+        items: cached_items
+    };
+    let path = cache.index_path();
+    match io::file_writer(&path, ~[io::Create, io::Truncate]) {
+        result::Ok(writer) => index.encode(&json::Encoder(writer)),
+        result::Err(e) => {
+            sess.fatal(fmt!("could not write `%s`: %s", path.to_str(), e))
+        }
+    }
+}
@@ -12,8 +12,10 @@ use core::prelude::*;
 
 use driver::session::Session;
 use driver::session;
+use metadata::csearch;
 use middle::pat_util::{pat_bindings};
 use middle::ty;
+use middle::typeck;
 use util::ppaux::{ty_to_str};
 
 use core::char;
@@ -25,6 +27,7 @@ use core::i32;
 use core::i64;
 use core::int;
 use core::io::WriterUtil;
+use core::io;
 use core::str;
 use core::u8;
 use core::u16;
@@ -32,11 +35,13 @@ use core::u32;
 use core::u64;
 use core::uint;
 use core::vec;
+use std::json;
 use std::oldmap::{Map, HashMap};
 use std::oldmap;
 use std::oldsmallintmap::{Map, SmallIntMap};
 use std::oldsmallintmap;
 use syntax::ast_util::{path_to_ident};
+use syntax::ast_map;
 use syntax::attr;
 use syntax::codemap::span;
 use syntax::codemap;
@@ -88,6 +93,9 @@ pub enum lint {
 
     legacy_modes,
 
+    dead_code,
+    unused_result,
+
     // FIXME(#3266)--make liveness warnings lintable
     // unused_variable,
     // dead_assignment
@@ -221,6 +229,17 @@ pub fn get_lint_dict() -> lint_dict {
            desc: "warn about deprecated uses of `self`",
            default: warn}),
 
+        (~"dead_code",
+         @{lint: dead_code,
+           desc: "items that are never used",
+           default: warn}),
+
+        (~"unused_result",
+         @{lint: unused_result,
+           desc: "results of #[must_use] types and functions that are \
+                  discarded",
+           default: warn}),
+
         /* FIXME(#3266)--make liveness warnings lintable
         (~"unused_variable",
          @{lint: unused_variable,
@@ -236,6 +255,139 @@ pub fn get_lint_dict() -> lint_dict {
     oldmap::hash_from_vec(v)
 }
 
+/// The name under which a lint is known to attributes and the command line
+pub fn lint_to_str(lint: lint) -> ~str {
+    for get_lint_dict().each_ref |&k, &v| {
+        if v.lint == lint { return copy k; }
+    }
+    die!(~"lint missing from the lint dictionary")
+}
+
+type lint_group = @{lints: ~[lint],
+                    desc: &static/str};
+
+pub type lint_group_dict = HashMap<~str,lint_group>;
+
+/// Named sets of lints, usable wherever the name of a lint is
+pub fn get_lint_groups() -> lint_group_dict {
+    let v = ~[
+        (~"memory",
+         @{lints: ~[managed_heap_memory, owned_heap_memory, heap_memory],
+           desc: "all uses of heap memory"}),
+
+        (~"deprecated",
+         @{lints: ~[deprecated_mode, deprecated_pattern, deprecated_self],
+           desc: "all uses of deprecated features"}),
+    ];
+    oldmap::hash_from_vec(v)
+}
+
+/// Looks up a lint or a lint group by name, returning the lints it covers
+pub fn find_lints(dict: lint_dict, groups: lint_group_dict, name: &str)
+               -> Option<~[lint]> {
+    let name = name.to_owned();
+    match dict.find(&name) {
+      Some(spec) => Some(~[spec.lint]),
+      None => groups.find(&name).map(|group| copy group.lints)
+    }
+}
+
+pub fn level_from_str(s: &str) -> Option<level> {
+    for [allow, warn, deny, forbid].each |&level| {
+        if level_to_str(level) == s { return Some(level); }
+    }
+    None
+}
+
+/**
+ * Reads the lint levels of a `--lint-config` file. The file is either a
+ * JSON object mapping names of lints or lint groups to levels, or a list
+ * of lines of the form `name = level`, in which `#` starts a comment and
+ * a `[lints]` header is allowed:
+ *
+ *     [lints]
+ *     memory = "deny"
+ *     non-camel-case-types = warn  # for now
+ *
+ * Returns the names, with dashes replaced by underscores, and their
+ * levels; groups come first so that the lints in them can be overridden.
+ */
+pub fn read_lint_config(path: &Path) -> Result<~[(~str, level)], ~str> {
+    let src = match io::read_whole_file_str(path) {
+      Ok(move src) => src,
+      Err(move e) => return Err(e)
+    };
+    let parsed = if str::trim_left(src).starts_with("{") {
+        parse_json_lint_config(src)
+    } else {
+        parse_lint_config_lines(src)
+    };
+    let levels = match move parsed {
+      Ok(move levels) => levels,
+      Err(move e) => return Err(e)
+    };
+    let groups = get_lint_groups();
+    let (in_groups, lints) = do vec::partition(levels) |entry| {
+        let (ref name, _) = *entry;
+        groups.contains_key_ref(name)
+    };
+    Ok(vec::append(in_groups, lints))
+}
+
+fn parse_json_lint_config(src: &str) -> Result<~[(~str, level)], ~str> {
+    let obj = match json::from_str(src) {
+      Ok(json::Object(move obj)) => obj,
+      Ok(_) => return Err(~"expected a JSON object"),
+      Err(e) => return Err(fmt!("%u:%u: %s", e.line, e.col, *e.msg))
+    };
+    let mut levels = ~[];
+    for obj.each |&(name, value)| {
+        let name = str::replace(*name, ~"-", ~"_");
+        match *value {
+          json::String(ref s) => {
+            match level_from_str(*s) {
+              Some(level) => levels.push((name, level)),
+              None => {
+                return Err(fmt!("unknown lint level `%s` for `%s`",
+                                *s, name));
+              }
+            }
+          }
+          _ => return Err(fmt!("expected a lint level for `%s`", name))
+        }
+    }
+    Ok(levels)
+}
+
+fn parse_lint_config_lines(src: &str) -> Result<~[(~str, level)], ~str> {
+    let mut levels = ~[];
+    for str::lines_any(src).eachi |i, line| {
+        let line = match str::find_char(*line, '#') {
+          Some(pos) => line.slice(0, pos),
+          None => copy *line
+        };
+        let line = str::trim(line);
+        if line.is_empty() || line == ~"[lints]" { loop; }
+        match str::find_char(line, '=') {
+          Some(pos) => {
+            let name = str::trim(line.slice(0, pos));
+            let name = str::replace(name, ~"-", ~"_");
+            let value = str::trim(line.slice(pos + 1, line.len()));
+            let value = str::trim_chars(value, ['"']);
+            match level_from_str(value) {
+              Some(level) => levels.push((name, level)),
+              None => {
+                return Err(fmt!("line %u: unknown lint level `%s`",
+                                i + 1, value));
+              }
+            }
+          }
+          None => return Err(fmt!("line %u: expected `name = level`", i + 1))
+        }
+    }
+    Ok(levels)
+}
+
 // This is a highly not-optimal set of data structure decisions.
 type lint_modes = SmallIntMap<level>;
 type lint_mode_map = HashMap<ast::node_id, lint_modes>;
@@ -279,6 +431,7 @@ fn clone_lint_modes(modes: lint_modes) -> lint_modes {
 }
 
 type ctxt_ = {dict: lint_dict,
+              groups: lint_group_dict,
               curr: lint_modes,
               is_default: bool,
               sess: Session};
@@ -300,8 +453,8 @@ impl ctxt {
         }
     }
 
-    fn span_lint(level: level, span: span, +msg: ~str) {
-        self.sess.span_lint_level(level, span, msg);
+    fn span_lint(lint: lint, level: level, span: span, +msg: ~str) {
+        self.sess.span_lint_level(lint, level, span, msg);
     }
 
     /**
@@ -346,24 +499,27 @@ impl ctxt {
 
         for triples.each |pair| {
             let (meta, level, lintname) = /*bad*/copy *pair;
-            match self.dict.find(&lintname) {
+            match find_lints(self.dict, self.groups, lintname) {
               None => {
                 self.span_lint(
+                    unrecognized_lint,
                     new_ctxt.get_level(unrecognized_lint),
                     meta.span,
                     fmt!("unknown `%s` attribute: `%s`",
                          level_to_str(level), lintname));
               }
-              Some(lint) => {
-
-                if new_ctxt.get_level(lint.lint) == forbid &&
-                    level != forbid {
-                    self.span_lint(
-                        forbid,
-                        meta.span,
-                        fmt!("%s(%s) overruled by outer forbid(%s)",
-                             level_to_str(level),
-                             lintname, lintname));
+              Some(lints) => {
+                for lints.each |&lint| {
+                    if new_ctxt.get_level(lint) == forbid &&
+                        level != forbid {
+                        self.span_lint(
+                            lint,
+                            forbid,
+                            meta.span,
+                            fmt!("%s(%s) overruled by outer forbid(%s)",
+                                 level_to_str(level),
+                                 lintname, lint_to_str(lint)));
+                    }
                 }
 
                 // we do multiple unneeded copies of the
@@ -375,7 +531,9 @@ impl ctxt {
                     ctxt_({is_default: false,
                            curr: c,
                            .. *new_ctxt});
-                new_ctxt.set_level(lint.lint, level);
+                for lints.each |&lint| {
+                    new_ctxt.set_level(lint, level);
+                }
               }
             }
         }
@@ -395,6 +553,7 @@ fn build_settings_item(i: @ast::item, &&cx: ctxt, v: visit::vt<ctxt>) {
 
 pub fn build_settings_crate(sess: session::Session, crate: @ast::crate) {
     let cx = ctxt_({dict: get_lint_dict(),
+                    groups: get_lint_groups(),
                     curr: oldsmallintmap::mk(),
                     is_default: true,
                     sess: sess});
@@ -428,10 +587,11 @@ pub fn build_settings_crate(sess: session::Session, crate: @ast::crate) {
     sess.abort_if_errors();
 }
 
-fn check_item(i: @ast::item, cx: ty::ctxt) {
+fn check_item(i: @ast::item, cx: ty::ctxt, method_map: typeck::method_map) {
     check_item_ctypes(cx, i);
     check_item_while_true(cx, i);
     check_item_path_statement(cx, i);
+    check_item_unused_result(cx, method_map, i);
     check_item_non_camel_case_types(cx, i);
     check_item_heap(cx, i);
     check_item_structural_records(cx, i);
@@ -825,6 +985,92 @@ fn check_item_path_statement(cx: ty::ctxt, it: @ast::item) {
     visit::visit_item(it, (), visit);
 }
 
+// Whether the item `did` is marked #[must_use]. Attributes of items from
+// other crates come from their metadata.
+fn is_must_use(cx: ty::ctxt, did: ast::def_id) -> bool {
+    if did.crate == ast::local_crate {
+        match cx.items.find(&did.node) {
+          Some(ast_map::node_item(it, _)) => {
+            attr::attrs_contains_name(it.attrs, "must_use")
+          }
+          Some(ast_map::node_method(m, _, _)) => {
+            attr::attrs_contains_name(m.attrs, "must_use")
+          }
+          _ => false
+        }
+    } else {
+        let mut found = false;
+        do csearch::get_item_attrs(cx.cstore, did) |meta_items| {
+            if attr::contains_name(meta_items, "must_use") { found = true; }
+        }
+        found
+    }
+}
+
+fn check_item_unused_result(cx: ty::ctxt, method_map: typeck::method_map,
+                            it: @ast::item) {
+    // The function or method an expression calls, if it is known statically
+    fn callee_def_id(cx: ty::ctxt, method_map: typeck::method_map,
+                     e: @ast::expr) -> Option<ast::def_id> {
+        match e.node {
+          ast::expr_call(callee, _, _) => {
+            match cx.def_map.find(&callee.id) {
+              Some(ast::def_fn(did, _)) |
+              Some(ast::def_static_method(did, _, _)) => Some(did),
+              _ => None
+            }
+          }
+          ast::expr_method_call(*) => {
+            match method_map.find(&e.id) {
+              Some(typeck::method_map_entry {
+                  origin: typeck::method_static(did),
+                  _
+                }) => Some(did),
+              _ => None
+            }
+          }
+          _ => None
+        }
+    }
+
+    let visit = item_stopping_visitor(
+        visit::mk_simple_visitor(@visit::SimpleVisitor {
+            visit_stmt: |s: @ast::stmt| {
+                match s.node {
+                  ast::stmt_semi(e, id) => {
+                    let t = ty::expr_ty(cx, e);
+                    let type_must_use = match ty::get(t).sty {
+                      ty::ty_enum(did, _) | ty::ty_struct(did, _) => {
+                        is_must_use(cx, did)
+                      }
+                      _ => false
+                    };
+                    if type_must_use {
+                        cx.sess.span_lint(
+                            unused_result, id, it.id, s.span,
+                            fmt!("unused result of type `%s` which must \
+                                  be used", ty_to_str(cx, t)));
+                    } else {
+                        match callee_def_id(cx, method_map, e) {
+                          Some(did) if is_must_use(cx, did) => {
+                            cx.sess.span_lint(
+                                unused_result, id, it.id, s.span,
+                                fmt!("unused return value of `%s` which \
+                                      must be used",
+                                     ty::item_path_str(cx, did)));
+                          }
+                          _ => ()
+                        }
+                    }
+                  }
+                  _ => ()
+                }
+            },
+            .. *visit::default_simple_visitor()
+        }));
+    visit::visit_item(it, (), visit);
+}
+
 fn check_item_non_camel_case_types(cx: ty::ctxt, it: @ast::item) {
     fn is_camel_case(cx: ty::ctxt, ident: ast::ident) -> bool {
         let ident = cx.sess.str_of(ident);
@@ -991,10 +1237,11 @@ fn check_item_deprecated_modes(tcx: ty::ctxt, it: @ast::item) {
     }
 }
 
-pub fn check_crate(tcx: ty::ctxt, crate: @ast::crate) {
+pub fn check_crate(tcx: ty::ctxt, method_map: typeck::method_map,
+                   crate: @ast::crate) {
     let v = visit::mk_simple_visitor(@visit::SimpleVisitor {
         visit_item: |it|
-            check_item(it, tcx),
+            check_item(it, tcx, method_map),
         visit_fn: |fk, decl, body, span, id|
             check_fn(tcx, fk, decl, body, span, id),
         .. *visit::default_simple_visitor()
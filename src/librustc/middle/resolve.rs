@@ -14,7 +14,7 @@ use driver::session;
 use driver::session::Session;
 use metadata::csearch::{each_path, get_method_names_if_trait};
 use metadata::csearch::{get_static_methods_if_impl, get_type_name_if_impl};
-use metadata::cstore::find_use_stmt_cnum;
+use metadata::cstore::{find_use_stmt_cnum, iter_crate_data};
 use metadata::decoder::{def_like, dl_def, dl_field, dl_impl};
 use middle::lang_items::LanguageItems;
 use middle::lint::{deny, allow, forbid, level, unused_imports, warn};
@@ -22,6 +22,7 @@ use middle::pat_util::{pat_bindings};
 
 use core::cmp;
 use core::str;
+use core::uint;
 use core::vec;
 use syntax::ast::{RegionTyParamBound, TraitTyParamBound, _mod, add, arm};
 use syntax::ast::{binding_mode, bitand, bitor, bitxor, blk};
@@ -67,6 +68,7 @@ use syntax::parse::token::ident_interner;
 use syntax::parse::token::special_idents;
 use syntax::print::pprust::{pat_to_str, path_to_str};
 use syntax::codemap::{span, dummy_sp};
+use syntax::diagnostic::{Diagnostic, error};
 use syntax::visit::{default_visitor, fk_method, mk_vt, Visitor, visit_block};
 use syntax::visit::{visit_crate, visit_expr, visit_expr_opt, visit_fn};
 use syntax::visit::{visit_foreign_item, visit_item, visit_method_helper};
@@ -326,6 +328,45 @@ pub fn namespace_for_duplicate_checking_mode(mode: DuplicateCheckingMode)
     }
 }
 
+// Returns the candidate closest to `name` by edit distance, as long as it is
+// close enough to plausibly be a misspelling of it. Used to suggest fixes for
+// unresolved names.
+pub fn find_best_match(name: &str, candidates: &[~str]) -> Option<~str> {
+    let max_distance = uint::max(str::char_len(name) / 3, 1);
+    let mut best = None;
+    let mut best_distance = max_distance + 1;
+    for candidates.each |candidate| {
+        if str::eq_slice(*candidate, name) {
+            loop;
+        }
+        let distance = str::levdistance(name, *candidate);
+        if distance < best_distance {
+            best = Some(copy *candidate);
+            best_distance = distance;
+        }
+    }
+    return best;
+}
+
+// Returns true if a definition read from crate metadata binds a name in any
+// of the given namespaces.
+pub fn def_is_in_namespaces(def: def, namespaces: &[Namespace]) -> bool {
+    for namespaces.each |namespace| {
+        let found = match (def, *namespace) {
+            (def_fn(*), ValueNS) | (def_static_method(*), ValueNS) |
+            (def_const(*), ValueNS) | (def_variant(*), ValueNS) |
+            (def_struct(*), _) => true,
+            (def_ty(*), TypeNS) | (def_mod(*), TypeNS) |
+            (def_foreign_mod(*), TypeNS) => true,
+            _ => false
+        };
+        if found {
+            return true;
+        }
+    }
+    return false;
+}
+
 /// One local scope.
 pub struct Rib {
     bindings: HashMap<ident,def_like>,
@@ -1011,16 +1052,18 @@ pub impl Resolver {
                     // had the duplicate.
                     let ns = namespace_for_duplicate_checking_mode(
                         duplicate_checking_mode);
-                    self.session.span_err(sp,
+                    let mut d = Diagnostic::spanned(error, sp,
                         fmt!("duplicate definition of %s %s",
                              namespace_to_str(ns),
                              self.session.str_of(name)));
-                    do child.span_for_namespace(ns).iter() |sp| {
-                        self.session.span_note(*sp,
-                             fmt!("first definition of %s %s here:",
+                    d.span_label(sp, ~"duplicate definition");
+                    do child.span_for_namespace(ns).iter() |first_sp| {
+                        d.span_label(*first_sp,
+                             fmt!("first definition of %s %s here",
                                   namespace_to_str(ns),
                                   self.session.str_of(name)));
                     }
+                    self.session.emit_diagnostic(&d);
                 }
                 return (child, new_parent);
             }
@@ -1976,9 +2019,18 @@ pub impl Resolver {
                     // We presumably emitted an error. Continue.
                     let idents = import_directive.module_path.get();
                     let msg = fmt!("failed to resolve import: %s",
-                                   self.import_path_to_str(idents,
+                                   self.import_path_to_str(/*bad*/copy idents,
                                    *import_directive.subclass));
-                    self.session.span_err(import_directive.span, msg);
+                    let mut d = Diagnostic::spanned(error,
+                                                    import_directive.span,
+                                                    msg);
+                    match *import_directive.subclass {
+                        SingleImport(_, source, _) => {
+                            self.suggest_for_import(&mut d, idents, source);
+                        }
+                        GlobImport => {}
+                    }
+                    self.session.emit_diagnostic(&d);
                 }
                 Indeterminate => {
                     // Bail out. We'll come around next time.
@@ -2573,7 +2625,13 @@ pub impl Resolver {
                                               TypeNS,
                                               false) {
                 Failed => {
-                    self.session.span_err(span, ~"unresolved name");
+                    let mut d = Diagnostic::spanned(error, span,
+                                                    ~"unresolved name");
+                    let candidates = self.names_in_module(search_module,
+                                                          TypeNS);
+                    self.suggest_names(&mut d, name, candidates);
+                    self.suggest_paths(&mut d, name, [TypeNS]);
+                    self.session.emit_diagnostic(&d);
                     return Failed;
                 }
                 Indeterminate => {
@@ -2675,8 +2733,14 @@ pub impl Resolver {
                             module_path.get_elt(0));
                         match result {
                             Failed => {
-                                self.session.span_err(span,
-                                                      ~"unresolved name");
+                                let name = module_path.get_elt(0);
+                                let mut d = Diagnostic::spanned(
+                                    error, span, ~"unresolved name");
+                                let candidates =
+                                    self.names_visible_from(module_, TypeNS);
+                                self.suggest_names(&mut d, name, candidates);
+                                self.suggest_paths(&mut d, name, [TypeNS]);
+                                self.session.emit_diagnostic(&d);
                                 return Failed;
                             }
                             Indeterminate => {
@@ -3500,6 +3564,13 @@ pub impl Resolver {
         return Some(dl_def(def));
     }
 
+    /// The key under which a binding of `name` is stored in a rib. Two
+    /// identifiers have the same key only if they have the same name and
+    /// were introduced by the same macro expansions.
+    fn rib_key(name: ident) -> ident {
+        self.session.intr().hygienic_name(name)
+    }
+
     fn search_ribs(ribs: @DVec<@Rib>, name: ident, span: span,
                    allow_capturing_self: AllowCapturingSelfFlag)
                 -> Option<def_like> {
@@ -3507,22 +3578,33 @@ pub impl Resolver {
         // XXX: This should not use a while loop.
         // XXX: Try caching?
 
-        let mut i = (*ribs).len();
-        while i != 0 {
-            i -= 1;
-            let rib = (*ribs).get_elt(i);
-            match rib.bindings.find(&name) {
-                Some(def_like) => {
-                    return self.upvarify(ribs, i, def_like, span,
-                                      allow_capturing_self);
-                }
-                None => {
-                    // Continue.
+        // An identifier that a macro introduced refers first to the bindings
+        // that the same expansion introduced. Failing that, it means what
+        // its name means where the macro was used, so strip the marks one
+        // expansion at a time.
+        let mut name = name;
+        loop {
+            let key = self.rib_key(name);
+            let mut i = (*ribs).len();
+            while i != 0 {
+                i -= 1;
+                let rib = (*ribs).get_elt(i);
+                match rib.bindings.find(&key) {
+                    Some(def_like) => {
+                        return self.upvarify(ribs, i, def_like, span,
+                                          allow_capturing_self);
+                    }
+                    None => {
+                        // Continue.
+                    }
                 }
             }
-        }
 
-        return None;
+            match self.session.intr().outer_ctxt(name.ctxt) {
+                Some(ctxt) => name = ident { repr: name.repr, ctxt: ctxt },
+                None => return None
+            }
+        }
     }
 
     fn resolve_crate(@self) {
@@ -3792,7 +3874,8 @@ pub impl Resolver {
                     // the item that bound it
                     self.record_def(type_parameter.id,
                                     def_typaram_binder(node_id));
-                    (*function_type_rib).bindings.insert(name, def_like);
+                    (*function_type_rib).bindings.insert(self.rib_key(name),
+                                                         def_like);
                 }
             }
 
@@ -4113,11 +4196,17 @@ pub impl Resolver {
                   }
                   Some(binding_i) => {
                     if binding_0.binding_mode != binding_i.binding_mode {
-                        self.session.span_err(
+                        let mut d = Diagnostic::spanned(
+                            error,
                             binding_i.span,
                             fmt!("variable `%s` is bound with different \
                                       mode in pattern #%u than in pattern #1",
                                  self.session.str_of(key), i + 1));
+                        d.span_label(binding_i.span,
+                                     fmt!("bound in pattern #%u", i + 1));
+                        d.span_label(binding_0.span,
+                                     ~"bound in pattern #1");
+                        self.session.emit_diagnostic(&d);
                     }
                   }
                 }
@@ -4238,9 +4327,17 @@ pub impl Resolver {
                         self.record_def(path_id, def);
                     }
                     None => {
-                        self.session.span_err
-                            (ty.span, fmt!("use of undeclared type name `%s`",
-                                           self.idents_to_str(path.idents)));
+                        let mut d = Diagnostic::spanned(
+                            error, ty.span,
+                            fmt!("use of undeclared type name `%s`",
+                                 self.idents_to_str(path.idents)));
+                        let name = path.idents.last();
+                        if path.idents.len() == 1 && !path.global {
+                            let candidates = self.names_in_scope(TypeNS);
+                            self.suggest_names(&mut d, name, candidates);
+                        }
+                        self.suggest_paths(&mut d, name, [TypeNS]);
+                        self.session.emit_diagnostic(&d);
                     }
                 }
             }
@@ -4351,17 +4448,18 @@ pub impl Resolver {
                             // because that breaks the assumptions later
                             // passes make about or-patterns.)
 
+                            let key = self.rib_key(ident);
                             match bindings_list {
                                 Some(bindings_list)
-                                if !bindings_list.contains_key_ref(&ident)
+                                if !bindings_list.contains_key_ref(&key)
                                     => {
                                     let last_rib = (*self.value_ribs).last();
-                                    last_rib.bindings.insert(ident,
+                                    last_rib.bindings.insert(key,
                                                              dl_def(def));
-                                    bindings_list.insert(ident, pat_id);
+                                    bindings_list.insert(key, pat_id);
                                 }
                                 Some(b) => {
-                                  if b.find(&ident) == Some(pat_id) {
+                                  if b.find(&key) == Some(pat_id) {
                                       // Then this is a duplicate variable
                                       // in the same disjunct, which is an
                                       // error
@@ -4375,7 +4473,7 @@ pub impl Resolver {
                                 }
                                 None => {
                                     let last_rib = (*self.value_ribs).last();
-                                    last_rib.bindings.insert(ident,
+                                    last_rib.bindings.insert(key,
                                                              dl_def(def));
                                 }
                             }
@@ -4848,9 +4946,17 @@ pub impl Resolver {
                                         wrong_name));
                         }
                         else {
-                            self.session.span_err(expr.span,
-                                                fmt!("unresolved name: %s",
-                                                wrong_name));
+                            let mut d = Diagnostic::spanned(
+                                error, expr.span,
+                                fmt!("unresolved name: %s", wrong_name));
+                            let name = path.idents.last();
+                            if path.idents.len() == 1 && !path.global {
+                                let candidates =
+                                    self.names_in_scope(ValueNS);
+                                self.suggest_names(&mut d, name, candidates);
+                            }
+                            self.suggest_paths(&mut d, name, [ValueNS]);
+                            self.session.emit_diagnostic(&d);
                         }
                     }
                 }
@@ -4908,7 +5014,8 @@ pub impl Resolver {
             expr_loop(_, Some(label)) => {
                 do self.with_label_rib {
                     let def_like = dl_def(def_label(expr.id));
-                    self.label_ribs.last().bindings.insert(label, def_like);
+                    let key = self.rib_key(label);
+                    self.label_ribs.last().bindings.insert(key, def_like);
 
                     visit_expr(expr, (), visitor);
                 }
@@ -5269,6 +5376,265 @@ pub impl Resolver {
     }
 
 
+    //
+    // Suggestions
+    //
+    // When a name fails to resolve, we look for what was probably meant:
+    // names in scope that are a small edit distance away, and items with
+    // exactly that name elsewhere in this crate or in a linked crate, which
+    // a `use` would bring into scope. Like the diagnostics below, none of
+    // this is fast.
+    //
+
+    /// Returns the names bound in the given namespace by the local ribs.
+    fn names_in_ribs(namespace: Namespace) -> ~[~str] {
+        let ribs = match namespace {
+            ValueNS => self.value_ribs,
+            TypeNS => self.type_ribs
+        };
+
+        let mut names = ~[];
+        for (*ribs).get().each |rib| {
+            for rib.bindings.each_key_ref |&name| {
+                names.push(self.session.str_of(name));
+            }
+        }
+        return names;
+    }
+
+    /// Returns the names bound in the given namespace by the items and the
+    /// imports of a single module.
+    fn names_in_module(module_: @Module, namespace: Namespace) -> ~[~str] {
+        let mut names = ~[];
+        for module_.children.each_ref |&name, &child_name_bindings| {
+            if child_name_bindings.defined_in_namespace(namespace) {
+                names.push(self.session.str_of(name));
+            }
+        }
+        for module_.import_resolutions.each_ref |&name, &import_resolution| {
+            if import_resolution.target_for_namespace(namespace).is_some() {
+                names.push(self.session.str_of(name));
+            }
+        }
+        return names;
+    }
+
+    /**
+     * Returns the names visible from the given module, following the scope
+     * chain outward the same way that `resolve_item_in_lexical_scope` does
+     * when it is not searching through modules.
+     */
+    fn names_visible_from(module_: @Module, namespace: Namespace)
+                       -> ~[~str] {
+        let mut names = ~[];
+        let mut search_module = module_;
+        loop {
+            names.push_all_move(self.names_in_module(search_module,
+                                                     namespace));
+            match search_module.parent_link {
+                NoParentLink => {
+                    break;
+                }
+                ModuleParentLink(parent_module, _) => {
+                    match search_module.kind {
+                        NormalModuleKind => break,
+                        ExternModuleKind |
+                        TraitModuleKind |
+                        AnonymousModuleKind => {
+                            search_module = parent_module;
+                        }
+                    }
+                }
+                BlockParentLink(parent_module, _) => {
+                    search_module = parent_module;
+                }
+            }
+        }
+        return names;
+    }
+
+    /// Returns every name in scope at the current point of the traversal.
+    fn names_in_scope(namespace: Namespace) -> ~[~str] {
+        let mut names = self.names_in_ribs(namespace);
+        names.push_all_move(self.names_visible_from(self.current_module,
+                                                    namespace));
+        match namespace {
+            TypeNS => {
+                for self.primitive_type_table.primitive_types.each_key_ref
+                        |&name| {
+                    names.push(self.session.str_of(name));
+                }
+            }
+            ValueNS => {}
+        }
+        return names;
+    }
+
+    /**
+     * Finds the module that a crate-relative path names by following
+     * children only. Unlike `resolve_module_path_from_root`, this reports
+     * no errors, and so is safe to use after resolution has already failed.
+     */
+    fn find_module_by_path(path: &[ident]) -> Option<@Module> {
+        let mut search_module = self.graph_root.get_module();
+        for path.each |ident| {
+            match search_module.children.find(ident) {
+                None => {
+                    return None;
+                }
+                Some(child_name_bindings) => {
+                    match child_name_bindings.get_module_if_available() {
+                        None => {
+                            return None;
+                        }
+                        Some(child_module) => {
+                            search_module = child_module;
+                        }
+                    }
+                }
+            }
+        }
+        return Some(search_module);
+    }
+
+    /**
+     * Returns the paths, relative to the crate root, of every item in this
+     * crate named `name` in one of the given namespaces. Block-local items
+     * and the contents of external crates are skipped; the latter are
+     * found through the crate store instead.
+     */
+    fn find_local_paths_to_name(module_: @Module,
+                                name: ident,
+                                namespaces: &[Namespace],
+                                paths: &mut ~[~str]) {
+        match module_.children.find(&name) {
+            Some(child_name_bindings)
+                    if namespaces.any(|namespace|
+                        child_name_bindings.defined_in_namespace(
+                            *namespace)) => {
+                let path = match module_.parent_link {
+                    NoParentLink => self.session.str_of(name),
+                    _ => fmt!("%s::%s",
+                              self.module_to_str(module_),
+                              self.session.str_of(name))
+                };
+                paths.push(path);
+            }
+            Some(_) | None => {}
+        }
+
+        for module_.children.each_value_ref |&child_name_bindings| {
+            match child_name_bindings.get_module_if_available() {
+                None => {
+                    // Not a module.
+                }
+                Some(child_module) => {
+                    let is_local = match child_module.def_id {
+                        Some(def_id) => def_id.crate == local_crate,
+                        None => true
+                    };
+                    match child_module.kind {
+                        NormalModuleKind | ExternModuleKind if is_local => {
+                            self.find_local_paths_to_name(child_module,
+                                                          name,
+                                                          namespaces,
+                                                          paths);
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        }
+    }
+
+    /**
+     * Returns the full paths of the items named `name` in one of the given
+     * namespaces, first in this crate and then in every crate in the crate
+     * store. Paths into other crates are prefixed with the crate's name.
+     */
+    fn find_paths_to_name(name: ident, namespaces: &[Namespace]) -> ~[~str] {
+        let mut paths = ~[];
+        self.find_local_paths_to_name(self.graph_root.get_module(),
+                                      name,
+                                      namespaces,
+                                      &mut paths);
+
+        let name_str = self.session.str_of(name);
+        let cstore = self.session.cstore;
+        do iter_crate_data(cstore) |cnum, cdata| {
+            for each_path(cstore, cnum) |path_string, def_like| {
+                match def_like {
+                    dl_def(def) if def_is_in_namespaces(def, namespaces) => {
+                        let mut pieces = split_str(path_string, ~"::");
+                        if pieces.pop() == name_str {
+                            let path = fmt!("%s::%s", cdata.name,
+                                            path_string);
+                            if !paths.contains(&path) {
+                                paths.push(path);
+                            }
+                        }
+                    }
+                    dl_def(_) | dl_impl(_) | dl_field => {}
+                }
+            }
+        }
+
+        return paths;
+    }
+
+    /**
+     * Notes the closest of `candidates` to `name`, if any is close enough.
+     * Like the rest of the suggestions, the note points at the span of the
+     * error itself.
+     */
+    fn suggest_names(d: &mut Diagnostic, name: ident, candidates: &[~str]) {
+        match find_best_match(self.session.str_of(name), candidates) {
+            Some(best) => {
+                d.span_note(d.span.get(), fmt!("did you mean `%s`?", best));
+            }
+            None => {}
+        }
+    }
+
+    /// Notes the items named `name` that a `use` would bring into scope.
+    fn suggest_paths(d: &mut Diagnostic, name: ident,
+                     namespaces: &[Namespace]) {
+        let paths = self.find_paths_to_name(name, namespaces);
+        for paths.eachi |i, path| {
+            if i == 3 {
+                d.span_note(d.span.get(),
+                            fmt!("... and %u other candidates",
+                                 paths.len() - 3));
+                break;
+            }
+            d.span_note(d.span.get(),
+                        fmt!("`%s` is defined at `%s`; consider adding \
+                              `use %s;`",
+                             self.session.str_of(name), *path, *path));
+        }
+    }
+
+    /**
+     * Adds suggestions to the error for a failed single import. The module
+     * part of the path is looked up again without reporting errors; if it
+     * didn't resolve, the error for that has already been reported.
+     */
+    fn suggest_for_import(d: &mut Diagnostic,
+                          module_path: &[ident],
+                          source: ident) {
+        match self.find_module_by_path(module_path) {
+            None => {}
+            Some(containing_module) => {
+                let mut candidates = self.names_in_module(containing_module,
+                                                          ValueNS);
+                candidates.push_all_move(
+                    self.names_in_module(containing_module, TypeNS));
+                self.suggest_names(d, source, candidates);
+                self.suggest_paths(d, source, [ValueNS, TypeNS]);
+            }
+        }
+    }
+
     //
     // Diagnostics
     //
@@ -30,7 +30,7 @@ use back::link::{mangle_internal_name_by_path_and_seq};
 use back::link::{mangle_internal_name_by_path};
 use back::link::{mangle_internal_name_by_seq};
 use back::link::{mangle_internal_name_by_type_only};
-use back::{link, abi, upcall};
+use back::{link, abi, lto, upcall};
 use driver::session;
 use driver::session::Session;
 use lib::llvm::{ModuleRef, ValueRef, TypeRef, BasicBlockRef};
@@ -41,6 +41,7 @@ use metadata::common::link_meta;
 use metadata::{csearch, cstore, decoder, encoder};
 use middle::astencode;
 use middle::borrowck::RootInfo;
+use middle::incremental;
 use middle::pat_util::*;
 use middle::resolve;
 use middle::trans::_match;
@@ -1691,7 +1692,7 @@ pub fn copy_args_to_allocas(fcx: fn_ctxt,
         fcx.llargs.insert(arg_id, local_mem(llarg));
 
         if fcx.ccx.sess.opts.extra_debuginfo {
-            debuginfo::create_arg(bcx, args[arg_n], args[arg_n].ty.span);
+            debuginfo::create_arg(bcx, args[arg_n]);
         }
     }
 
@@ -2022,6 +2023,9 @@ pub fn trans_item(ccx: @crate_ctxt, item: ast::item) {
                                          /*bad*/copy *path,
                                          ~[path_name(item.ident)]),
                                       decl, body, llfndecl, item.id);
+        } else if ccx.reused_items.contains_key_ref(&item.id) {
+            // The body is a stub; the code comes from the incremental cache
+            get_item_val(ccx, item.id);
         } else if tps.is_empty() {
             let llfndecl = get_item_val(ccx, item.id);
             trans_fn(ccx,
@@ -2477,7 +2481,10 @@ pub fn get_item_val(ccx: @crate_ctxt, id: ast::node_id) -> ValueRef {
             ccx.sess.bug(~"get_item_val(): unexpected variant")
           }
         };
-        if !(exprt || ccx.reachable.contains_key_ref(&id)) {
+        // With an incremental cache, the code of one build is linked
+        // against that of the next by symbol, so nothing is internalized.
+        if !(exprt || ccx.reachable.contains_key_ref(&id)) &&
+                ccx.sess.opts.incremental.is_none() {
             lib::llvm::SetLinkage(val, lib::llvm::InternalLinkage);
         }
         ccx.item_vals.insert(id, val);
@@ -2894,13 +2901,21 @@ pub fn crate_ctxt_to_encode_parms(cx: @crate_ctxt) -> encoder::encode_parms {
         discrim_symbols: cx.discrim_symbols,
         link_meta: /*bad*/copy cx.link_meta,
         cstore: cx.sess.cstore,
-        encode_inlined_item: encode_inlined_item
+        encode_inlined_item: encode_inlined_item,
+        bitcode: None
     };
 }
 
-pub fn write_metadata(cx: @crate_ctxt, crate: &ast::crate) {
+pub fn write_metadata(cx: @crate_ctxt, crate: &ast::crate, output: &Path) {
     if !*cx.sess.building_library { return; }
-    let encode_parms = crate_ctxt_to_encode_parms(cx);
+    let encode_parms = if cx.sess.lto() {
+        // The module's bitcode is taken before the metadata is added to it
+        let bitcode = lto::module_bitcode(cx.sess, cx.llmod,
+                                          &output.with_filetype("lto.bc"));
+        {bitcode: Some(bitcode), .. crate_ctxt_to_encode_parms(cx)}
+    } else {
+        crate_ctxt_to_encode_parms(cx)
+    };
     let llmeta = C_bytes(encoder::encode_metadata(encode_parms, crate));
     let llconst = C_struct(~[llmeta]);
     let mut llglobal = str::as_c_str(~"rust_metadata", |buf| {
@@ -2936,7 +2951,9 @@ pub fn trans_crate(sess: session::Session,
                    tcx: ty::ctxt,
                    output: &Path,
                    emap2: resolve::ExportMap2,
-                   maps: astencode::Maps) -> (ModuleRef, link_meta) {
+                   maps: astencode::Maps,
+                   incremental: Option<@incremental::Cache>)
+                -> (ModuleRef, link_meta) {
 
     let symbol_hasher = @hash::default_state();
     let link_meta =
@@ -3048,7 +3065,11 @@ pub fn trans_crate(sess: session::Session,
               crate_map: crate_map,
               mut uses_gc: false,
               dbg_cx: dbg_cx,
-              mut do_not_commit_warning_issued: false
+              mut do_not_commit_warning_issued: false,
+              reused_items: match incremental {
+                  Some(cache) => cache.reused,
+                  None => HashMap()
+              }
         };
 
         {
@@ -3067,7 +3088,16 @@ pub fn trans_crate(sess: session::Session,
         write_abi_version(ccx);
 
         // Translate the metadata.
-        write_metadata(ccx, crate);
+        write_metadata(ccx, crate, output);
+
+        match incremental {
+            Some(cache) => {
+                incremental::link_cached_code(cache, sess, llmod,
+                                              ccx.item_symbols);
+                incremental::save(cache, sess, llmod, ccx.item_symbols);
+            }
+            None => {}
+        }
         if ccx.sess.trans_stats() {
             io::println(~"--- trans stats ---");
             io::println(fmt!("n_static_tydescs: %u",
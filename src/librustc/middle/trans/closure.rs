@@ -21,6 +21,7 @@ use middle::trans::build::*;
 use middle::trans::callee;
 use middle::trans::common::*;
 use middle::trans::datum::{Datum, INIT, ByRef, ByValue, ZeroMem};
+use middle::trans::debuginfo;
 use middle::trans::expr;
 use middle::trans::glue;
 use middle::trans::machine;
@@ -354,6 +355,9 @@ pub fn load_environment(fcx: fn_ctxt,
         }
         let def_id = ast_util::def_id_of_def(cap_var.def);
         fcx.llupvars.insert(def_id.node, upvarptr);
+        if fcx.ccx.sess.opts.extra_debuginfo {
+            debuginfo::create_upvar(bcx, cap_var, upvarptr);
+        }
         i += 1u;
     }
     if load_ret_handle {
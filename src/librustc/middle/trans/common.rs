@@ -225,7 +225,9 @@ pub struct crate_ctxt {
      // is not emitted by LLVM's GC pass when no functions use GC.
      mut uses_gc: bool,
      dbg_cx: Option<debuginfo::debug_ctxt>,
-     mut do_not_commit_warning_issued: bool
+     mut do_not_commit_warning_issued: bool,
+     // Functions whose code is taken from the incremental cache
+     reused_items: HashMap<ast::node_id, ()>
 }
 
 // Types used for llself.
@@ -13,8 +13,8 @@ use core::prelude::*;
 use driver::session;
 use lib::llvm::ValueRef;
 use lib::llvm::llvm;
+use middle::moves;
 use middle::pat_util::*;
-use middle::trans::base;
 use middle::trans::build::B;
 use middle::trans::common::*;
 use middle::trans::machine;
@@ -25,10 +25,8 @@ use util::ppaux::ty_to_str;
 
 use core::libc;
 use core::option;
-use core::sys;
 use std::oldmap::HashMap;
 use std::oldmap;
-use syntax::ast::Ty;
 use syntax::codemap::{span, CharPos};
 use syntax::parse::token::ident_interner;
 use syntax::{ast, codemap, ast_util, ast_map};
@@ -52,6 +50,9 @@ const StructureTypeTag: int = 19;
 const MemberTag: int = 13;
 const ArrayTypeTag: int = 1;
 const SubrangeTag: int = 33;
+const UnionTypeTag: int = 23;
+const EnumerationTypeTag: int = 4;
+const EnumeratorTag: int = 40;
 
 const DW_ATE_boolean: int = 0x02;
 const DW_ATE_float: int = 0x04;
@@ -107,13 +108,16 @@ fn add_named_metadata(cx: @crate_ctxt, name: ~str, val: ValueRef) {
 pub type debug_ctxt = {
     llmetadata: metadata_cache,
     names: namegen,
-    crate_file: ~str
+    crate_file: ~str,
+    // The descriptions of the types that have been described so far
+    tydescs: HashMap<ty::t, @metadata<tydesc_md>>
 };
 
 pub fn mk_ctxt(+crate: ~str, intr: @ident_interner) -> debug_ctxt {
     {llmetadata: oldmap::HashMap(),
      names: new_namegen(intr),
-     crate_file: crate}
+     crate_file: crate,
+     tydescs: oldmap::HashMap()}
 }
 
 fn update_cache(cache: metadata_cache, mdtag: int, val: debug_metadata) {
@@ -129,11 +133,11 @@ type metadata<T> = {node: ValueRef, data: T};
 
 type file_md = {path: ~str};
 type compile_unit_md = {name: ~str};
-type subprogram_md = {id: ast::node_id};
-type local_var_md = {id: ast::node_id};
+type subprogram_md = {llfn: ValueRef};
+type local_var_md = {id: ast::node_id, name: ast::ident};
 type tydesc_md = {hash: uint};
 type block_md = {start: codemap::Loc, end: codemap::Loc};
-type argument_md = {id: ast::node_id};
+type argument_md = {id: ast::node_id, name: ast::ident};
 type retval_md = {id: ast::node_id};
 
 type metadata_cache = HashMap<int, ~[debug_metadata]>;
@@ -262,8 +266,11 @@ fn line_from_span(cm: @codemap::CodeMap, sp: span) -> uint {
     cm.lookup_char_pos(sp.lo).line
 }
 
+// The lexical block of the innermost block of `cx` that corresponds to
+// some node of the AST. The node's id serves as the block's unique id,
+// so that the statements and the variables of a block are in the same
+// lexical block, and that those of different blocks never are.
 fn create_block(cx: block) -> @metadata<block_md> {
-    let cache = get_cache(cx.ccx());
     let mut cx = cx;
     while cx.node_info.is_none() {
         match cx.parent {
@@ -271,38 +278,27 @@ fn create_block(cx: block) -> @metadata<block_md> {
           None => die!()
         }
     }
-    let sp = cx.node_info.get().span;
+    let info = cx.node_info.get();
 
-    let start = cx.sess().codemap.lookup_char_pos(sp.lo);
+    let start = cx.sess().codemap.lookup_char_pos(info.span.lo);
     let fname = /*bad*/copy start.file.name;
-    let end = cx.sess().codemap.lookup_char_pos(sp.hi);
+    let end = cx.sess().codemap.lookup_char_pos(info.span.hi);
     let tg = LexicalBlockTag;
-    /*match cached_metadata::<@metadata<block_md>>(
-        cache, tg,
-        {|md| start == md.data.start && end == md.data.end}) {
-      option::Some(md) { return md; }
-      option::None {}
-    }*/
 
     let parent = match cx.parent {
         None => create_function(cx.fcx).node,
         Some(bcx) => create_block(bcx).node
     };
     let file_node = create_file(cx.ccx(), fname);
-    let unique_id = match cache.find(&LexicalBlockTag) {
-      option::Some(v) => vec::len(v) as int,
-      option::None => 0
-    };
     let lldata = ~[lltag(tg),
                   parent,
                   lli32(start.line.to_int()),
                   lli32(start.col.to_int()),
                   file_node.node,
-                  lli32(unique_id)
+                  lli32(info.id)
                  ];
     let val = llmdnode(lldata);
     let mdval = @{node: val, data: {start: start, end: end}};
-    //update_cache(cache, tg, block_metadata(mdval));
     return mdval;
 }
 
@@ -322,7 +318,17 @@ fn create_basic_type(cx: @crate_ctxt, t: ty::t, span: span)
       option::None => ()
     }
 
-    let (name, encoding) = (~"uint", DW_ATE_unsigned);
+    let encoding = match ty::get(t).sty {
+      ty::ty_nil | ty::ty_bot => DW_ATE_unsigned,
+      ty::ty_bool => DW_ATE_boolean,
+      ty::ty_int(ast::ty_char) => DW_ATE_unsigned_char,
+      ty::ty_int(_) => DW_ATE_signed,
+      // So that strings show as characters
+      ty::ty_uint(ast::ty_u8) => DW_ATE_unsigned_char,
+      ty::ty_uint(_) => DW_ATE_unsigned,
+      ty::ty_float(_) => DW_ATE_float,
+      _ => cx.sess.bug(~"create_basic_type: not a basic type")
+    };
 
     let fname = filename_from_span(cx, span);
     let file_node = create_file(cx, fname);
@@ -330,7 +336,7 @@ fn create_basic_type(cx: @crate_ctxt, t: ty::t, span: span)
     let (size, align) = size_and_align_of(cx, t);
     let lldata = ~[lltag(tg),
                   cu_node.node,
-                  llstr(name),
+                  llstr(ty_to_str(cx.tcx, t)),
                   file_node.node,
                   lli32(0), //XXX source line
                   lli64(size * 8),  // size in bits
@@ -349,58 +355,16 @@ fn create_pointer_type(cx: @crate_ctxt, t: ty::t, span: span,
                        pointee: @metadata<tydesc_md>)
     -> @metadata<tydesc_md> {
     let tg = PointerTypeTag;
-    /*let cache = cx.llmetadata;
-    match cached_metadata::<@metadata<tydesc_md>>(
-        cache, tg, {|md| ty::hash_ty(t) == ty::hash_ty(md.data.hash)}) {
-      option::Some(md) { return md; }
-      option::None {}
-    }*/
     let (size, align) = size_and_align_of(cx, t);
     let fname = filename_from_span(cx, span);
     let file_node = create_file(cx, fname);
-    //let cu_node = create_compile_unit(cx, fname);
     let llnode = create_derived_type(tg, file_node.node, ~"", 0, size * 8,
                                      align * 8, 0, pointee.node);
     let mdval = @{node: llnode, data: {hash: ty::type_id(t)}};
-    //update_cache(cache, tg, tydesc_metadata(mdval));
     add_named_metadata(cx, ~"llvm.dbg.ty", llnode);
     return mdval;
 }
 
-struct StructCtxt {
-    file: ValueRef,
-    name: ~str,
-    line: int,
-    members: ~[ValueRef],
-    total_size: int,
-    align: int
-}
-
-fn finish_structure(cx: @mut StructCtxt) -> ValueRef {
-    return create_composite_type(StructureTypeTag,
-                                 /*bad*/copy cx.name,
-                                 cx.file,
-                                 cx.line,
-                                 cx.total_size,
-                                 cx.align,
-                                 0,
-                                 option::None,
-                                 option::Some(/*bad*/copy cx.members));
-}
-
-fn create_structure(file: @metadata<file_md>, +name: ~str, line: int)
-                 -> @mut StructCtxt {
-    let cx = @mut StructCtxt {
-        file: file.node,
-        name: name,
-        line: line,
-        members: ~[],
-        total_size: 0,
-        align: 64 //XXX different alignment per arch?
-    };
-    return cx;
-}
-
 fn create_derived_type(type_tag: int, file: ValueRef, +name: ~str, line: int,
                        size: int, align: int, offset: int, ty: ValueRef)
     -> ValueRef {
@@ -417,66 +381,73 @@ fn create_derived_type(type_tag: int, file: ValueRef, +name: ~str, line: int,
     return llmdnode(lldata);
 }
 
-fn add_member(cx: @mut StructCtxt,
-              +name: ~str,
-              line: int,
-              size: int,
-              align: int,
-              ty: ValueRef) {
-    cx.members.push(create_derived_type(MemberTag, cx.file, name, line,
-                                       size * 8, align * 8, cx.total_size,
-                                       ty));
-    cx.total_size += size * 8;
+// A member of type `t` at `offset` bytes into a composite type
+fn create_member(cx: @crate_ctxt, file: ValueRef, +name: ~str, t: ty::t,
+                 offset: uint, span: span) -> ValueRef {
+    let ty_md = create_ty(cx, t, span);
+    let (size, align) = size_and_align_of(cx, t);
+    create_derived_type(MemberTag, file, name, 0, size * 8, align * 8,
+                        (offset * 8u) as int, ty_md.node)
 }
 
-fn create_record(cx: @crate_ctxt, t: ty::t, fields: ~[ast::ty_field],
-                 span: span) -> @metadata<tydesc_md> {
-    let fname = filename_from_span(cx, span);
-    let file_node = create_file(cx, fname);
-    let scx = create_structure(file_node,
-                               cx.sess.str_of(
-                                   ((/*bad*/copy cx.dbg_cx).get().names)
-                                   (~"rec")),
-                               line_from_span(cx.sess.codemap,
-                                              span) as int);
-    for fields.each |field| {
-        let field_t = ty::get_field(cx.tcx, t, field.node.ident).mt.ty;
-        let ty_md = create_ty(cx, field_t, field.node.mt.ty);
-        let (size, align) = size_and_align_of(cx, field_t);
-        add_member(scx, cx.sess.str_of(field.node.ident),
-                   line_from_span(cx.sess.codemap, field.span) as int,
-                   size as int, align as int, ty_md.node);
+// The members for `fields`, laid out as LLVM lays out a structure of
+// their types, starting `base` bytes into a composite type
+fn create_members(cx: @crate_ctxt, file: ValueRef, fields: &[(~str, ty::t)],
+                  base: uint, span: span) -> ~[ValueRef] {
+    let lltys = do fields.map |field| {
+        match *field { (_, t) => type_of::type_of(cx, t) }
+    };
+    let llty = T_struct(lltys);
+    do vec::mapi(fields) |i, field| {
+        match *field {
+          (ref name, t) => {
+            let offset = base + machine::llelement_offset(cx, llty, i);
+            create_member(cx, file, copy *name, t, offset, span)
+          }
+        }
     }
-    let mdval = @{node: finish_structure(scx), data:{hash: ty::type_id(t)}};
-    return mdval;
 }
 
-fn create_boxed_type(cx: @crate_ctxt, outer: ty::t, _inner: ty::t,
-                     span: span, boxed: @metadata<tydesc_md>)
+// A structure of type `t` with a member for each of `fields`. Records,
+// structs and tuples, as well as the boxes and vectors that the runtime
+// manages, are described this way.
+fn create_struct(cx: @crate_ctxt, t: ty::t, +name: ~str,
+                 fields: &[(~str, ty::t)], span: span) -> ValueRef {
+    let file_node = create_file(cx, filename_from_span(cx, span)).node;
+    let members = create_members(cx, file_node, fields, 0u, span);
+    let (size, align) = size_and_align_of(cx, t);
+    create_composite_type(StructureTypeTag, name, file_node, 0, size * 8,
+                          align * 8, 0, option::None,
+                          option::Some(members))
+}
+
+// A structure the size of `t` without any members, for the types that
+// aren't described any further, like traits
+fn create_opaque_type(cx: @crate_ctxt, t: ty::t, span: span)
     -> @metadata<tydesc_md> {
-    //let tg = StructureTypeTag;
-    /*let cache = cx.llmetadata;
-    match cached_metadata::<@metadata<tydesc_md>>(
-        cache, tg, {|md| ty::hash_ty(outer) == ty::hash_ty(md.data.hash)}) {
-      option::Some(md) { return md; }
-      option::None {}
-    }*/
-    let fname = filename_from_span(cx, span);
-    let file_node = create_file(cx, fname);
-    //let cu_node = create_compile_unit_metadata(cx, fname);
-    let uint_t = ty::mk_uint(cx.tcx);
-    let refcount_type = create_basic_type(cx, uint_t, span);
-    let scx = create_structure(file_node, ty_to_str(cx.tcx, outer), 0);
-    add_member(scx, ~"refcnt", 0, sys::size_of::<uint>() as int,
-               sys::min_align_of::<uint>() as int, refcount_type.node);
-    add_member(scx, ~"boxed", 0, 8, //XXX member_size_and_align(??)
-               8, //XXX just a guess
-               boxed.node);
-    let llnode = finish_structure(scx);
-    let mdval = @{node: llnode, data: {hash: ty::type_id(outer)}};
-    //update_cache(cache, tg, tydesc_metadata(mdval));
+    let file_node = create_file(cx, filename_from_span(cx, span)).node;
+    let (size, align) = size_and_align_of(cx, t);
+    let llnode = create_composite_type(StructureTypeTag,
+                                       ty_to_str(cx.tcx, t), file_node, 0,
+                                       size * 8, align * 8, 0, option::None,
+                                       option::None);
+    return @{node: llnode, data: {hash: ty::type_id(t)}};
+}
+
+// The box that `outer` points to: the header that the runtime uses to
+// manage it, then `inner`
+fn create_boxed_type(cx: @crate_ctxt, outer: ty::t, inner: ty::t,
+                     span: span) -> @metadata<tydesc_md> {
+    let box_t = tuplify_box_ty(cx.tcx, inner);
+    let names = ~[~"refcnt", ~"tydesc", ~"prev", ~"next", ~"boxed"];
+    let fields = match ty::get(box_t).sty {
+      ty::ty_tup(ref elts) => vec::zip(names, copy *elts),
+      _ => cx.sess.bug(~"create_boxed_type: box isn't a tuple")
+    };
+    let llnode = create_struct(cx, box_t, ty_to_str(cx.tcx, outer), fields,
+                               span);
     add_named_metadata(cx, ~"llvm.dbg.ty", llnode);
-    return mdval;
+    return @{node: llnode, data: {hash: ty::type_id(outer)}};
 }
 
 fn create_composite_type(type_tag: int, +name: ~str, file: ValueRef,
@@ -509,132 +480,246 @@ fn create_composite_type(type_tag: int, +name: ~str, file: ValueRef,
     return llmdnode(lldata);
 }
 
-fn create_vec(cx: @crate_ctxt, vec_t: ty::t, elem_t: ty::t,
-              vec_ty_span: codemap::span, elem_ty: @ast::Ty)
-    -> @metadata<tydesc_md> {
-    let fname = filename_from_span(cx, vec_ty_span);
-    let file_node = create_file(cx, fname);
-    let elem_ty_md = create_ty(cx, elem_t, elem_ty);
-    let scx = create_structure(file_node, ty_to_str(cx.tcx, vec_t), 0);
-    let size_t_type = create_basic_type(cx, ty::mk_uint(cx.tcx), vec_ty_span);
-    add_member(scx, ~"fill", 0, sys::size_of::<libc::size_t>() as int,
-               sys::min_align_of::<libc::size_t>() as int, size_t_type.node);
-    add_member(scx, ~"alloc", 0, sys::size_of::<libc::size_t>() as int,
-               sys::min_align_of::<libc::size_t>() as int, size_t_type.node);
-    let subrange = llmdnode(~[lltag(SubrangeTag), lli64(0), lli64(0)]);
-    let (arr_size, arr_align) = size_and_align_of(cx, elem_t);
-    let data_ptr = create_composite_type(ArrayTypeTag, ~"", file_node.node, 0,
-                                         arr_size, arr_align, 0,
-                                         option::Some(elem_ty_md.node),
-                                         option::Some(~[subrange]));
-    add_member(scx, ~"data", 0, 0, // clang says the size should be 0
-               sys::min_align_of::<u8>() as int, data_ptr);
-    let llnode = finish_structure(scx);
-    return @{node: llnode, data: {hash: ty::type_id(vec_t)}};
-}
-
-fn create_ty(_cx: @crate_ctxt, _t: ty::t, _ty: @ast::Ty)
-    -> @metadata<tydesc_md> {
-    /*let cache = get_cache(cx);
-    match cached_metadata::<@metadata<tydesc_md>>(
-        cache, tg, {|md| t == md.data.hash}) {
-      option::Some(md) { return md; }
-      option::None {}
-    }*/
-
-    /* FIXME (#2012): disabled this code as part of the patch that moves
-     * recognition of named builtin types into resolve. I tried to fix
-     * it, but it seems to already be broken -- it's only called when
-     * --xg is given, and compiling with --xg fails on trivial programs.
-     *
-     * Generating an ast::ty from a ty::t seems like it should not be
-     * needed. It is only done to track spans, but you will not get the
-     * right spans anyway -- types tend to refer to stuff defined
-     * elsewhere, not be self-contained.
-     */
-
-    die!();
-    /*
-    fn t_to_ty(cx: crate_ctxt, t: ty::t, span: span) -> @ast::ty {
-        let ty = match ty::get(t).struct {
-          ty::ty_nil { ast::ty_nil }
-          ty::ty_bot { ast::ty_bot }
-          ty::ty_bool { ast::ty_bool }
-          ty::ty_int(t) { ast::ty_int(t) }
-          ty::ty_float(t) { ast::ty_float(t) }
-          ty::ty_uint(t) { ast::ty_uint(t) }
-          ty::ty_box(mt) { ast::ty_box({ty: t_to_ty(cx, mt.ty, span),
-                                        mutbl: mt.mutbl}) }
-          ty::ty_uniq(mt) { ast::ty_uniq({ty: t_to_ty(cx, mt.ty, span),
-                                          mutbl: mt.mutbl}) }
-          ty::ty_rec(fields) {
-            let fs = ~[];
-            for field in fields {
-                fs.push({node: {ident: field.ident,
-                               mt: {ty: t_to_ty(cx, field.mt.ty, span),
-                                    mutbl: field.mt.mutbl}},
-                        span: span});
-            }
-            ast::ty_rec(fs)
-          }
-          ty::ty_vec(mt) { ast::ty_vec({ty: t_to_ty(cx, mt.ty, span),
-                                        mutbl: mt.mutbl}) }
-          _ {
-            cx.sess.span_bug(span, "t_to_ty: Can't handle this type");
-          }
-        };
-        return @{node: ty, span: span};
+// The contents of a `~[T]` or `@[T]` box: the number of bytes of
+// elements in use and allocated, then the elements. The number of
+// elements isn't known statically, so they're described as an array
+// without bounds that a debugger can index up to `fill`.
+fn create_vec(cx: @crate_ctxt, vec_t: ty::t, elem_t: ty::t, span: span)
+    -> ValueRef {
+    let file_node = create_file(cx, filename_from_span(cx, span)).node;
+    let uint_t = ty::mk_uint(cx.tcx);
+    let mut members = create_members(cx, file_node,
+                                     ~[(~"fill", uint_t),
+                                       (~"alloc", uint_t)],
+                                     0u, span);
+    let elem_md = create_ty(cx, elem_t, span);
+    let (_, elem_align) = size_and_align_of(cx, elem_t);
+    let subrange = llmdnode(~[lltag(SubrangeTag), lli64(0), lli64(-1)]);
+    let elems = create_composite_type(ArrayTypeTag, ~"", file_node, 0, 0,
+                                      elem_align * 8, 0,
+                                      option::Some(elem_md.node),
+                                      option::Some(~[subrange]));
+    let llty = T_vec(cx, type_of::type_of(cx, elem_t));
+    let offset = machine::llelement_offset(cx, llty, 2u);
+    // clang says the size should be 0
+    members.push(create_derived_type(MemberTag, file_node, ~"data", 0, 0,
+                                     elem_align * 8, (offset * 8u) as int,
+                                     elems));
+    let (size, align) = size_and_align_of(cx, vec_t);
+    create_composite_type(StructureTypeTag, ty_to_str(cx.tcx, vec_t),
+                          file_node, 0, size * 8, align * 8, 0, option::None,
+                          option::Some(members))
+}
+
+// A vector that holds its `len` elements directly
+fn create_fixed_vec(cx: @crate_ctxt, t: ty::t, elem_t: ty::t, len: uint,
+                    span: span) -> ValueRef {
+    let file_node = create_file(cx, filename_from_span(cx, span)).node;
+    let elem_md = create_ty(cx, elem_t, span);
+    let (size, align) = size_and_align_of(cx, t);
+    let subrange = llmdnode(~[lltag(SubrangeTag), lli64(0),
+                              lli64(len as int - 1)]);
+    create_composite_type(ArrayTypeTag, ~"", file_node, 0, size * 8,
+                          align * 8, 0, option::Some(elem_md.node),
+                          option::Some(~[subrange]))
+}
+
+// A slice is a pointer to its first element and the number of bytes of
+// its elements
+fn create_slice(cx: @crate_ctxt, t: ty::t, elem_t: ty::t, span: span)
+    -> ValueRef {
+    let fields = ~[(~"data_ptr", ty::mk_imm_ptr(cx.tcx, elem_t)),
+                   (~"byte_len", ty::mk_uint(cx.tcx))];
+    create_struct(cx, t, ty_to_str(cx.tcx, t), fields, span)
+}
+
+// A C-like enum is described as an enumeration of its variants. Any
+// other enum is a union of a structure for each variant, which starts
+// with the discriminant unless the enum has only one variant, so that a
+// debugger shows which variant a value is along with its fields.
+fn create_enum(cx: @crate_ctxt, t: ty::t, did: ast::def_id,
+               substs: &ty::substs, span: span) -> ValueRef {
+    let file_node = create_file(cx, filename_from_span(cx, span)).node;
+    let name = ty_to_str(cx.tcx, t);
+    let (size, align) = size_and_align_of(cx, t);
+    let variants = ty::enum_variants(cx.tcx, did);
+    let enumerators = do vec::map(*variants) |v| {
+        llmdnode(~[lltag(EnumeratorTag),
+                   llstr(cx.sess.str_of(v.name)),
+                   lli64(v.disr_val)])
+    };
+    if ty::type_is_c_like_enum(cx.tcx, t) {
+        return create_composite_type(EnumerationTypeTag, name, file_node,
+                                     0, size * 8, align * 8, 0,
+                                     option::None,
+                                     option::Some(enumerators));
     }
 
-    match ty.node {
-      ast::ty_box(mt) {
-        let inner_t = match ty::get(t).struct {
-          ty::ty_box(boxed) { boxed.ty }
-          _ { cx.sess.span_bug(ty.span, "t_to_ty was incoherent"); }
+    let (discr_size, discr_align) = size_and_align_of(cx, ty::mk_int(cx.tcx));
+    let discr = create_composite_type(EnumerationTypeTag, ~"", file_node, 0,
+                                      discr_size * 8, discr_align * 8, 0,
+                                      option::None,
+                                      option::Some(enumerators));
+    let degen = ty::enum_is_univariant(cx.tcx, did);
+    // The fields of a variant follow the discriminant, as in `GEP_enum`
+    let payload = if degen {
+        0u
+    } else {
+        machine::llelement_offset(cx, type_of::type_of(cx, t), 1u)
+    };
+    let members = do vec::map(*variants) |v| {
+        let args = do vec::mapi(v.args) |i, arg| {
+            (fmt!("__%u", i), ty::subst(cx.tcx, substs, *arg))
         };
-        let md = create_ty(cx, inner_t, mt.ty);
-        let box = create_boxed_type(cx, t, inner_t, ty.span, md);
-        return create_pointer_type(cx, t, ty.span, box);
-      }
-
-      ast::ty_uniq(mt) {
-        let inner_t = match ty::get(t).struct {
-          ty::ty_uniq(boxed) { boxed.ty }
-          // Hoping we'll have a way to eliminate this check soon.
-          _ { cx.sess.span_bug(ty.span, "t_to_ty was incoherent"); }
+        let mut fields = if degen {
+            ~[]
+        } else {
+            ~[create_derived_type(MemberTag, file_node, ~"discriminant", 0,
+                                  discr_size * 8, discr_align * 8, 0, discr)]
         };
-        let md = create_ty(cx, inner_t, mt.ty);
-        return create_pointer_type(cx, t, ty.span, md);
-      }
+        fields.push_all(create_members(cx, file_node, args, payload, span));
+        let variant_name = cx.sess.str_of(v.name);
+        let variant = create_composite_type(StructureTypeTag,
+                                            copy variant_name, file_node, 0,
+                                            size * 8, align * 8, 0,
+                                            option::None,
+                                            option::Some(fields));
+        create_derived_type(MemberTag, file_node, variant_name, 0, size * 8,
+                            align * 8, 0, variant)
+    };
+    create_composite_type(UnionTypeTag, name, file_node, 0, size * 8,
+                          align * 8, 0, option::None, option::Some(members))
+}
 
-      ast::ty_infer {
-        let inferred = t_to_ty(cx, t, ty.span);
-        return create_ty(cx, t, inferred);
-      }
+// The type of a function: its return type, or null if it returns nil,
+// then the types of its arguments
+fn create_subroutine_type(cx: @crate_ctxt, fn_t: ty::t, span: span)
+    -> ValueRef {
+    let file_node = create_file(cx, filename_from_span(cx, span)).node;
+    let output = ty::ty_fn_ret(fn_t);
+    let ret_node = if ty::type_is_nil(output) {
+        llnull()
+    } else {
+        create_ty(cx, output, span).node
+    };
+    let arg_nodes = do ty::ty_fn_args(fn_t).map |arg| {
+        create_ty(cx, arg.ty, span).node
+    };
+    create_composite_type(SubroutineTag, ~"", file_node, 0, 0, 0, 0,
+                          option::None,
+                          option::Some(vec::append(~[ret_node], arg_nodes)))
+}
 
-      ast::ty_rec(fields) {
-        return create_record(cx, t, fields, ty.span);
-      }
+// A closure, or a bare function, is a pair of a pointer to its code and
+// a pointer to the box of its environment. What the environment holds
+// depends on the closure; its captured variables are described along
+// with the closure's own variables, by `create_upvar`.
+fn create_fn_pair(cx: @crate_ctxt, t: ty::t, span: span) -> ValueRef {
+    let file_node = create_file(cx, filename_from_span(cx, span)).node;
+    let llty = type_of::type_of(cx, t);
+    let (ptr_size, ptr_align) = size_and_align_of(cx, ty::mk_nil_ptr(cx.tcx));
+    let code = create_derived_type(PointerTypeTag, file_node, ~"", 0,
+                                   ptr_size * 8, ptr_align * 8, 0,
+                                   create_subroutine_type(cx, t, span));
+    let env_offset = machine::llelement_offset(cx, llty, 1u);
+    let members = ~[create_derived_type(MemberTag, file_node, ~"code", 0,
+                                        ptr_size * 8, ptr_align * 8, 0,
+                                        code),
+                    create_member(cx, file_node, ~"env",
+                                  ty::mk_opaque_box(cx.tcx), env_offset,
+                                  span)];
+    let (size, align) = size_and_align_of(cx, t);
+    create_composite_type(StructureTypeTag, ty_to_str(cx.tcx, t), file_node,
+                          0, size * 8, align * 8, 0, option::None,
+                          option::Some(members))
+}
 
-      ast::ty_vec(mt) {
-        let inner_t = ty::sequence_element_type(cx.tcx, t);
-        let inner_ast_t = t_to_ty(cx, inner_t, mt.ty.span);
-        let v = create_vec(cx, t, inner_t, ty.span, inner_ast_t);
-        return create_pointer_type(cx, t, ty.span, v);
-      }
+/// The description of the type `t`, which is given the file of `span`
+pub fn create_ty(cx: @crate_ctxt, t: ty::t, span: span)
+    -> @metadata<tydesc_md> {
+    let dbg_cx = (/*bad*/copy cx.dbg_cx).get();
+    match dbg_cx.tydescs.find(&t) {
+      option::Some(md) => return md,
+      option::None => ()
+    }
 
-      ast::ty_path(_, id) {
-        match cx.tcx.def_map.get(id) {
-          ast::def_prim_ty(pty) {
-            return create_basic_type(cx, t, pty, ty.span);
-          }
-          _ {}
-        }
+    // A struct or an enum can point to itself. Until it's described, it
+    // is described as opaque, which is what those pointers point to.
+    match ty::get(t).sty {
+      ty::ty_enum(*) | ty::ty_struct(*) => {
+        dbg_cx.tydescs.insert(t, create_opaque_type(cx, t, span));
       }
+      _ => ()
+    }
 
-      _ {}
+    let llnode = match /*bad*/copy ty::get(t).sty {
+      ty::ty_nil | ty::ty_bot | ty::ty_bool | ty::ty_int(_) |
+      ty::ty_uint(_) | ty::ty_float(_) => {
+        return create_basic_type(cx, t, span);
+      }
+      ty::ty_estr(ty::vstore_uniq) | ty::ty_estr(ty::vstore_box) => {
+        let mt = ty::mt {ty: ty::mk_u8(cx.tcx), mutbl: ast::m_imm};
+        let boxed = create_boxed_type(cx, t, ty::mk_unboxed_vec(cx.tcx, mt),
+                                      span);
+        create_pointer_type(cx, t, span, boxed).node
+      }
+      ty::ty_evec(mt, ty::vstore_uniq) | ty::ty_evec(mt, ty::vstore_box) => {
+        let boxed = create_boxed_type(cx, t, ty::mk_unboxed_vec(cx.tcx, mt),
+                                      span);
+        create_pointer_type(cx, t, span, boxed).node
+      }
+      ty::ty_unboxed_vec(mt) => create_vec(cx, t, mt.ty, span),
+      ty::ty_estr(ty::vstore_slice(_)) => {
+        create_slice(cx, t, ty::mk_u8(cx.tcx), span)
+      }
+      ty::ty_evec(mt, ty::vstore_slice(_)) => {
+        create_slice(cx, t, mt.ty, span)
+      }
+      ty::ty_estr(ty::vstore_fixed(n)) => {
+        // Fixed-length strings have a trailing null
+        create_fixed_vec(cx, t, ty::mk_u8(cx.tcx), n + 1u, span)
+      }
+      ty::ty_evec(mt, ty::vstore_fixed(n)) => {
+        create_fixed_vec(cx, t, mt.ty, n, span)
+      }
+      ty::ty_box(mt) | ty::ty_uniq(mt) => {
+        let boxed = create_boxed_type(cx, t, mt.ty, span);
+        create_pointer_type(cx, t, span, boxed).node
+      }
+      ty::ty_opaque_box | ty::ty_opaque_closure_ptr(_) => {
+        let boxed = create_boxed_type(cx, t, ty::mk_u8(cx.tcx), span);
+        create_pointer_type(cx, t, span, boxed).node
+      }
+      ty::ty_ptr(mt) | ty::ty_rptr(_, mt) => {
+        let pointee = create_ty(cx, mt.ty, span);
+        create_pointer_type(cx, t, span, pointee).node
+      }
+      ty::ty_rec(fields) => {
+        let fields = do fields.map |f| {
+            (cx.sess.str_of(f.ident), f.mt.ty)
+        };
+        create_struct(cx, t, ty_to_str(cx.tcx, t), fields, span)
+      }
+      ty::ty_struct(did, ref substs) => {
+        let fields = do ty::lookup_struct_fields(cx.tcx, did).map |f| {
+            (cx.sess.str_of(f.ident),
+             ty::lookup_field_type(cx.tcx, did, f.id, substs))
+        };
+        create_struct(cx, t, ty_to_str(cx.tcx, t), fields, span)
+      }
+      ty::ty_tup(elts) => {
+        let fields = do vec::mapi(elts) |i, elt| {
+            (fmt!("__%u", i), *elt)
+        };
+        create_struct(cx, t, ty_to_str(cx.tcx, t), fields, span)
+      }
+      ty::ty_enum(did, ref substs) => create_enum(cx, t, did, substs, span),
+      ty::ty_bare_fn(_) | ty::ty_closure(_) => create_fn_pair(cx, t, span),
+      _ => create_opaque_type(cx, t, span).node
     };
-    */
+    let mdval = @{node: llnode, data: {hash: ty::type_id(t)}};
+    dbg_cx.tydescs.insert(t, mdval);
+    return mdval;
 }
 
 fn filename_from_span(cx: @crate_ctxt, sp: codemap::span) -> ~str {
@@ -654,102 +739,90 @@ fn create_var(type_tag: int, context: ValueRef, +name: ~str, file: ValueRef,
     return llmdnode(lldata);
 }
 
-pub fn create_local_var(bcx: block, local: @ast::local)
-    -> @metadata<local_var_md> {
-    unsafe {
-        let cx = bcx.ccx();
-        let cache = get_cache(cx);
-        let tg = AutoVariableTag;
-        match cached_metadata::<@metadata<local_var_md>>(
-            cache, tg, |md| md.data.id == local.node.id) {
-          option::Some(md) => return md,
-          option::None => ()
+// Describes the variable `id`, which is stored at `llptr`, in `context`,
+// and declares where it is
+fn declare_var(bcx: block, type_tag: int, context: ValueRef,
+               id: ast::node_id, name: ast::ident, sp: span,
+               llptr: ValueRef) -> ValueRef {
+    let cx = bcx.ccx();
+    let loc = cx.sess.codemap.lookup_char_pos(sp.lo);
+    let ty = node_id_type(bcx, id);
+    let tymd = create_ty(cx, ty, sp);
+    let filemd = create_file(cx, /*bad*/copy loc.file.name);
+    let mdnode = create_var(type_tag, context, cx.sess.str_of(name),
+                            filemd.node, loc.line as int, tymd.node);
+    let declargs = ~[llmdnode(~[llptr]), mdnode];
+    trans::build::Call(bcx, cx.intrinsics.get(&~"llvm.dbg.declare"),
+                       declargs);
+    return mdnode;
+}
+
+/// Declares each variable that `local` binds, in the innermost lexical
+/// block
+pub fn create_local_var(bcx: block, local: @ast::local) {
+    let cx = bcx.ccx();
+    let cache = get_cache(cx);
+    let tg = AutoVariableTag;
+    let context = match bcx.parent {
+        None => create_function(bcx.fcx).node,
+        Some(_) => create_block(bcx).node
+    };
+    do pat_bindings(cx.tcx.def_map, local.node.pat) |_, id, sp, path| {
+        // A variable that's bound to an immediate has no address
+        match bcx.fcx.lllocals.find(&id) {
+          option::Some(local_mem(llptr)) => {
+            let name = path.idents.last();
+            let mdnode = declare_var(bcx, tg, context, id, name, sp, llptr);
+            let mdval = @{node: mdnode, data: {id: id, name: name}};
+            update_cache(cache, tg, local_var_metadata(mdval));
+          }
+          _ => ()
         }
+    }
+}
 
-        let name = match local.node.pat.node {
-          ast::pat_ident(_, pth, _) => ast_util::path_to_ident(pth),
-          // FIXME this should be handled (#2533)
-          _ => die!(~"no single variable name for local")
-        };
-        let loc = cx.sess.codemap.lookup_char_pos(local.span.lo);
-        let ty = node_id_type(bcx, local.node.id);
-        let tymd = create_ty(cx, ty, local.node.ty);
-        let filemd = create_file(cx, /*bad*/copy loc.file.name);
-        let context = match bcx.parent {
-            None => create_function(bcx.fcx).node,
-            Some(_) => create_block(bcx).node
-        };
-        let mdnode = create_var(tg, context, cx.sess.str_of(name),
-                                filemd.node, loc.line as int, tymd.node);
-        let mdval = @{node: mdnode, data: {id: local.node.id}};
-        update_cache(cache, AutoVariableTag, local_var_metadata(mdval));
-
-        let llptr = match bcx.fcx.lllocals.find(&local.node.id) {
-          option::Some(local_mem(v)) => v,
-          option::Some(_) => {
-            bcx.tcx().sess.span_bug(local.span, ~"local is bound to \
-                    something weird");
-          }
-          option::None => {
-            match bcx.fcx.lllocals.get(&local.node.pat.id) {
-              local_imm(v) => v,
-              _ => bcx.tcx().sess.span_bug(local.span, ~"local is bound to \
-                                                         something weird")
-            }
+/// Declares each variable that the argument `arg` binds
+pub fn create_arg(bcx: block, arg: ast::arg) {
+    let fcx = bcx.fcx, cx = fcx.ccx;
+    let cache = get_cache(cx);
+    let tg = ArgVariableTag;
+    let context = create_function(fcx).node;
+    do pat_bindings(cx.tcx.def_map, arg.pat) |_, id, sp, path| {
+        match fcx.llargs.find(&id) {
+          option::Some(local_mem(llptr)) => {
+            let name = path.idents.last();
+            let mdnode = declare_var(bcx, tg, context, id, name, sp, llptr);
+            let mdval = @{node: mdnode, data: {id: id, name: name}};
+            update_cache(cache, tg, argument_metadata(mdval));
           }
-        };
-        let declargs = ~[llmdnode(~[llptr]), mdnode];
-        trans::build::Call(bcx, cx.intrinsics.get(&~"llvm.dbg.declare"),
-                           declargs);
-        return mdval;
+          _ => ()
+        }
     }
 }
 
-pub fn create_arg(bcx: block, arg: ast::arg, sp: span)
-    -> Option<@metadata<argument_md>> {
-    unsafe {
-        let fcx = bcx.fcx, cx = fcx.ccx;
-        let cache = get_cache(cx);
-        let tg = ArgVariableTag;
+/// Declares a variable that a closure captures, which is stored at
+/// `llptr` in its environment, as a variable of the closure. Only the
+/// variables that were themselves declared can be, since the name of a
+/// variable is only known from its declaration.
+pub fn create_upvar(bcx: block, cap_var: &moves::CaptureVar,
+                    llptr: ValueRef) {
+    let cx = bcx.ccx();
+    let cache = get_cache(cx);
+    let id = ast_util::def_id_of_def(cap_var.def).node;
+    let name = match cached_metadata::<@metadata<local_var_md>>(
+        cache, AutoVariableTag, |md| md.data.id == id) {
+      option::Some(md) => md.data.name,
+      option::None => {
         match cached_metadata::<@metadata<argument_md>>(
-            cache, ArgVariableTag, |md| md.data.id == arg.id) {
-          option::Some(md) => return Some(md),
-          option::None => ()
+            cache, ArgVariableTag, |md| md.data.id == id) {
+          option::Some(md) => md.data.name,
+          option::None => return
         }
-
-        let loc = cx.sess.codemap.lookup_char_pos(sp.lo);
-        let ty = node_id_type(bcx, arg.id);
-        let tymd = create_ty(cx, ty, arg.ty);
-        let filemd = create_file(cx, /*bad*/copy loc.file.name);
-        let context = create_function(bcx.fcx);
-
-        match arg.pat.node {
-            ast::pat_ident(_, path, _) => {
-                // XXX: This is wrong; it should work for multiple bindings.
-                let mdnode = create_var(tg,
-                                        context.node,
-                                        cx.sess.str_of(path.idents.last()),
-                                        filemd.node,
-                                        loc.line as int,
-                                        tymd.node);
-
-                let mdval = @{node: mdnode, data: {id: arg.id}};
-                update_cache(cache, tg, argument_metadata(mdval));
-
-                let llptr = match fcx.llargs.get(&arg.id) {
-                  local_mem(v) | local_imm(v) => v,
-                };
-                let declargs = ~[llmdnode(~[llptr]), mdnode];
-                trans::build::Call(bcx,
-                                   cx.intrinsics.get(&~"llvm.dbg.declare"),
-                                   declargs);
-                return Some(mdval);
-            }
-            _ => {
-                return None;
-            }
-        }
-    }
+      }
+    };
+    let context = create_function(bcx.fcx).node;
+    declare_var(bcx, AutoVariableTag, context, id, name, cap_var.span,
+                llptr);
 }
 
 pub fn update_source_pos(cx: block, s: span) {
@@ -769,80 +842,98 @@ pub fn update_source_pos(cx: block, s: span) {
     }
 }
 
+/**
+ * The subprogram of the function that `fcx` translates. Each
+ * monomorphized instance of a generic function is a subprogram of its
+ * own, whose name includes the types it's instantiated with.
+ */
 pub fn create_function(fcx: fn_ctxt) -> @metadata<subprogram_md> {
     let cx = fcx.ccx;
     let dbg_cx = (/*bad*/copy cx.dbg_cx).get();
 
+    let cache = get_cache(cx);
+    match cached_metadata::<@metadata<subprogram_md>>(
+        cache, SubprogramTag, |md| md.data.llfn == fcx.llfn) {
+      option::Some(md) => return md,
+      option::None => ()
+    }
+
     debug!("~~");
     log(debug, fcx.id);
 
     let sp = fcx.span.get();
     log(debug, cx.sess.codemap.span_to_str(sp));
 
-    let (ident, ret_ty, id) = match cx.tcx.items.get(&fcx.id) {
+    let (ident, fn_ty) = match cx.tcx.items.get(&fcx.id) {
       ast_map::node_item(item, _) => {
         match /*bad*/copy item.node {
-          ast::item_fn(decl, _, _, _) => {
-            (item.ident, decl.output, item.id)
+          ast::item_fn(*) => {
+            (item.ident, Some(ty::node_id_to_type(cx.tcx, item.id)))
           }
           _ => fcx.ccx.sess.span_bug(item.span, ~"create_function: item \
                                                   bound to non-function")
         }
       }
       ast_map::node_method(method, _, _) => {
-          (method.ident, method.decl.output, method.id)
+          (method.ident, Some(ty::node_id_to_type(cx.tcx, method.id)))
+      }
+      ast_map::node_trait_method(@ast::provided(method), _, _) => {
+          (method.ident, Some(ty::node_id_to_type(cx.tcx, method.id)))
       }
       ast_map::node_expr(expr) => {
         match /*bad*/copy expr.node {
-          ast::expr_fn(_, decl, _, _) => {
-            ((dbg_cx.names)(~"fn"), decl.output, expr.id)
-          }
-          ast::expr_fn_block(decl, _) => {
-            ((dbg_cx.names)(~"fn"), decl.output, expr.id)
+          ast::expr_fn(*) | ast::expr_fn_block(*) => {
+            ((dbg_cx.names)(~"fn"),
+             Some(ty::node_id_to_type(cx.tcx, expr.id)))
           }
           _ => fcx.ccx.sess.span_bug(expr.span,
                                      ~"create_function: \
                                        expected an expr_fn or fn_block here")
         }
       }
-      ast_map::node_dtor(_, _, did, _) => {
-        ((dbg_cx.names)(~"dtor"), ast_util::dtor_ty(), did.node)
-      }
+      ast_map::node_dtor(*) => ((dbg_cx.names)(~"dtor"), None),
       _ => fcx.ccx.sess.bug(~"create_function: unexpected \
                               sort of node")
     };
 
     log(debug, ident);
-    log(debug, id);
 
-    let cache = get_cache(cx);
-    match cached_metadata::<@metadata<subprogram_md>>(
-        cache, SubprogramTag, |md| md.data.id == id) {
-      option::Some(md) => return md,
-      option::None => ()
-    }
+    let name = match fcx.param_substs {
+      Some(ref substs) if !substs.tys.is_empty() => {
+        let tys = substs.tys.map(|t| ty_to_str(cx.tcx, *t));
+        fmt!("%s<%s>", cx.sess.str_of(ident), str::connect(tys, ~", "))
+      }
+      _ => cx.sess.str_of(ident)
+    };
+    let linkage_name = unsafe {
+        str::raw::from_c_str(llvm::LLVMGetValueName(fcx.llfn))
+    };
 
     let loc = cx.sess.codemap.lookup_char_pos(sp.lo);
     let file_node = create_file(cx, copy loc.file.name).node;
-    let ty_node = if cx.sess.opts.extra_debuginfo {
-        match ret_ty.node {
-          ast::ty_nil => llnull(),
-          _ => create_ty(cx, ty::node_id_to_type(cx.tcx, id), ret_ty).node
-        }
-    } else {
-        llnull()
+    let sub_node = match fn_ty {
+      Some(fn_ty) if cx.sess.opts.extra_debuginfo => {
+        let fn_ty = match fcx.param_substs {
+          Some(ref substs) => {
+            ty::subst_tps(cx.tcx, substs.tys, substs.self_ty, fn_ty)
+          }
+          None => fn_ty
+        };
+        create_subroutine_type(cx, fn_ty, sp)
+      }
+      _ => {
+        create_composite_type(SubroutineTag, ~"", file_node, 0, 0, 0, 0,
+                              option::None, option::Some(~[llnull()]))
+      }
     };
-    let sub_node = create_composite_type(SubroutineTag, ~"", file_node, 0, 0,
-                                         0, 0, option::None,
-                                         option::Some(~[ty_node]));
 
     let fn_metadata = ~[lltag(SubprogramTag),
                        llunused(),
                        file_node,
-                       llstr(cx.sess.str_of(ident)),
+                       llstr(copy name),
                         //XXX fully-qualified C++ name:
-                       llstr(cx.sess.str_of(ident)),
-                       llstr(~""), //XXX MIPS name?????
+                       llstr(name),
+                       llstr(linkage_name),
                        file_node,
                        lli32(loc.line as int),
                        sub_node,
@@ -860,7 +951,7 @@ pub fn create_function(fcx: fn_ctxt) -> @metadata<subprogram_md> {
                       ];
     let val = llmdnode(fn_metadata);
     add_named_metadata(cx, ~"llvm.dbg.sp", val);
-    let mdval = @{node: val, data: {id: id}};
+    let mdval = @{node: val, data: {llfn: fcx.llfn}};
     update_cache(cache, SubprogramTag, subprogram_metadata(mdval));
 
     return mdval;
@@ -16,6 +16,7 @@ use middle::trans::type_of;
 use middle::ty::field;
 use middle::ty;
 
+use core::libc::c_uint;
 use syntax::parse::token::special_idents;
 
 // Creates a simpler, size-equivalent type. The resulting type is guaranteed
@@ -178,6 +179,16 @@ pub fn llalign_of(cx: @crate_ctxt, t: TypeRef) -> ValueRef {
     }
 }
 
+// Returns the offset in bytes of the given element of a struct type, as
+// it's laid out for the current target.
+pub fn llelement_offset(cx: @crate_ctxt, struct_ty: TypeRef,
+                        element: uint) -> uint {
+    unsafe {
+        return llvm::LLVMOffsetOfElement(cx.td.lltd, struct_ty,
+                                         element as c_uint) as uint;
+    }
+}
+
 // Computes the size of the data part of an enum.
 pub fn static_size_of_enum(cx: @crate_ctxt, t: ty::t) -> uint {
     if cx.enum_sizes.contains_key_ref(&t) { return cx.enum_sizes.get(&t); }
@@ -124,6 +124,7 @@ use syntax::ast_util::{visibility_to_privacy};
 use syntax::ast_util;
 use syntax::codemap::{span, spanned, respan};
 use syntax::codemap;
+use syntax::diagnostic;
 use syntax::parse::token::special_idents;
 use syntax::print::pprust;
 use syntax::visit;
@@ -506,11 +507,14 @@ pub fn check_no_duplicate_fields(tcx: ty::ctxt,
         let (id, sp) = *p;
         match field_names.find(&id) {
           Some(orig_sp) => {
-            tcx.sess.span_err(sp, fmt!("Duplicate field \
-                                   name %s in record type declaration",
-                                        tcx.sess.str_of(id)));
-            tcx.sess.span_note(orig_sp, ~"First declaration of \
-                                          this field occurred here");
+            let mut d = diagnostic::Diagnostic::spanned(
+                diagnostic::error, sp,
+                fmt!("Duplicate field name %s in record type declaration",
+                     tcx.sess.str_of(id)));
+            d.span_label(sp, ~"duplicate field");
+            d.span_label(orig_sp, ~"first declaration of this field \
+                                    occurred here");
+            tcx.sess.emit_diagnostic(&d);
             break;
           }
           None => {
@@ -49,6 +49,7 @@ use syntax::ast_map;
 use syntax::ast_util::{def_id_of_def, local_def};
 use syntax::attr;
 use syntax::codemap::{span, dummy_sp};
+use syntax::diagnostic;
 use syntax::parse;
 use syntax::visit::{default_simple_visitor, default_visitor};
 use syntax::visit::{mk_simple_visitor, mk_vt, visit_crate, visit_item};
@@ -445,12 +446,15 @@ pub impl CoherenceChecker {
 
                     if self.polytypes_unify(polytype_a, polytype_b) {
                         let session = self.crate_context.tcx.sess;
-                        session.span_err(self.span_of_impl(implementation_b),
-                                         ~"conflicting implementations for a \
-                                           trait");
-                        session.span_note(self.span_of_impl(implementation_a),
-                                          ~"note conflicting implementation \
-                                            here");
+                        let span_a = self.span_of_impl(implementation_a);
+                        let span_b = self.span_of_impl(implementation_b);
+                        let mut d = diagnostic::Diagnostic::spanned(
+                            diagnostic::error, span_b,
+                            ~"conflicting implementations for a trait");
+                        d.span_label(span_b, ~"second implementation");
+                        d.span_label(span_a, ~"conflicting implementation \
+                                               here");
+                        session.emit_diagnostic(&d);
                     }
                 }
             }
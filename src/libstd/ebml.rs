@@ -394,6 +394,13 @@ pub mod reader {
             f()
         }
 
+        // Every field that was encoded is in the document, so there's
+        // never a missing one to default
+        fn read_field_or<T>(&self, name: &str, idx: uint, f: fn() -> T,
+                            _default: fn() -> T) -> T {
+            self.read_field(name, idx, f)
+        }
+
         fn read_tup<T>(&self, len: uint, f: fn() -> T) -> T {
             debug!("read_tup(len=%u)", len);
             f()
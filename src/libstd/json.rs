@@ -899,6 +899,16 @@ pub impl Decoder: serialize::Decoder {
         }
     }
 
+    fn read_field_or<T>(&self, name: &str, idx: uint, f: fn() -> T,
+                        default: fn() -> T) -> T {
+        debug!("read_field_or(%s, idx=%u)", name, idx);
+        let has_field = match *self.peek() {
+            Object(ref obj) => obj.contains_key(&name.to_owned()),
+            _ => true
+        };
+        if has_field { self.read_field(name, idx, f) } else { default() }
+    }
+
     fn read_tup<T>(&self, len: uint, f: fn() -> T) -> T {
         debug!("read_tup(len=%u)", len);
         let value = f();
@@ -100,6 +100,9 @@ pub trait Decoder {
     fn read_rec<T>(&self, f: fn() -> T) -> T;
     fn read_struct<T>(&self, name: &str, _len: uint, f: fn() -> T) -> T;
     fn read_field<T>(&self, name: &str, idx: uint, f: fn() -> T) -> T;
+    // Like read_field, but calls `default` instead if the field is missing
+    fn read_field_or<T>(&self, name: &str, idx: uint, f: fn() -> T,
+                        default: fn() -> T) -> T;
 
     fn read_tup<T>(&self, sz: uint, f: fn() -> T) -> T;
     fn read_tup_elt<T>(&self, idx: uint, f: fn() -> T) -> T;
@@ -549,6 +552,52 @@ pub impl<
     }
 }
 
+// ___________________________________________________________________________
+// Defaults
+//
+// The values #[auto_decode] gives to #[serial_skip] fields, and to
+// #[serial_default] fields that are missing.
+
+pub trait DecodeDefault {
+    static fn decode_default() -> Self;
+}
+
+pub impl uint: DecodeDefault { static fn decode_default() -> uint { 0 } }
+pub impl u8: DecodeDefault { static fn decode_default() -> u8 { 0 } }
+pub impl u16: DecodeDefault { static fn decode_default() -> u16 { 0 } }
+pub impl u32: DecodeDefault { static fn decode_default() -> u32 { 0 } }
+pub impl u64: DecodeDefault { static fn decode_default() -> u64 { 0 } }
+pub impl int: DecodeDefault { static fn decode_default() -> int { 0 } }
+pub impl i8: DecodeDefault { static fn decode_default() -> i8 { 0 } }
+pub impl i16: DecodeDefault { static fn decode_default() -> i16 { 0 } }
+pub impl i32: DecodeDefault { static fn decode_default() -> i32 { 0 } }
+pub impl i64: DecodeDefault { static fn decode_default() -> i64 { 0 } }
+pub impl float: DecodeDefault { static fn decode_default() -> float { 0. } }
+pub impl f32: DecodeDefault { static fn decode_default() -> f32 { 0. } }
+pub impl f64: DecodeDefault { static fn decode_default() -> f64 { 0. } }
+pub impl bool: DecodeDefault { static fn decode_default() -> bool { false } }
+pub impl (): DecodeDefault { static fn decode_default() -> () { () } }
+
+pub impl ~str: DecodeDefault {
+    static fn decode_default() -> ~str { ~"" }
+}
+
+pub impl @str: DecodeDefault {
+    static fn decode_default() -> @str { @"" }
+}
+
+pub impl<T> ~[T]: DecodeDefault {
+    static fn decode_default() -> ~[T] { ~[] }
+}
+
+pub impl<T> @[T]: DecodeDefault {
+    static fn decode_default() -> @[T] { @[] }
+}
+
+pub impl<T> Option<T>: DecodeDefault {
+    static fn decode_default() -> Option<T> { None }
+}
+
 // ___________________________________________________________________________
 // Helper routines
 //
@@ -29,8 +29,30 @@ macro_rules! interner_key (
         (-3 as uint, 0u)))
 )
 
-#[deriving_eq]
-pub struct ident { repr: uint }
+/// An identifier is an interned name plus the syntax context it was written
+/// in. Names that a macro expansion introduces carry a context marked by
+/// that expansion, which is how `middle::resolve` keeps them from capturing,
+/// or being captured by, local bindings at the call site. Everywhere else
+/// identifiers are compared and hashed by name alone.
+pub struct ident { repr: uint, ctxt: SyntaxContext }
+
+/// An index into the syntax context table kept by the ident interner
+pub type SyntaxContext = uint;
+
+/// The context of identifiers written directly in the source
+pub const empty_ctxt: SyntaxContext = 0;
+
+pub enum SyntaxContext_ {
+    EmptyCtxt,
+    /// The context of tokens transcribed by the macro expansion with the
+    /// given mark, inside the parent context
+    Mark(uint, SyntaxContext)
+}
+
+pub impl ident : cmp::Eq {
+    pure fn eq(&self, other: &ident) -> bool { self.repr == other.repr }
+    pure fn ne(&self, other: &ident) -> bool { !(*self).eq(other) }
+}
 
 pub impl<S: Encoder> ident: Encodable<S> {
     fn encode(&self, s: &S) {
@@ -288,6 +310,9 @@ pub struct crate_ {
     module: _mod,
     attrs: ~[attribute],
     config: crate_cfg,
+    // The `macro_rules!` definitions marked #[macro_export], kept after
+    // expansion so that they can be written into the crate metadata
+    exported_macros: ~[@item],
 }
 
 pub type meta_item = spanned<meta_item_>;
@@ -1555,6 +1580,7 @@ pub struct struct_field_ {
     kind: struct_field_kind,
     id: node_id,
     ty: @Ty,
+    attrs: ~[attribute],
 }
 
 pub type struct_field = spanned<struct_field_>;
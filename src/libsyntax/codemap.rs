@@ -315,6 +315,23 @@ pub impl CodeMap {
         return filemap;
     }
 
+    /// Describes where an expansion came from, innermost first: `foo! at
+    /// a.rs:3:4, inside bar! at a.rs:10:1`
+    pub fn expn_info_to_str(&self, ei: @ExpnInfo) -> ~str {
+        match *ei {
+          ExpandedFrom({call_site: cs, callie: ref callie}) => {
+            let loc = self.lookup_char_pos(cs.lo);
+            let here = fmt!("%s! at %s:%u:%u", callie.name, loc.file.name,
+                            loc.line, loc.col.to_uint());
+            match cs.expn_info {
+              Some(outer) => here + ~", inside " +
+                             self.expn_info_to_str(outer),
+              None => here
+            }
+          }
+        }
+    }
+
     pub fn mk_substr_filename(&self, sp: span) -> ~str {
         let pos = self.lookup_char_pos(sp.lo);
         return fmt!("<%s:%u:%u>", pos.file.name,
@@ -528,6 +545,26 @@ mod test {
         fm.next_line(BytePos(10));
         fm.next_line(BytePos(2));
     }
+
+    #[test]
+    fn expn_info_to_str_nested() {
+        let cm = CodeMap::new();
+        let fm = cm.new_filemap(~"blork.rs", @~"bar!(\nfoo!())");
+        fm.next_line(BytePos(0));
+        fm.next_line(BytePos(6));
+        let outer = @ExpandedFrom({
+            call_site: span {lo: BytePos(0), hi: BytePos(13),
+                             expn_info: None},
+            callie: {name: ~"bar", span: None}
+        });
+        let inner = @ExpandedFrom({
+            call_site: span {lo: BytePos(6), hi: BytePos(12),
+                             expn_info: Some(outer)},
+            callie: {name: ~"foo", span: None}
+        });
+        check_equal(&cm.expn_info_to_str(inner),
+                    &~"foo! at blork.rs:2:0, inside bar! at blork.rs:1:0");
+    }
 }
 
 
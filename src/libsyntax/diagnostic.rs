@@ -21,10 +21,11 @@ use core::str;
 use core::vec;
 use core::dvec::DVec;
 
+use std::json;
+use std::serialize::Encodable;
 use std::term;
 
-pub type Emitter = fn@(cmsp: Option<(@codemap::CodeMap, span)>,
-                   msg: &str, lvl: level);
+pub type Emitter = fn@(cm: Option<@codemap::CodeMap>, d: &Diagnostic);
 
 
 pub trait span_handler {
@@ -34,6 +35,8 @@ pub trait span_handler {
     fn span_note(@mut self, sp: span, msg: &str);
     fn span_bug(@mut self, sp: span, msg: &str) -> !;
     fn span_unimpl(@mut self, sp: span, msg: &str) -> !;
+    fn span_lint(@mut self, sp: span, msg: &str, lvl: level, lint: &str);
+    fn emit_diagnostic(@mut self, d: &Diagnostic);
     fn handler(@mut self) -> handler;
 }
 
@@ -47,10 +50,7 @@ pub trait handler {
     fn note(@mut self, msg: &str);
     fn bug(@mut self, msg: &str) -> !;
     fn unimpl(@mut self, msg: &str) -> !;
-    fn emit(@mut self,
-            cmsp: Option<(@codemap::CodeMap, span)>,
-            msg: &str,
-            lvl: level);
+    fn emit(@mut self, cm: Option<@codemap::CodeMap>, d: &Diagnostic);
 }
 
 struct HandlerT {
@@ -65,18 +65,16 @@ struct CodemapT {
 
 impl CodemapT: span_handler {
     fn span_fatal(@mut self, sp: span, msg: &str) -> ! {
-        self.handler.emit(Some((self.cm, sp)), msg, fatal);
-        die!();
+        self.emit_fatal(&Diagnostic::spanned(fatal, sp, msg))
     }
     fn span_err(@mut self, sp: span, msg: &str) {
-        self.handler.emit(Some((self.cm, sp)), msg, error);
-        self.handler.bump_err_count();
+        self.emit_diagnostic(&Diagnostic::spanned(error, sp, msg));
     }
     fn span_warn(@mut self, sp: span, msg: &str) {
-        self.handler.emit(Some((self.cm, sp)), msg, warning);
+        self.emit_diagnostic(&Diagnostic::spanned(warning, sp, msg));
     }
     fn span_note(@mut self, sp: span, msg: &str) {
-        self.handler.emit(Some((self.cm, sp)), msg, note);
+        self.emit_diagnostic(&Diagnostic::spanned(note, sp, msg));
     }
     fn span_bug(@mut self, sp: span, msg: &str) -> ! {
         self.span_fatal(sp, ice_msg(msg));
@@ -84,18 +82,40 @@ impl CodemapT: span_handler {
     fn span_unimpl(@mut self, sp: span, msg: &str) -> ! {
         self.span_bug(sp, ~"unimplemented " + msg);
     }
+    fn span_lint(@mut self, sp: span, msg: &str, lvl: level, lint: &str) {
+        let mut d = Diagnostic::spanned(lvl, sp, msg);
+        d.lint = Some(str::from_slice(lint));
+        self.emit_diagnostic(&d);
+    }
+    fn emit_diagnostic(@mut self, d: &Diagnostic) {
+        match d.level {
+          fatal => self.emit_fatal(d),
+          error => {
+            self.handler.emit(Some(self.cm), d);
+            self.handler.bump_err_count();
+          }
+          warning | note => self.handler.emit(Some(self.cm), d)
+        }
+    }
     fn handler(@mut self) -> handler {
         self.handler
     }
 }
 
+priv impl CodemapT {
+    fn emit_fatal(@mut self, d: &Diagnostic) -> ! {
+        self.handler.emit(Some(self.cm), d);
+        die!();
+    }
+}
+
 impl HandlerT: handler {
     fn fatal(@mut self, msg: &str) -> ! {
-        (self.emit)(None, msg, fatal);
+        (self.emit)(None, &Diagnostic::new(fatal, msg));
         die!();
     }
     fn err(@mut self, msg: &str) {
-        (self.emit)(None, msg, error);
+        (self.emit)(None, &Diagnostic::new(error, msg));
         self.bump_err_count();
     }
     fn bump_err_count(@mut self) {
@@ -115,10 +135,10 @@ impl HandlerT: handler {
         self.fatal(s);
     }
     fn warn(@mut self, msg: &str) {
-        (self.emit)(None, msg, warning);
+        (self.emit)(None, &Diagnostic::new(warning, msg));
     }
     fn note(@mut self, msg: &str) {
-        (self.emit)(None, msg, note);
+        (self.emit)(None, &Diagnostic::new(note, msg));
     }
     fn bug(@mut self, msg: &str) -> ! {
         self.fatal(ice_msg(msg));
@@ -126,11 +146,83 @@ impl HandlerT: handler {
     fn unimpl(@mut self, msg: &str) -> ! {
         self.bug(~"unimplemented " + msg);
     }
-    fn emit(@mut self,
-            cmsp: Option<(@codemap::CodeMap, span)>,
-            msg: &str,
-            lvl: level) {
-        (self.emit)(cmsp, msg, lvl);
+    fn emit(@mut self, cm: Option<@codemap::CodeMap>, d: &Diagnostic) {
+        (self.emit)(cm, d);
+    }
+}
+
+/// A label drawn under a span of source code
+pub struct SpanLabel {
+    span: span,
+    label: ~str,
+}
+
+/// A note attached to a diagnostic, printed immediately after it
+pub struct SubDiagnostic {
+    span: Option<span>,
+    message: ~str,
+}
+
+/**
+ * A single diagnostic, with everything needed to print it.
+ *
+ * Besides the span it is reported at, a diagnostic may point at any number
+ * of other spans, each with a label, and carry notes of its own. The
+ * emitter draws all the labelled spans in one source snippet and prints the
+ * notes right after it, so they can't get separated from the diagnostic
+ * they explain. Use it like this:
+ *
+ *     let mut d = Diagnostic::spanned(error, new_loan_sp, msg);
+ *     d.span_label(new_loan_sp, ~"second borrow here");
+ *     d.span_label(old_loan_sp, ~"prior loan granted here");
+ *     d.note(~"the prior loan lasts until the end of the block");
+ *     sess.span_diagnostic.emit_diagnostic(&d);
+ */
+pub struct Diagnostic {
+    level: level,
+    message: ~str,
+    /// The lint that caused the diagnostic, if any
+    lint: Option<~str>,
+    /// The span the diagnostic is reported at, pointed to with a caret
+    span: Option<span>,
+    /// Labels for the primary span and for any secondary spans
+    labels: ~[SpanLabel],
+    notes: ~[SubDiagnostic],
+}
+
+pub impl Diagnostic {
+    static fn new(lvl: level, msg: &str) -> Diagnostic {
+        Diagnostic {
+            level: lvl,
+            message: str::from_slice(msg),
+            lint: None,
+            span: None,
+            labels: ~[],
+            notes: ~[]
+        }
+    }
+
+    static fn spanned(lvl: level, sp: span, msg: &str) -> Diagnostic {
+        let mut d = Diagnostic::new(lvl, msg);
+        d.span = Some(sp);
+        d
+    }
+
+    /// Labels a span. If it isn't the primary span it is underlined as a
+    /// secondary span.
+    fn span_label(&mut self, sp: span, label: &str) {
+        self.labels.push(SpanLabel { span: sp,
+                                     label: str::from_slice(label) });
+    }
+
+    fn note(&mut self, msg: &str) {
+        self.notes.push(SubDiagnostic { span: None,
+                                        message: str::from_slice(msg) });
+    }
+
+    fn span_note(&mut self, sp: span, msg: &str) {
+        self.notes.push(SubDiagnostic { span: Some(sp),
+                                        message: str::from_slice(msg) });
     }
 }
 
@@ -147,7 +239,7 @@ pub fn mk_handler(emitter: Option<Emitter>) -> @handler {
     let emit: Emitter = match emitter {
         Some(e) => e,
         None => {
-            let emit: Emitter = |cmsp, msg, t| emit(cmsp, msg, t);
+            let emit: Emitter = |cm, d| emit(cm, d);
             emit
         }
     };
@@ -155,6 +247,24 @@ pub fn mk_handler(emitter: Option<Emitter>) -> @handler {
     @mut HandlerT { mut err_count: 0, emit: emit } as @handler
 }
 
+/// The format in which diagnostics are written to stderr
+#[deriving_eq]
+pub enum ErrorFormat {
+    /// Plain text with source snippets, meant to be read by people
+    HumanReadable,
+    /// One JSON object per line, meant to be read by tools
+    Json,
+}
+
+/// Returns the emitter that writes diagnostics in the given format
+pub fn emitter_for_format(format: ErrorFormat) -> Emitter {
+    let emit: Emitter = match format {
+      HumanReadable => |cm, d| emit(cm, d),
+      Json => |cm, d| json_emit(cm, d)
+    };
+    emit
+}
+
 #[deriving_eq]
 pub enum level {
     fatal,
@@ -198,84 +308,158 @@ fn print_diagnostic(topic: ~str, lvl: level, msg: &str) {
 }
 
 pub fn collect(messages: @DVec<~str>)
-    -> fn@(Option<(@codemap::CodeMap, span)>, &str, level)
+    -> fn@(Option<@codemap::CodeMap>, &Diagnostic)
 {
-    let f: @fn(Option<(@codemap::CodeMap, span)>, &str, level) =
-        |_o, msg: &str, _l| { messages.push(msg.to_str()); };
+    let f: @fn(Option<@codemap::CodeMap>, &Diagnostic) =
+        |_cm, d: &Diagnostic| { messages.push(copy d.message); };
     f
 }
 
-pub fn emit(cmsp: Option<(@codemap::CodeMap, span)>, msg: &str, lvl: level) {
-    match cmsp {
-      Some((cm, sp)) => {
+pub fn emit(cm: Option<@codemap::CodeMap>, d: &Diagnostic) {
+    match (cm, d.span) {
+      (Some(cm), Some(sp)) => {
         let sp = cm.adjust_span(sp);
-        let ss = cm.span_to_str(sp);
-        let lines = cm.span_to_lines(sp);
-        print_diagnostic(ss, lvl, msg);
-        highlight_lines(cm, sp, lines);
+        print_diagnostic(cm.span_to_str(sp), d.level, d.message);
+        highlight_spans(io::stderr(), cm, sp, d.labels);
         print_macro_backtrace(cm, sp);
       }
-      None => {
-        print_diagnostic(~"", lvl, msg);
+      _ => {
+        print_diagnostic(~"", d.level, d.message);
       }
     }
+    for d.notes.each |n| {
+        match (cm, n.span) {
+          (Some(cm), Some(sp)) => {
+            let sp = cm.adjust_span(sp);
+            print_diagnostic(cm.span_to_str(sp), note, n.message);
+            highlight_spans(io::stderr(), cm, sp, ~[]);
+          }
+          _ => {
+            print_diagnostic(~"", note, n.message);
+          }
+        }
+    }
+}
+
+/// A span to be drawn by `highlight_spans`, resolved to source locations
+struct Mark {
+    lo: codemap::Loc,
+    hi: codemap::Loc,
+    label: Option<~str>,
+    is_primary: bool,
 }
 
-fn highlight_lines(cm: @codemap::CodeMap,
-                   sp: span,
-                   lines: @codemap::FileLines) {
-    let fm = lines.file;
+pure fn mark_le(a: &Mark, b: &Mark) -> bool {
+    a.lo.line < b.lo.line || (a.lo.line == b.lo.line && a.lo.col <= b.lo.col)
+}
 
-    // arbitrarily only print up to six lines of the error
+/**
+ * Writes the source lines covered by the primary span and by any labelled
+ * secondary spans, underlining each span beneath the line it starts on:
+ * the primary span as `^~~~`, secondary spans as `----`. Labels are printed
+ * after the underline. Spans in other files than the primary one get their
+ * own snippet.
+ */
+fn highlight_spans(w: io::Writer, cm: @codemap::CodeMap, sp: span,
+                   labels: &[SpanLabel]) {
+    let mut primary_label = None;
+    let mut marks = ~[];
+    for labels.each |l| {
+        let lsp = cm.adjust_span(l.span);
+        if lsp == sp {
+            primary_label = Some(copy l.label);
+        } else {
+            marks.push(Mark { lo: cm.lookup_char_pos(lsp.lo),
+                              hi: cm.lookup_char_pos(lsp.hi),
+                              label: Some(copy l.label),
+                              is_primary: false });
+        }
+    }
+    marks.push(Mark { lo: cm.lookup_char_pos(sp.lo),
+                      hi: cm.lookup_char_pos(sp.hi),
+                      label: primary_label,
+                      is_primary: true });
+
+    // Draw the primary span's file first, then any others in the order
+    // their spans were labelled
+    let mut files = ~[marks.last().lo.file];
+    for marks.each |m| {
+        if !files.any(|f| f.start_pos == m.lo.file.start_pos) {
+            files.push(m.lo.file);
+        }
+    }
+    for files.each |fm| {
+        let in_file = marks.filtered(|m| m.lo.file.start_pos == fm.start_pos);
+        highlight_marks(w, *fm, std::sort::merge_sort(in_file, mark_le));
+    }
+}
+
+fn highlight_marks(w: io::Writer, fm: @codemap::FileMap, marks: &[Mark]) {
+    // arbitrarily only print up to six lines of any one span
     let max_lines = 6u;
-    let mut elided = false;
-    let mut display_lines = /* FIXME (#2543) */ copy lines.lines;
-    if vec::len(display_lines) > max_lines {
-        display_lines = vec::slice(display_lines, 0u, max_lines);
-        elided = true;
-    }
-    // Print the offending lines
-    for display_lines.each |line| {
-        io::stderr().write_str(fmt!("%s:%u ", fm.name, *line + 1u));
-        let s = fm.get_line(*line as int) + ~"\n";
-        io::stderr().write_str(s);
-    }
-    if elided {
-        let last_line = display_lines[vec::len(display_lines) - 1u];
-        let s = fmt!("%s:%u ", fm.name, last_line + 1u);
-        let mut indent = str::len(s);
-        let mut out = ~"";
-        while indent > 0u { out += ~" "; indent -= 1u; }
-        out += ~"...\n";
-        io::stderr().write_str(out);
-    }
-
-
-    // If there's one line at fault we can easily point to the problem
-    if vec::len(lines.lines) == 1u {
-        let lo = cm.lookup_char_pos(sp.lo);
-        let mut digits = 0u;
-        let mut num = (lines.lines[0] + 1u) / 10u;
-
-        // how many digits must be indent past?
-        while num > 0u { num /= 10u; digits += 1u; }
-
-        // indent past |name:## | and the 0-offset column location
-        let mut left = str::len(fm.name) + digits + lo.col.to_uint() + 3u;
-        let mut s = ~"";
-        while left > 0u { str::push_char(&mut s, ' '); left -= 1u; }
-
-        s += ~"^";
-        let hi = cm.lookup_char_pos(sp.hi);
-        if hi.col != lo.col {
-            // the ^ already takes up one space
-            let mut width = hi.col.to_uint() - lo.col.to_uint() - 1u;
-            while width > 0u { str::push_char(&mut s, '~'); width -= 1u; }
+
+    // The (0-based) lines to print, and those among them that are the last
+    // ones shown of a span that had to be cut short
+    let mut lines = ~[];
+    let mut elided_lines = ~[];
+    for marks.each |m| {
+        let first = m.lo.line - 1u;
+        let last = uint::min(m.hi.line - 1u, first + max_lines - 1u);
+        for uint::range(first, last + 1u) |line| {
+            if !lines.contains(&line) { lines.push(line); }
+        }
+        if last < m.hi.line - 1u { elided_lines.push(last); }
+    }
+    let lines = std::sort::merge_sort(lines, |a, b| *a <= *b);
+
+    let mut prev_line = None;
+    for lines.each |&line| {
+        let prefix = fmt!("%s:%u ", fm.name, line + 1u);
+        match prev_line {
+          Some(prev) if line > prev + 1u => print_elision(w, prefix.len()),
+          _ => ()
         }
-        io::stderr().write_str(s + ~"\n");
+        w.write_str(prefix + fm.get_line(line as int) + ~"\n");
+
+        for marks.each |m| {
+            if m.lo.line - 1u != line { loop; }
+            let mut s = str::from_bytes(
+                vec::from_elem(prefix.len() + m.lo.col.to_uint(), ' ' as u8));
+            // A span ending on a later line is underlined up to the end of
+            // its first line
+            let end = if m.hi.line == m.lo.line {
+                m.hi.col.to_uint()
+            } else {
+                str::char_len(fm.get_line(line as int))
+            };
+            let width = if end > m.lo.col.to_uint() {
+                end - m.lo.col.to_uint()
+            } else {
+                1u
+            };
+            if m.is_primary {
+                s += ~"^";
+                s += str::repeat("~", width - 1u);
+            } else {
+                s += str::repeat("-", width);
+            }
+            match m.label {
+              Some(ref label) => { s += ~" "; s += *label; }
+              None => ()
+            }
+            w.write_str(s + ~"\n");
+        }
+        if elided_lines.contains(&line) { print_elision(w, prefix.len()); }
+        prev_line = Some(line);
     }
 }
 
+fn print_elision(w: io::Writer, indent: uint) {
+    let mut out = str::from_bytes(vec::from_elem(indent, ' ' as u8));
+    out += ~"...\n";
+    w.write_str(out);
+}
+
 fn print_macro_backtrace(cm: @codemap::CodeMap, sp: span) {
     do option::iter(&sp.expn_info) |ei| {
         let ss = option::map_default(&ei.callie.span, @~"",
@@ -288,6 +472,113 @@ fn print_macro_backtrace(cm: @codemap::CodeMap, sp: span) {
     }
 }
 
+/// A span as it appears in the output of `json_emit`. Byte offsets are
+/// relative to the start of the file, lines are 1-based and columns are
+/// 0-based character offsets, as in the human-readable output.
+#[auto_encode]
+struct JsonSpan {
+    file_name: ~str,
+    byte_start: uint,
+    byte_end: uint,
+    line_start: uint,
+    line_end: uint,
+    column_start: uint,
+    column_end: uint,
+    is_primary: bool,
+    label: Option<~str>,
+}
+
+/// A single diagnostic as it appears in the output of `json_emit`. Notes
+/// attached to the diagnostic are nested in `notes`, with level "note".
+#[auto_encode]
+struct JsonDiagnostic {
+    level: ~str,
+    message: ~str,
+    lint: Option<~str>,
+    spans: ~[JsonSpan],
+    notes: ~[JsonDiagnostic],
+}
+
+fn json_span(cm: @codemap::CodeMap, sp: span, is_primary: bool,
+             label: Option<~str>) -> JsonSpan {
+    let sp = cm.adjust_span(sp);
+    let lo = cm.lookup_char_pos(sp.lo);
+    let hi = cm.lookup_char_pos(sp.hi);
+    JsonSpan {
+        file_name: copy lo.file.name,
+        byte_start: (sp.lo - lo.file.start_pos).to_uint(),
+        byte_end: (sp.hi - lo.file.start_pos).to_uint(),
+        line_start: lo.line,
+        line_end: hi.line,
+        column_start: lo.col.to_uint(),
+        column_end: hi.col.to_uint(),
+        is_primary: is_primary,
+        label: label
+    }
+}
+
+/// The spans of a diagnostic reported at `sp`: the primary span first, then
+/// the labelled secondary spans, then one secondary span for the site of
+/// each macro expansion `sp` went through.
+fn json_spans(cm: @codemap::CodeMap, sp: span,
+              labels: &[SpanLabel]) -> ~[JsonSpan] {
+    let mut primary_label = None;
+    let mut secondary = ~[];
+    for labels.each |l| {
+        if l.span == sp {
+            primary_label = Some(copy l.label);
+        } else {
+            secondary.push(json_span(cm, l.span, false,
+                                     Some(copy l.label)));
+        }
+    }
+    let mut spans = ~[json_span(cm, sp, true, primary_label)];
+    spans.push_all_move(secondary);
+
+    let mut expn_info = sp.expn_info;
+    loop {
+        match expn_info {
+          Some(@codemap::ExpandedFrom(ref ei)) => {
+            spans.push(json_span(cm, ei.call_site, false,
+                                 Some(fmt!("in expansion of %s!",
+                                           ei.callie.name))));
+            expn_info = ei.call_site.expn_info;
+          }
+          None => break
+        }
+    }
+    spans
+}
+
+fn json_diagnostic(cm: Option<@codemap::CodeMap>,
+                   d: &Diagnostic) -> JsonDiagnostic {
+    let spans = match (cm, d.span) {
+      (Some(cm), Some(sp)) => json_spans(cm, sp, d.labels),
+      _ => ~[]
+    };
+    let notes = do d.notes.map |n| {
+        let mut sub = Diagnostic::new(note, n.message);
+        sub.span = n.span;
+        json_diagnostic(cm, &sub)
+    };
+    JsonDiagnostic {
+        level: diagnosticstr(d.level),
+        message: copy d.message,
+        lint: copy d.lint,
+        spans: spans,
+        notes: notes
+    }
+}
+
+/// Writes a diagnostic to stderr as a single line of JSON
+pub fn json_emit(cm: Option<@codemap::CodeMap>, d: &Diagnostic) {
+    let diagnostic = json_diagnostic(cm, d);
+    let s = do io::with_str_writer |wr| {
+        diagnostic.encode(&json::Encoder(wr));
+    };
+    io::stderr().write_str(s + ~"\n");
+}
+
 pub fn expect<T: Copy>(diag: span_handler,
                        opt: Option<T>,
                        msg: fn() -> ~str) -> T {
@@ -296,3 +587,59 @@ pub fn expect<T: Copy>(diag: span_handler,
        None => diag.handler().bug(msg())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use core::prelude::*;
+
+    use codemap::{BytePos, CodeMap, span};
+    use diagnostic::{SpanLabel, highlight_spans};
+
+    use core::io;
+    use core::str;
+
+    fn sp(lo: uint, hi: uint) -> span {
+        span {lo: BytePos(lo), hi: BytePos(hi), expn_info: None}
+    }
+
+    fn label(lo: uint, hi: uint, label: &str) -> SpanLabel {
+        SpanLabel { span: sp(lo, hi), label: str::from_slice(label) }
+    }
+
+    #[test]
+    fn test_two_labels_on_one_line() {
+        let cm = @CodeMap::new();
+        let fm = cm.new_filemap(~"test.rs", @~"foo(&mut x, &x);\n");
+        fm.next_line(BytePos(0));
+        let out = do io::with_str_writer |w| {
+            highlight_spans(w, cm, sp(12, 14),
+                            [label(4, 10, "first borrow"),
+                             label(12, 14, "second borrow")]);
+        };
+        assert str::lines(out) == ~[
+            ~"test.rs:1 foo(&mut x, &x);",
+            ~"              ------ first borrow",
+            ~"                      ^~ second borrow",
+            ~""];
+    }
+
+    #[test]
+    fn test_labels_on_different_lines() {
+        let cm = @CodeMap::new();
+        let fm = cm.new_filemap(~"test.rs",
+                                @~"let p = &mut v;\nlet q = &mut v;\n");
+        fm.next_line(BytePos(0));
+        fm.next_line(BytePos(16));
+        let out = do io::with_str_writer |w| {
+            highlight_spans(w, cm, sp(24, 30),
+                            [label(24, 30, "second borrow"),
+                             label(8, 14, "first borrow")]);
+        };
+        assert str::lines(out) == ~[
+            ~"test.rs:1 let p = &mut v;",
+            ~"                  ------ first borrow",
+            ~"test.rs:2 let q = &mut v;",
+            ~"                  ^~~~~~ second borrow",
+            ~""];
+    }
+}
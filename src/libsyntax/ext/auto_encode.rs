@@ -76,6 +76,27 @@ would yield functions like:
         }
     }
 
+Fields of a struct can be tagged to change how they're encoded:
+
+    #[auto_encode]
+    #[auto_decode]
+    struct Config {
+        #[serial_name = "user-name"]
+        name: ~str,
+        #[serial_default]
+        retries: uint,
+        #[serial_default = "~[~\"localhost\"]"]
+        hosts: ~[~str],
+        #[serial_skip]
+        cache: ~[~str],
+    }
+
+`serial_name` gives the name the field is encoded and decoded under.
+`serial_default` makes decoding use a default if the field is missing,
+through `read_field_or`: the default is the given expression, or else
+`std::serialize::DecodeDefault::decode_default()`. `serial_skip` leaves the
+field out of the encoding, and decoding sets it to its default.
+
 FIXME (#2810)--Hygiene. Search for "__" strings.  We also assume "std" is the
 standard library.
 
@@ -669,7 +690,12 @@ fn mk_struct_ser_impl(
     fields: ~[@ast::struct_field],
     tps: ~[ast::ty_param]
 ) -> @ast::item {
-    let fields = do mk_struct_fields(fields).mapi |idx, field| {
+    let mut stmts = ~[];
+    for mk_struct_fields(cx, fields).each |field| {
+        // #[serial_skip] fields aren't written at all
+        if field.skip { loop; }
+        let idx = stmts.len();
+
         // ast for `|| self.$(name).encode(__s)`
         let expr_lambda = cx.lambda_expr(
             cx.expr_call(
@@ -688,7 +714,7 @@ fn mk_struct_ser_impl(
         );
 
         // ast for `__s.emit_field($(name), $(idx), $(expr_lambda))`
-        cx.stmt(
+        stmts.push(cx.stmt(
             cx.expr_call(
                 span,
                 cx.expr_field(
@@ -697,13 +723,13 @@ fn mk_struct_ser_impl(
                     cx.ident_of(~"emit_field")
                 ),
                 ~[
-                    cx.lit_str(span, @cx.str_of(field.ident)),
+                    cx.lit_str(span, @copy field.name),
                     cx.lit_uint(span, idx),
                     expr_lambda,
                 ]
             )
-        )
-    };
+        ));
+    }
 
     // ast for `__s.emit_struct($(name), || $(fields))`
     let ser_body = cx.expr_call(
@@ -715,8 +741,8 @@ fn mk_struct_ser_impl(
         ),
         ~[
             cx.lit_str(span, @cx.str_of(ident)),
-            cx.lit_uint(span, vec::len(fields)),
-            cx.lambda_stmts(span, fields),
+            cx.lit_uint(span, vec::len(stmts)),
+            cx.lambda_stmts(span, stmts),
         ]
     );
 
@@ -730,37 +756,55 @@ fn mk_struct_deser_impl(
     fields: ~[@ast::struct_field],
     tps: ~[ast::ty_param]
 ) -> @ast::item {
-    let fields = do mk_struct_fields(fields).mapi |idx, field| {
-        // ast for `|| std::serialize::decode(__d)`
-        let expr_lambda = cx.lambda(
-            cx.expr_blk(
-                cx.expr_call(
-                    span,
-                    cx.expr_path_global(span, ~[
-                        cx.ident_of(~"std"),
-                        cx.ident_of(~"serialize"),
-                        cx.ident_of(~"Decodable"),
-                        cx.ident_of(~"decode"),
-                    ]),
-                    ~[cx.expr_var(span, ~"__d")]
+    let mut idx = 0u;
+    let fields = do mk_struct_fields(cx, fields).map |field| {
+        let expr = if field.skip {
+            // Skipped fields aren't read at all
+            field.default.get()
+        } else {
+            // ast for `|| std::serialize::decode(__d)`
+            let expr_lambda = cx.lambda(
+                cx.expr_blk(
+                    cx.expr_call(
+                        span,
+                        cx.expr_path_global(span, ~[
+                            cx.ident_of(~"std"),
+                            cx.ident_of(~"serialize"),
+                            cx.ident_of(~"Decodable"),
+                            cx.ident_of(~"decode"),
+                        ]),
+                        ~[cx.expr_var(span, ~"__d")]
+                    )
                 )
-            )
-        );
+            );
 
-        // ast for `__d.read_field($(name), $(idx), $(expr_lambda))`
-        let expr: @ast::expr = cx.expr_call(
-            span,
-            cx.expr_field(
-                span,
-                cx.expr_var(span, ~"__d"),
-                cx.ident_of(~"read_field")
-            ),
-            ~[
-                cx.lit_str(span, @cx.str_of(field.ident)),
+            // ast for `__d.read_field($(name), $(idx), $(expr_lambda))`,
+            // or for `__d.read_field_or($(name), $(idx), $(expr_lambda),
+            // || $(default))` if the field has a default
+            let mut args = ~[
+                cx.lit_str(span, @copy field.name),
                 cx.lit_uint(span, idx),
                 expr_lambda,
-            ]
-        );
+            ];
+            let read = match field.default {
+                Some(default) => {
+                    args.push(cx.lambda_expr(default));
+                    ~"read_field_or"
+                }
+                None => ~"read_field"
+            };
+            idx += 1;
+
+            cx.expr_call(
+                span,
+                cx.expr_field(
+                    span,
+                    cx.expr_var(span, ~"__d"),
+                    cx.ident_of(read)
+                ),
+                args
+            )
+        };
 
         codemap::spanned {
             node: ast::field_ {
@@ -782,7 +826,7 @@ fn mk_struct_deser_impl(
         ),
         ~[
             cx.lit_str(span, @cx.str_of(ident)),
-            cx.lit_uint(span, vec::len(fields)),
+            cx.lit_uint(span, idx),
             cx.lambda_expr(
                 cx.expr(
                     span,
@@ -806,15 +850,59 @@ struct field {
     span: span,
     ident: ast::ident,
     mutbl: ast::mutability,
+    // The name the field is encoded under, from #[serial_name = "..."]
+    name: ~str,
+    // Whether #[serial_skip] leaves the field out of the encoding
+    skip: bool,
+    // The value of a skipped field, or of a #[serial_default] field that's
+    // missing from the input
+    default: Option<@ast::expr>,
 }
 
-fn mk_struct_fields(fields: ~[@ast::struct_field]) -> ~[field] {
+fn mk_struct_fields(cx: ext_ctxt, fields: ~[@ast::struct_field])
+                 -> ~[field] {
     do fields.map |field| {
         let (ident, mutbl) = match field.node.kind {
             ast::named_field(ident, mutbl, _) => (ident, mutbl),
             _ => die!(~"[auto_encode] does not support \
                         unnamed fields")
         };
+        let attrs = copy field.node.attrs;
+
+        let names = attr::find_attrs_by_name(attrs, "serial_name");
+        let name = if names.is_empty() {
+            cx.str_of(ident)
+        } else {
+            match attr::get_meta_item_value_str(attr::attr_meta(names[0])) {
+              Some(name) => name,
+              None => cx.span_fatal(names[0].span,
+                                    ~"#[serial_name] takes a string, as in \
+                                      #[serial_name = \"name\"]")
+            }
+        };
+
+        let skip = attr::attrs_contains_name(attrs, "serial_skip");
+        let defaults = attr::find_attrs_by_name(attrs, "serial_default");
+        let default = if !defaults.is_empty() {
+            let meta = attr::attr_meta(defaults[0]);
+            match attr::get_meta_item_value_str(meta) {
+              // #[serial_default = "expr"]
+              Some(src) => {
+                let p = parse::new_parser_from_source_str(
+                    cx.parse_sess(), cx.cfg(), ~"<serial_default>",
+                    codemap::FssInternal(defaults[0].span), @src);
+                let e = p.parse_expr();
+                p.abort_if_errors();
+                Some(e)
+              }
+              // #[serial_default]
+              None => Some(default_value(cx, field.span))
+            }
+        } else if skip {
+            Some(default_value(cx, field.span))
+        } else {
+            None
+        };
 
         field {
             span: field.span,
@@ -823,10 +911,27 @@ fn mk_struct_fields(fields: ~[@ast::struct_field]) -> ~[field] {
                 ast::struct_mutable => ast::m_mutbl,
                 ast::struct_immutable => ast::m_imm,
             },
+            name: name,
+            skip: skip,
+            default: default,
         }
     }
 }
 
+// ast for `std::serialize::DecodeDefault::decode_default()`
+fn default_value(cx: ext_ctxt, span: span) -> @ast::expr {
+    cx.expr_call(
+        span,
+        cx.expr_path_global(span, ~[
+            cx.ident_of(~"std"),
+            cx.ident_of(~"serialize"),
+            cx.ident_of(~"DecodeDefault"),
+            cx.ident_of(~"decode_default"),
+        ]),
+        ~[]
+    )
+}
+
 fn mk_enum_ser_impl(
     cx: ext_ctxt,
     span: span,
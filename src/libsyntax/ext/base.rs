@@ -62,6 +62,11 @@ pub enum MacResult {
     MRDef(MacroDef)
 }
 
+// Called by the expander with each `extern mod` it meets; returns the
+// macros the named crate exports and, for a #[syntax_extension] crate,
+// the extensions it registers.
+pub type MacroCrateLoader = fn@(ext_ctxt, @ast::view_item) -> ~[MacroDef];
+
 pub enum SyntaxExtension {
 
     // #[auto_encode] and such
@@ -75,21 +80,33 @@ pub enum SyntaxExtension {
     ItemTT(SyntaxExpanderTTItem),
 }
 
+pub fn builtin_normal_tt(f: SyntaxExpanderTTFun) -> SyntaxExtension {
+    NormalTT(SyntaxExpanderTT{expander: f, span: None})
+}
+
+pub fn builtin_item_tt(f: SyntaxExpanderTTItemFun) -> SyntaxExtension {
+    ItemTT(SyntaxExpanderTTItem{expander: f, span: None})
+}
+
+// A #[syntax_extension] crate defines a `register_syntax_extensions`
+// function of this type. When the crate is loaded it is called once, and
+// should pass each extension it provides, and its name, to `register`.
+pub type SyntaxExtensionRegistrar =
+    fn(register: fn@(~str, SyntaxExtension));
+
 // A temporary hard-coded map of methods for expanding syntax extension
-// AST nodes into full ASTs
+// AST nodes into full ASTs; crates loaded with `extern mod` may add to it
 pub fn syntax_expander_table() -> HashMap<~str, SyntaxExtension> {
-    fn builtin_normal_tt(f: SyntaxExpanderTTFun) -> SyntaxExtension {
-        NormalTT(SyntaxExpanderTT{expander: f, span: None})
-    }
-    fn builtin_item_tt(f: SyntaxExpanderTTItemFun) -> SyntaxExtension {
-        ItemTT(SyntaxExpanderTTItem{expander: f, span: None})
-    }
     let syntax_expanders = HashMap();
     syntax_expanders.insert(~"macro_rules",
                             builtin_item_tt(
                                 ext::tt::macro_rules::add_new_extension));
     syntax_expanders.insert(~"fmt",
                             builtin_normal_tt(ext::fmt::expand_syntax_ext));
+    syntax_expanders.insert(~"format",
+                            builtin_normal_tt(ext::format::expand_format));
+    syntax_expanders.insert(~"write",
+                            builtin_normal_tt(ext::format::expand_write));
     syntax_expanders.insert(
         ~"auto_encode",
         ItemDecorator(ext::auto_encode::expand_auto_encode));
@@ -98,6 +115,9 @@ pub fn syntax_expander_table() -> HashMap<~str, SyntaxExtension> {
         ItemDecorator(ext::auto_encode::expand_auto_decode));
     syntax_expanders.insert(~"env",
                             builtin_normal_tt(ext::env::expand_syntax_ext));
+    syntax_expanders.insert(~"concat",
+                            builtin_normal_tt(
+                                ext::concat::expand_syntax_ext));
     syntax_expanders.insert(~"concat_idents",
                             builtin_normal_tt(
                                 ext::concat_idents::expand_syntax_ext));
@@ -110,6 +130,15 @@ pub fn syntax_expander_table() -> HashMap<~str, SyntaxExtension> {
     syntax_expanders.insert(~"deriving_iter_bytes",
                             ItemDecorator(
                                 ext::deriving::expand_deriving_iter_bytes));
+    syntax_expanders.insert(~"deriving_ord",
+                            ItemDecorator(
+                                ext::deriving::expand_deriving_ord));
+    syntax_expanders.insert(~"deriving_clone",
+                            ItemDecorator(
+                                ext::deriving::expand_deriving_clone));
+    syntax_expanders.insert(~"deriving_to_str",
+                            ItemDecorator(
+                                ext::deriving::expand_deriving_to_str));
 
     // Quasi-quoting expanders
     syntax_expanders.insert(
@@ -181,6 +210,13 @@ pub trait ext_ctxt {
     fn next_id(@mut self) -> ast::node_id;
     pure fn trace_macros(@mut self) -> bool;
     fn set_trace_macros(@mut self, x: bool);
+    pure fn trace_expansion(@mut self) -> bool;
+    fn set_trace_expansion(@mut self, x: bool);
+    fn export_macro(@mut self, it: @ast::item);
+    fn exported_macros(@mut self) -> ~[@ast::item];
+    /* the directory of the crate's root source file */
+    fn crate_dir(@mut self) -> Path;
+    fn set_crate_dir(@mut self, dir: Path);
     /* for unhygienic identifier transformation */
     fn str_of(@mut self, id: ast::ident) -> ~str;
     fn ident_of(@mut self, st: ~str) -> ast::ident;
@@ -193,7 +229,10 @@ pub fn mk_ctxt(parse_sess: parse::parse_sess,
         cfg: ast::crate_cfg,
         backtrace: Option<@ExpnInfo>,
         mod_path: ~[ast::ident],
-        trace_mac: bool
+        trace_mac: bool,
+        trace_expn: bool,
+        exported_macros: ~[@ast::item],
+        crate_dir: Path
     }
     impl CtxtRepr: ext_ctxt {
         fn codemap(@mut self) -> @CodeMap { self.parse_sess.cm }
@@ -264,6 +303,20 @@ pub fn mk_ctxt(parse_sess: parse::parse_sess,
         fn set_trace_macros(@mut self, x: bool) {
             self.trace_mac = x
         }
+        pure fn trace_expansion(@mut self) -> bool {
+            self.trace_expn
+        }
+        fn set_trace_expansion(@mut self, x: bool) {
+            self.trace_expn = x
+        }
+        fn export_macro(@mut self, it: @ast::item) {
+            self.exported_macros.push(it);
+        }
+        fn exported_macros(@mut self) -> ~[@ast::item] {
+            copy self.exported_macros
+        }
+        fn crate_dir(@mut self) -> Path { copy self.crate_dir }
+        fn set_crate_dir(@mut self, dir: Path) { self.crate_dir = dir; }
 
         fn str_of(@mut self, id: ast::ident) -> ~str {
             *self.parse_sess.interner.get(id)
@@ -277,7 +330,10 @@ pub fn mk_ctxt(parse_sess: parse::parse_sess,
         cfg: cfg,
         backtrace: None,
         mod_path: ~[],
-        trace_mac: false
+        trace_mac: false,
+        trace_expn: false,
+        exported_macros: ~[],
+        crate_dir: Path(".")
     };
     move ((move imp) as @ext_ctxt)
 }
@@ -0,0 +1,74 @@
+// Copyright 2013 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+/*
+ * The compiler code necessary to support the concat! extension, which
+ * joins literals into a single string literal: `concat!("x = ", 1, 'c')`
+ * expands to `"x = 1c"`.
+ */
+
+use core::prelude::*;
+
+use ast;
+use ext::base::*;
+use ext::base;
+use ext::build::mk_base_str;
+
+use core::i64;
+use core::str;
+use core::u64;
+
+pub fn expand_syntax_ext(cx: ext_ctxt, sp: span, tts: ~[ast::token_tree])
+    -> base::MacResult {
+    let es = get_exprs_from_tts(cx, tts);
+    let mut res = ~"";
+    for es.each |e| {
+        res += expr_to_lit_str(cx, *e);
+    }
+    base::MRExpr(mk_base_str(cx, sp, res))
+}
+
+fn expr_to_lit_str(cx: ext_ctxt, e: @ast::expr) -> ~str {
+    match e.node {
+      ast::expr_lit(l) => lit_to_str(cx, l),
+      // Negative numbers are parsed as negations of literals
+      ast::expr_unary(ast::neg, @ast::expr { node: ast::expr_lit(l), _ }) => {
+        match l.node {
+          ast::lit_int(_, t) if t != ast::ty_char => ~"-" + lit_to_str(cx, l),
+          ast::lit_int_unsuffixed(_) | ast::lit_float(*) |
+          ast::lit_float_unsuffixed(_) => ~"-" + lit_to_str(cx, l),
+          _ => cx.span_fatal(e.span, ~"concat! expects literals")
+        }
+      }
+      _ => cx.span_fatal(e.span, ~"concat! expects literals")
+    }
+}
+
+fn lit_to_str(cx: ext_ctxt, l: @ast::lit) -> ~str {
+    match l.node {
+      ast::lit_str(s) => copy *s,
+      ast::lit_int(c, ast::ty_char) => str::from_char(c as char),
+      ast::lit_int(i, _) | ast::lit_int_unsuffixed(i) => i64::str(i),
+      ast::lit_uint(u, _) => u64::str(u),
+      ast::lit_float(s, _) | ast::lit_float_unsuffixed(s) => copy *s,
+      ast::lit_bool(b) => if b { ~"true" } else { ~"false" },
+      ast::lit_nil => cx.span_fatal(l.span, ~"concat! can't include ()")
+    }
+}
+
+//
+// Local Variables:
+// mode: rust
+// fill-column: 78;
+// indent-tabs-mode: nil
+// c-basic-offset: 4
+// buffer-file-coding-system: utf-8-unix
+// End:
+//
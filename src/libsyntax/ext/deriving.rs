@@ -8,19 +8,21 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-/// The compiler code necessary to implement the #[deriving_eq] and
-/// #[deriving_iter_bytes] extensions.
+/// The compiler code necessary to implement the #[deriving_eq],
+/// #[deriving_iter_bytes], #[deriving_ord], #[deriving_clone] and
+/// #[deriving_to_str] extensions.
 
 use core::prelude::*;
 
 use ast;
-use ast::{TraitTyParamBound, Ty, and, bind_by_ref, binop, deref, enum_def};
-use ast::{enum_variant_kind, expr, expr_match, ident, item, item_};
-use ast::{item_enum, item_impl, item_struct, m_imm, meta_item, method};
-use ast::{named_field, or, pat, pat_ident, pat_wild, public, pure_fn};
+use ast::{TraitTyParamBound, Ty, add, and, bind_by_ref, binop, deref};
+use ast::{enum_def, enum_variant_kind, expr, expr_match, gt, ident};
+use ast::{impure_fn, item, item_, item_enum, item_impl, item_struct, lt};
+use ast::{m_imm, meta_item, method, named_field, not, or, pat, pat_enum};
+use ast::{pat_ident, pat_struct, pat_wild, public, pure_fn, purity};
 use ast::{re_anon, stmt, struct_def, struct_variant_kind};
 use ast::{sty_by_ref, sty_region, tuple_variant_kind, ty_nil, ty_param};
-use ast::{ty_param_bound, ty_path, ty_rptr, unnamed_field, variant};
+use ast::{ty_param_bound, ty_path, ty_rptr, ty_uniq, unnamed_field, variant};
 use ext::base::ext_ctxt;
 use ext::build;
 use codemap::{span, spanned};
@@ -28,6 +30,7 @@ use parse::token::special_idents::clownshoes_extensions;
 
 use core::dvec;
 use core::uint;
+use core::vec;
 
 enum Junction {
     Conjunction,
@@ -80,6 +83,42 @@ pub fn expand_deriving_iter_bytes(cx: ext_ctxt,
                     expand_deriving_iter_bytes_enum_def)
 }
 
+pub fn expand_deriving_ord(cx: ext_ctxt,
+                           span: span,
+                           _mitem: meta_item,
+                           in_items: ~[@item])
+                        -> ~[@item] {
+    expand_deriving(cx,
+                    span,
+                    in_items,
+                    expand_deriving_ord_struct_def,
+                    expand_deriving_ord_enum_def)
+}
+
+pub fn expand_deriving_clone(cx: ext_ctxt,
+                             span: span,
+                             _mitem: meta_item,
+                             in_items: ~[@item])
+                          -> ~[@item] {
+    expand_deriving(cx,
+                    span,
+                    in_items,
+                    expand_deriving_clone_struct_def,
+                    expand_deriving_clone_enum_def)
+}
+
+pub fn expand_deriving_to_str(cx: ext_ctxt,
+                              span: span,
+                              _mitem: meta_item,
+                              in_items: ~[@item])
+                           -> ~[@item] {
+    expand_deriving(cx,
+                    span,
+                    in_items,
+                    expand_deriving_to_str_struct_def,
+                    expand_deriving_to_str_enum_def)
+}
+
 fn expand_deriving(cx: ext_ctxt,
                    span: span,
                    in_items: ~[@item],
@@ -122,7 +161,8 @@ fn create_impl_item(cx: ext_ctxt, span: span, +item: item_) -> @item {
 }
 
 /// Creates a method from the given expression, the signature of which
-/// conforms to the `eq` or `ne` method.
+/// conforms to the `eq` or `ne` method. The methods of `Ord` have the same
+/// signature.
 fn create_eq_method(cx: ext_ctxt,
                     span: span,
                     method_ident: ident,
@@ -285,6 +325,82 @@ fn create_derived_iter_bytes_impl(cx: ext_ctxt,
     create_derived_impl(cx, span, type_ident, ty_params, methods, trait_path)
 }
 
+fn create_derived_ord_impl(cx: ext_ctxt,
+                           span: span,
+                           type_ident: ident,
+                           +ty_params: ~[ty_param],
+                           methods: &[@method])
+                        -> @item {
+    let trait_path = [
+        cx.ident_of(~"core"),
+        cx.ident_of(~"cmp"),
+        cx.ident_of(~"Ord")
+    ];
+    create_derived_impl(cx, span, type_ident, ty_params, methods, trait_path)
+}
+
+fn create_derived_clone_impl(cx: ext_ctxt,
+                             span: span,
+                             type_ident: ident,
+                             +ty_params: ~[ty_param],
+                             method: @method)
+                          -> @item {
+    let methods = [ method ];
+    let trait_path = [
+        cx.ident_of(~"core"),
+        cx.ident_of(~"clone"),
+        cx.ident_of(~"Clone")
+    ];
+    create_derived_impl(cx, span, type_ident, ty_params, methods, trait_path)
+}
+
+fn create_derived_to_str_impl(cx: ext_ctxt,
+                              span: span,
+                              type_ident: ident,
+                              +ty_params: ~[ty_param],
+                              method: @method)
+                           -> @item {
+    let methods = [ method ];
+    let trait_path = [
+        cx.ident_of(~"core"),
+        cx.ident_of(~"to_str"),
+        cx.ident_of(~"ToStr")
+    ];
+    create_derived_impl(cx, span, type_ident, ty_params, methods, trait_path)
+}
+
+// Creates a method that takes no arguments besides `&self`, such as `clone`
+// or `to_str`, from the given expression.
+fn create_self_only_method(cx: ext_ctxt,
+                           span: span,
+                           method_ident: ident,
+                           output_type: @Ty,
+                           purity: purity,
+                           body: @expr)
+                        -> @method {
+    // Create the function declaration.
+    let fn_decl = build::mk_fn_decl(~[], output_type);
+
+    // Create the body block.
+    let body_block = build::mk_simple_block(cx, span, body);
+
+    // Create the method.
+    let self_ty = spanned { node: sty_region(m_imm), span: span };
+    @ast::method {
+        ident: method_ident,
+        attrs: ~[],
+        tps: ~[],
+        self_ty: self_ty,
+        purity: purity,
+        decl: move fn_decl,
+        body: move body_block,
+        id: cx.next_id(),
+        span: span,
+        self_id: cx.next_id(),
+        vis: public
+    }
+}
+
 // Creates a method from the given set of statements conforming to the
 // signature of the `iter_bytes` method.
 fn create_iter_bytes_method(cx: ext_ctxt,
@@ -400,6 +516,49 @@ fn create_enum_variant_pattern(cx: ext_ctxt,
     }
 }
 
+// Creates a pattern that matches any value of the given variant without
+// binding its fields.
+fn create_enum_variant_wild_pattern(cx: ext_ctxt,
+                                    span: span,
+                                    variant: &variant)
+                                 -> @pat {
+    let variant_ident = variant.node.name;
+    let matching_path = build::mk_raw_path(span, ~[ variant_ident ]);
+    match variant.node.kind {
+        tuple_variant_kind(ref variant_args) => {
+            if variant_args.len() == 0 {
+                return build::mk_pat_ident_with_binding_mode(
+                    cx, span, variant_ident, ast::bind_infer);
+            }
+            build::mk_pat(cx, span, pat_enum(matching_path, None))
+        }
+        struct_variant_kind(*) => {
+            build::mk_pat(cx, span, pat_struct(matching_path, ~[], true))
+        }
+        enum_variant_kind(*) => {
+            cx.span_unimpl(span, ~"enum variants for `deriving`");
+        }
+    }
+}
+
+// Returns the names of the fields of a struct, or of a struct-like variant.
+fn struct_field_idents(cx: ext_ctxt,
+                       span: span,
+                       struct_def: &struct_def,
+                       extension_name: &str)
+                    -> ~[ident] {
+    do struct_def.fields.map |struct_field| {
+        match struct_field.node.kind {
+            named_field(ident, _, _) => ident,
+            unnamed_field => {
+                cx.span_unimpl(span,
+                               fmt!("unnamed fields with `%s`",
+                                    extension_name));
+            }
+        }
+    }
+}
+
 fn call_substructure_eq_method(cx: ext_ctxt,
                                span: span,
                                self_field: @expr,
@@ -470,6 +629,131 @@ fn call_substructure_iter_bytes_method(cx: ext_ctxt,
     build::mk_stmt(cx, span, self_call)
 }
 
+// Creates an expression comparing `field_count` pairs of fields
+// lexicographically with the strict comparison method `method_ident` (`lt` or
+// `gt`). `get_field(is_self, i)` creates a fresh expression for field `i` of
+// self or of the other value, along with a borrowed pointer to it. When every
+// pair is equal, the result is `equal_result`.
+fn create_lexical_ord_expr(cx: ext_ctxt,
+                           span: span,
+                           method_ident: ident,
+                           field_count: uint,
+                           equal_result: bool,
+                           get_field: fn(bool, uint) -> (@expr, @expr))
+                        -> @expr {
+    // Build the chain from the last field outwards.
+    let mut result = build::mk_bool(cx, span, equal_result);
+    for uint::range(0, field_count) |k| {
+        let i = field_count - k - 1;
+
+        // self.f < other.f
+        let (self_field, _) = get_field(true, i);
+        let (_, other_field_ref) = get_field(false, i);
+        let self_method = build::mk_access_(cx,
+                                            span,
+                                            self_field,
+                                            method_ident);
+        let self_call = build::mk_call_(cx,
+                                        span,
+                                        self_method,
+                                        ~[ other_field_ref ]);
+
+        // !(other.f < self.f), i.e. the fields are equal
+        let (other_field, _) = get_field(false, i);
+        let (_, self_field_ref) = get_field(true, i);
+        let other_method = build::mk_access_(cx,
+                                             span,
+                                             other_field,
+                                             method_ident);
+        let other_call = build::mk_call_(cx,
+                                         span,
+                                         other_method,
+                                         ~[ self_field_ref ]);
+        let not_other_call = build::mk_unary(cx, span, not, other_call);
+
+        // self.f < other.f || (self.f == other.f && <rest>)
+        let equal_and_rest = build::mk_binary(cx,
+                                              span,
+                                              and,
+                                              not_other_call,
+                                              result);
+        result = build::mk_binary(cx, span, or, self_call, equal_and_rest);
+    }
+    result
+}
+
+// Creates an expression for the position of the variant of `*value_ident`
+// in the enum. Variants compare in the order they are declared.
+fn create_variant_index_expr(cx: ext_ctxt,
+                             span: span,
+                             enum_definition: &enum_def,
+                             value_ident: ident)
+                          -> @expr {
+    let arms = do enum_definition.variants.mapi |i, variant| {
+        let pat = create_enum_variant_wild_pattern(cx, span, variant);
+        let index_expr = build::mk_uint(cx, span, i);
+        ast::arm {
+            pats: ~[ pat ],
+            guard: None,
+            body: build::mk_simple_block(cx, span, index_expr),
+        }
+    };
+    let value_expr = build::mk_path(cx, span, ~[ value_ident ]);
+    let value_expr = build::mk_unary(cx, span, deref, value_expr);
+    build::mk_expr(cx, span, expr_match(value_expr, arms))
+}
+
+// Joins the given `~str` expressions with `+`.
+fn create_str_concat_expr(cx: ext_ctxt,
+                          span: span,
+                          pieces: &[@expr])
+                       -> @expr {
+    let mut result = pieces[0];
+    for vec::tail(pieces).each |piece| {
+        result = build::mk_binary(cx, span, add, result, *piece);
+    }
+    result
+}
+
+// Creates the pieces of the string `name(a, b)` or `name { x: a, y: b }`
+// for `deriving_to_str`. `field_idents` is empty for tuple-like values.
+fn create_to_str_pieces(cx: ext_ctxt,
+                        span: span,
+                        name: ident,
+                        field_idents: &[ident],
+                        fields: &[@expr])
+                     -> ~[@expr] {
+    let name = cx.str_of(name);
+    if fields.is_empty() {
+        return ~[ build::mk_uniq_str(cx, span, name) ];
+    }
+
+    let to_str_ident = cx.ident_of(~"to_str");
+    let pieces = dvec::DVec();
+    let is_struct = !field_idents.is_empty();
+    for fields.eachi |i, field| {
+        // Create the text that comes before the field.
+        let separator = if i == 0 {
+            if is_struct { name + ~" { " } else { name + ~"(" }
+        } else {
+            ~", "
+        };
+        let label = if is_struct {
+            separator + cx.str_of(field_idents[i]) + ~": "
+        } else {
+            separator
+        };
+        pieces.push(build::mk_uniq_str(cx, span, label));
+
+        // Call the substructure method.
+        let field_method = build::mk_access_(cx, span, *field, to_str_ident);
+        pieces.push(build::mk_call_(cx, span, field_method, ~[]));
+    }
+    let close = if is_struct { ~" }" } else { ~")" };
+    pieces.push(build::mk_uniq_str(cx, span, close));
+    dvec::unwrap(move pieces)
+}
+
 fn variant_arg_count(cx: ext_ctxt, span: span, variant: &variant) -> uint {
     match variant.node.kind {
         tuple_variant_kind(args) => args.len(),
@@ -871,3 +1155,468 @@ fn expand_deriving_iter_bytes_enum_method(cx: ext_ctxt,
     create_iter_bytes_method(cx, span, ~[ self_match_stmt ])
 }
 
+// The strict comparison each `Ord` method is built from, and whether the
+// method holds for equal values.
+fn ord_method_parts(cx: ext_ctxt, method_name: ~str) -> (ident, bool) {
+    let strict_name = match method_name {
+        ~"lt" | ~"le" => ~"lt",
+        _ => ~"gt"
+    };
+    let equal_result = method_name == ~"le" || method_name == ~"ge";
+    (cx.ident_of(strict_name), equal_result)
+}
+
+fn expand_deriving_ord_struct_def(cx: ext_ctxt,
+                                  span: span,
+                                  struct_def: &struct_def,
+                                  type_ident: ident,
+                                  +ty_params: ~[ty_param])
+                               -> @item {
+    // Create the methods.
+    let methods = do ~[ ~"lt", ~"le", ~"ge", ~"gt" ].map |method_name| {
+        expand_deriving_ord_struct_method(cx,
+                                          span,
+                                          struct_def,
+                                          copy *method_name,
+                                          type_ident,
+                                          ty_params)
+    };
+
+    // Create the implementation.
+    return create_derived_ord_impl(cx,
+                                   span,
+                                   type_ident,
+                                   move ty_params,
+                                   methods);
+}
+
+fn expand_deriving_ord_enum_def(cx: ext_ctxt,
+                                span: span,
+                                enum_definition: &enum_def,
+                                type_ident: ident,
+                                +ty_params: ~[ty_param])
+                             -> @item {
+    // Create the methods.
+    let methods = do ~[ ~"lt", ~"le", ~"ge", ~"gt" ].map |method_name| {
+        expand_deriving_ord_enum_method(cx,
+                                        span,
+                                        enum_definition,
+                                        copy *method_name,
+                                        type_ident,
+                                        ty_params)
+    };
+
+    // Create the implementation.
+    return create_derived_ord_impl(cx,
+                                   span,
+                                   type_ident,
+                                   move ty_params,
+                                   methods);
+}
+
+fn expand_deriving_ord_struct_method(cx: ext_ctxt,
+                                     span: span,
+                                     struct_def: &struct_def,
+                                     method_name: ~str,
+                                     type_ident: ident,
+                                     ty_params: &[ty_param])
+                                  -> @method {
+    let self_ident = cx.ident_of(~"self");
+    let other_ident = cx.ident_of(~"__other");
+    let (strict_ident, equal_result) = ord_method_parts(cx,
+                                                        copy method_name);
+
+    // Create the body of the method.
+    let field_idents = struct_field_idents(cx,
+                                           span,
+                                           struct_def,
+                                           ~"deriving_ord");
+    let body = do create_lexical_ord_expr(cx,
+                                          span,
+                                          strict_ident,
+                                          field_idents.len(),
+                                          equal_result) |is_self, i| {
+        let base_ident = if is_self { self_ident } else { other_ident };
+        let field = build::mk_access(cx,
+                                     span,
+                                     ~[ base_ident ],
+                                     field_idents[i]);
+        let field_ref = build::mk_access(cx,
+                                         span,
+                                         ~[ base_ident ],
+                                         field_idents[i]);
+        (field, build::mk_addr_of(cx, span, field_ref))
+    };
+
+    // Create the method itself.
+    return create_eq_method(cx,
+                            span,
+                            cx.ident_of(method_name),
+                            type_ident,
+                            ty_params,
+                            body);
+}
+
+fn expand_deriving_ord_enum_method(cx: ext_ctxt,
+                                   span: span,
+                                   enum_definition: &enum_def,
+                                   method_name: ~str,
+                                   type_ident: ident,
+                                   ty_params: &[ty_param])
+                                -> @method {
+    let self_ident = cx.ident_of(~"self");
+    let other_ident = cx.ident_of(~"__other");
+    let (strict_ident, equal_result) = ord_method_parts(cx,
+                                                        copy method_name);
+
+    // Create the arms of the self match in the method body.
+    let self_arms = do enum_definition.variants.mapi |i, self_variant| {
+        let other_arms = dvec::DVec();
+
+        // Create the matching pattern.
+        let matching_pat = create_enum_variant_pattern(cx,
+                                                       span,
+                                                       self_variant,
+                                                       ~"__other");
+
+        // Create the matching pattern body, which compares the fields.
+        // The bindings are already borrowed pointers.
+        let arg_count = variant_arg_count(cx, span, self_variant);
+        let matching_body_expr = do create_lexical_ord_expr(
+                cx, span, strict_ident, arg_count, equal_result)
+                |is_self, j| {
+            let prefix = if is_self { ~"__self" } else { ~"__other" };
+            let field_ident = cx.ident_of(prefix + j.to_str());
+            (build::mk_path(cx, span, ~[ field_ident ]),
+             build::mk_path(cx, span, ~[ field_ident ]))
+        };
+        let matching_body_block = build::mk_simple_block(cx,
+                                                         span,
+                                                         matching_body_expr);
+
+        // Create the matching arm.
+        let matching_arm = ast::arm {
+            pats: ~[ matching_pat ],
+            guard: None,
+            body: move matching_body_block
+        };
+        other_arms.push(move matching_arm);
+
+        // Otherwise compare the positions of the variants. If there is only
+        // one variant then there will always be a match.
+        if enum_definition.variants.len() > 1 {
+            // Create the nonmatching pattern.
+            let nonmatching_pat = @ast::pat {
+                id: cx.next_id(),
+                node: pat_wild,
+                span: span
+            };
+
+            // Create the nonmatching pattern body.
+            let binop = if cx.str_of(strict_ident) == ~"lt" {
+                lt
+            } else {
+                gt
+            };
+            let self_index_expr = build::mk_uint(cx, span, i);
+            let other_index_expr = create_variant_index_expr(cx,
+                                                             span,
+                                                             enum_definition,
+                                                             other_ident);
+            let nonmatching_expr = build::mk_binary(cx,
+                                                    span,
+                                                    binop,
+                                                    self_index_expr,
+                                                    other_index_expr);
+            let nonmatching_body_block =
+                build::mk_simple_block(cx,
+                                       span,
+                                       nonmatching_expr);
+
+            // Create the nonmatching arm.
+            let nonmatching_arm = ast::arm {
+                pats: ~[ nonmatching_pat ],
+                guard: None,
+                body: nonmatching_body_block,
+            };
+            other_arms.push(nonmatching_arm);
+        }
+
+        // Create the self pattern.
+        let self_pat = create_enum_variant_pattern(cx,
+                                                   span,
+                                                   self_variant,
+                                                   ~"__self");
+
+        // Create the self pattern body.
+        let other_expr = build::mk_path(cx, span, ~[ other_ident ]);
+        let other_expr = build::mk_unary(cx, span, deref, other_expr);
+        let other_arms = dvec::unwrap(move other_arms);
+        let other_match_expr = expr_match(other_expr, move other_arms);
+        let other_match_expr = build::mk_expr(cx,
+                                              span,
+                                              move other_match_expr);
+        let other_match_body_block = build::mk_simple_block(cx,
+                                                            span,
+                                                            other_match_expr);
+
+        // Create the self arm.
+        ast::arm {
+            pats: ~[ self_pat ],
+            guard: None,
+            body: other_match_body_block,
+        }
+    };
+
+    // Create the method body.
+    let self_expr = build::mk_path(cx, span, ~[ self_ident ]);
+    let self_expr = build::mk_unary(cx, span, deref, self_expr);
+    let self_match_expr = expr_match(self_expr, self_arms);
+    let self_match_expr = build::mk_expr(cx, span, move self_match_expr);
+
+    // Create the method.
+    return create_eq_method(cx,
+                            span,
+                            cx.ident_of(method_name),
+                            type_ident,
+                            ty_params,
+                            self_match_expr);
+}
+
+fn expand_deriving_clone_struct_def(cx: ext_ctxt,
+                                    span: span,
+                                    struct_def: &struct_def,
+                                    type_ident: ident,
+                                    +ty_params: ~[ty_param])
+                                 -> @item {
+    let self_ident = cx.ident_of(~"self");
+    let clone_ident = cx.ident_of(~"clone");
+
+    // Clone each field.
+    let field_idents = struct_field_idents(cx,
+                                           span,
+                                           struct_def,
+                                           ~"deriving_clone");
+    let fields = do field_idents.map |ident| {
+        let self_field = build::mk_access(cx, span, ~[ self_ident ], *ident);
+        let self_method = build::mk_access_(cx,
+                                            span,
+                                            self_field,
+                                            clone_ident);
+        let self_call = build::mk_call_(cx, span, self_method, ~[]);
+        { ident: *ident, ex: self_call }
+    };
+
+    // Create the method.
+    let body = build::mk_struct_e(cx, span, ~[ type_ident ], fields);
+    let output_type = create_self_type_with_params(cx,
+                                                   span,
+                                                   type_ident,
+                                                   ty_params);
+    let method = create_self_only_method(cx,
+                                         span,
+                                         clone_ident,
+                                         output_type,
+                                         impure_fn,
+                                         body);
+
+    // Create the implementation.
+    return create_derived_clone_impl(cx,
+                                     span,
+                                     type_ident,
+                                     move ty_params,
+                                     method);
+}
+
+fn expand_deriving_clone_enum_def(cx: ext_ctxt,
+                                  span: span,
+                                  enum_definition: &enum_def,
+                                  type_ident: ident,
+                                  +ty_params: ~[ty_param])
+                               -> @item {
+    let clone_ident = cx.ident_of(~"clone");
+
+    // Create the arms of the match in the method body.
+    let arms = do enum_definition.variants.map |variant| {
+        // Create the matching pattern.
+        let pat = create_enum_variant_pattern(cx, span, variant, ~"__self");
+
+        // Clone each field.
+        let arg_count = variant_arg_count(cx, span, variant);
+        let fields = do vec::from_fn(arg_count) |i| {
+            let field_ident = cx.ident_of(~"__self" + i.to_str());
+            let field = build::mk_path(cx, span, ~[ field_ident ]);
+            let field_method = build::mk_access_(cx,
+                                                 span,
+                                                 field,
+                                                 clone_ident);
+            build::mk_call_(cx, span, field_method, ~[])
+        };
+
+        // Rebuild the variant from the clones.
+        let variant_ident = variant.node.name;
+        let clone_expr = match variant.node.kind {
+            tuple_variant_kind(*) if fields.is_empty() => {
+                build::mk_path(cx, span, ~[ variant_ident ])
+            }
+            tuple_variant_kind(*) => {
+                build::mk_call(cx, span, ~[ variant_ident ], fields)
+            }
+            struct_variant_kind(struct_def) => {
+                let field_idents = struct_field_idents(cx,
+                                                       span,
+                                                       struct_def,
+                                                       ~"deriving_clone");
+                let fields = do vec::map2(field_idents, fields) |ident, ex| {
+                    { ident: *ident, ex: *ex }
+                };
+                build::mk_struct_e(cx, span, ~[ variant_ident ], fields)
+            }
+            enum_variant_kind(*) => {
+                cx.span_unimpl(span, ~"enum variants for `deriving`");
+            }
+        };
+
+        // Create the arm.
+        ast::arm {
+            pats: ~[ pat ],
+            guard: None,
+            body: build::mk_simple_block(cx, span, clone_expr),
+        }
+    };
+
+    // Create the method body.
+    let self_ident = cx.ident_of(~"self");
+    let self_expr = build::mk_path(cx, span, ~[ self_ident ]);
+    let self_expr = build::mk_unary(cx, span, deref, self_expr);
+    let self_match_expr = expr_match(self_expr, arms);
+    let self_match_expr = build::mk_expr(cx, span, self_match_expr);
+
+    // Create the method.
+    let output_type = create_self_type_with_params(cx,
+                                                   span,
+                                                   type_ident,
+                                                   ty_params);
+    let method = create_self_only_method(cx,
+                                         span,
+                                         clone_ident,
+                                         output_type,
+                                         impure_fn,
+                                         self_match_expr);
+
+    // Create the implementation.
+    return create_derived_clone_impl(cx,
+                                     span,
+                                     type_ident,
+                                     move ty_params,
+                                     method);
+}
+
+fn create_to_str_method(cx: ext_ctxt, span: span, body: @expr) -> @method {
+    // Create the `~str` return type.
+    let str_ident = cx.ident_of(~"str");
+    let str_ty = build::mk_simple_ty_path(cx, span, str_ident);
+    let str_ty = @ast::Ty {
+        id: cx.next_id(),
+        node: ty_uniq(ast::mt { ty: str_ty, mutbl: m_imm }),
+        span: span
+    };
+    create_self_only_method(cx,
+                            span,
+                            cx.ident_of(~"to_str"),
+                            str_ty,
+                            pure_fn,
+                            body)
+}
+
+fn expand_deriving_to_str_struct_def(cx: ext_ctxt,
+                                     span: span,
+                                     struct_def: &struct_def,
+                                     type_ident: ident,
+                                     +ty_params: ~[ty_param])
+                                  -> @item {
+    let self_ident = cx.ident_of(~"self");
+
+    // Create the accessors for each field.
+    let field_idents = struct_field_idents(cx,
+                                           span,
+                                           struct_def,
+                                           ~"deriving_to_str");
+    let fields = do field_idents.map |ident| {
+        build::mk_access(cx, span, ~[ self_ident ], *ident)
+    };
+
+    // Create the method.
+    let pieces = create_to_str_pieces(cx,
+                                      span,
+                                      type_ident,
+                                      field_idents,
+                                      fields);
+    let body = create_str_concat_expr(cx, span, pieces);
+    let method = create_to_str_method(cx, span, body);
+
+    // Create the implementation.
+    return create_derived_to_str_impl(cx,
+                                      span,
+                                      type_ident,
+                                      move ty_params,
+                                      method);
+}
+
+fn expand_deriving_to_str_enum_def(cx: ext_ctxt,
+                                   span: span,
+                                   enum_definition: &enum_def,
+                                   type_ident: ident,
+                                   +ty_params: ~[ty_param])
+                                -> @item {
+    // Create the arms of the match in the method body.
+    let arms = do enum_definition.variants.map |variant| {
+        // Create the matching pattern.
+        let pat = create_enum_variant_pattern(cx, span, variant, ~"__self");
+
+        // Create the expressions for the fields.
+        let arg_count = variant_arg_count(cx, span, variant);
+        let fields = do vec::from_fn(arg_count) |i| {
+            let field_ident = cx.ident_of(~"__self" + i.to_str());
+            build::mk_path(cx, span, ~[ field_ident ])
+        };
+        let field_idents = match variant.node.kind {
+            struct_variant_kind(struct_def) => {
+                struct_field_idents(cx, span, struct_def, ~"deriving_to_str")
+            }
+            _ => ~[]
+        };
+
+        // Create the pattern body.
+        let pieces = create_to_str_pieces(cx,
+                                          span,
+                                          variant.node.name,
+                                          field_idents,
+                                          fields);
+        let body = create_str_concat_expr(cx, span, pieces);
+
+        // Create the arm.
+        ast::arm {
+            pats: ~[ pat ],
+            guard: None,
+            body: build::mk_simple_block(cx, span, body),
+        }
+    };
+
+    // Create the method body.
+    let self_ident = cx.ident_of(~"self");
+    let self_expr = build::mk_path(cx, span, ~[ self_ident ]);
+    let self_expr = build::mk_unary(cx, span, deref, self_expr);
+    let self_match_expr = expr_match(self_expr, arms);
+    let self_match_expr = build::mk_expr(cx, span, self_match_expr);
+
+    // Create the method.
+    let method = create_to_str_method(cx, span, self_match_expr);
+
+    // Create the implementation.
+    return create_derived_to_str_impl(cx,
+                                      span,
+                                      type_ident,
+                                      move ty_params,
+                                      method);
+}
@@ -22,16 +22,25 @@ use ext::build::mk_uniq_str;
 use core::option;
 use core::os;
 
+/* env!("VAR") expands to the value of VAR at compile time, or to "" if it
+ * isn't set; env!("VAR", "default") expands to "default" instead. */
 pub fn expand_syntax_ext(cx: ext_ctxt, sp: span, tts: ~[ast::token_tree])
     -> base::MacResult {
 
-    let var = get_single_str_from_tts(cx, sp, tts, "env!");
+    let exprs = get_exprs_from_tts(cx, tts);
+    let (var, default) = match exprs.len() {
+      1 => (expr_to_str(cx, exprs[0], ~"env! requires a string."), ~""),
+      2 => (expr_to_str(cx, exprs[0], ~"env! requires a string."),
+            expr_to_str(cx, exprs[1],
+                        ~"the default value of env! must be a string.")),
+      _ => cx.span_fatal(sp, ~"env! takes 1 or 2 arguments.")
+    };
 
     // FIXME (#2248): if this was more thorough it would manufacture an
     // Option<str> rather than just an maybe-empty string.
 
     let e = match os::getenv(var) {
-      option::None => mk_uniq_str(cx, sp, ~""),
+      option::None => mk_uniq_str(cx, sp, default),
       option::Some(ref s) => mk_uniq_str(cx, sp, (*s))
     };
     MRExpr(e)
@@ -13,12 +13,18 @@ use core::prelude::*;
 use ast::{crate, expr_, expr_mac, mac_invoc_tt};
 use ast::{tt_delim, tt_tok, item_mac, stmt_, stmt_mac, stmt_expr, stmt_semi};
 use ast;
+use attr;
 use codemap::{span, ExpandedFrom};
 use ext::base::*;
+use ext::tt::macro_rules;
 use fold::*;
 use parse::{parser, parse_expr_from_source_str, new_parser_from_tts};
+use print::pprust;
 
+use core::io;
+use core::io::WriterUtil;
 use core::option;
+use core::str;
 use core::vec;
 use std::oldmap::HashMap;
 
@@ -49,6 +55,7 @@ pub fn expand_expr(exts: HashMap<~str, SyntaxExtension>, cx: ext_ctxt,
                                                  span: exp_sp})) => {
                     cx.bt_push(ExpandedFrom({call_site: s,
                                 callie: {name: *extname, span: exp_sp}}));
+                    trace_start(cx, || tt_mac_to_str(cx, *extname, *tts));
 
                     let expanded = match exp(cx, (*mac).span, (*tts)) {
                       MRExpr(e) => e,
@@ -59,10 +66,13 @@ pub fn expand_expr(exts: HashMap<~str, SyntaxExtension>, cx: ext_ctxt,
                     };
 
                     //keep going, outside-in
-                    let fully_expanded = fld.fold_expr(expanded).node;
+                    let fully_expanded = fld.fold_expr(expanded);
+                    trace_done(cx, || pprust::expr_to_str(fully_expanded,
+                                                          cx.parse_sess()
+                                                            .interner));
                     cx.bt_pop();
 
-                    (fully_expanded, s)
+                    (fully_expanded.node, s)
                   }
                   _ => {
                     cx.span_fatal(pth.span,
@@ -88,9 +98,16 @@ pub fn expand_expr(exts: HashMap<~str, SyntaxExtension>, cx: ext_ctxt,
 // NB: there is some redundancy between this and expand_item, below, and
 // they might benefit from some amount of semantic and language-UI merger.
 pub fn expand_mod_items(exts: HashMap<~str, SyntaxExtension>, cx: ext_ctxt,
+                        load_macros: MacroCrateLoader,
                         module_: ast::_mod, fld: ast_fold,
                         orig: fn@(ast::_mod, ast_fold) -> ast::_mod)
                      -> ast::_mod {
+    // Any macros exported by the crates this module links to must be in
+    // the table before the items that use them are expanded.
+    for module_.view_items.each |vi| {
+        load_extern_macros(exts, cx, load_macros, *vi);
+    }
+
     // Fold the contents first:
     let module_ = orig(module_, fld);
 
@@ -110,7 +127,10 @@ pub fn expand_mod_items(exts: HashMap<~str, SyntaxExtension>, cx: ext_ctxt,
                   cx.bt_push(ExpandedFrom({call_site: attr.span,
                                            callie: {name: copy mname,
                                                     span: None}}));
+                  let intr = cx.parse_sess().interner;
+                  trace_start(cx, || pprust::attribute_to_str(*attr, intr));
                   let r = dec_fn(cx, attr.span, attr.node.value, items);
+                  trace_done(cx, || items_to_str(r, intr));
                   cx.bt_pop();
                   r
               }
@@ -121,6 +141,30 @@ pub fn expand_mod_items(exts: HashMap<~str, SyntaxExtension>, cx: ext_ctxt,
     ast::_mod { items: new_items, ..module_ }
 }
 
+// Registers the macros and syntax extensions provided by the crate an
+// `extern mod` refers to.
+fn load_extern_macros(exts: HashMap<~str, SyntaxExtension>, cx: ext_ctxt,
+                      load_macros: MacroCrateLoader, vi: @ast::view_item) {
+    match vi.node {
+      ast::view_item_use(*) => {
+        for load_macros(cx, vi).each |mdef| {
+            exts.insert((*mdef).name, (*mdef).ext);
+        }
+      }
+      _ => ()
+    }
+}
+
+// Turns the token trees of an exported `macro_rules!` read from another
+// crate's metadata back into a macro, as though the definition had
+// appeared at `sp`.
+pub fn load_exported_macro(cx: ext_ctxt, sp: span, name: ast::ident,
+                           +tts: ~[ast::token_tree]) -> MacroDef {
+    match macro_rules::add_new_extension(cx, sp, name, tts) {
+      MRDef(mdef) => mdef,
+      _ => cx.span_bug(sp, ~"macro_rules! did not define a macro")
+    }
+}
 
 // When we enter a module, record it, for the sake of `module!`
 pub fn expand_item(exts: HashMap<~str, SyntaxExtension>,
@@ -175,6 +219,7 @@ pub fn expand_item_mac(exts: HashMap<~str, SyntaxExtension>,
             cx.bt_push(ExpandedFrom({call_site: it.span,
                                      callie: {name: *extname,
                                               span: (*expand).span}}));
+            trace_start(cx, || tt_mac_to_str(cx, *extname, tts));
             ((*expand).expander)(cx, it.span, tts)
         }
         Some(ItemTT(ref expand)) => {
@@ -186,6 +231,10 @@ pub fn expand_item_mac(exts: HashMap<~str, SyntaxExtension>,
             cx.bt_push(ExpandedFrom({call_site: it.span,
                                      callie: {name: *extname,
                                               span: (*expand).span}}));
+            trace_start(cx, || fmt!("%s! %s { %s }", *extname,
+                                    cx.str_of(it.ident),
+                                    pprust::tts_to_str(tts, cx.parse_sess()
+                                                              .interner)));
             ((*expand).expander)(cx, it.span, it.ident, tts)
         }
         _ => cx.span_fatal(
@@ -200,10 +249,21 @@ pub fn expand_item_mac(exts: HashMap<~str, SyntaxExtension>,
         MRAny(_, item_maker, _) =>
             option::chain(item_maker(), |i| {fld.fold_item(i)}),
         MRDef(ref mdef) => {
+            // Only a literal macro_rules! can be exported: it is the
+            // invocation's token trees that are written to the metadata.
+            if *extname == ~"macro_rules" &&
+                attr::contains_name(attr::attr_metas(/*bad*/copy it.attrs),
+                                    ~"macro_export") {
+                cx.export_macro(it);
+            }
             exts.insert((*mdef).name, (*mdef).ext);
             None
         }
     };
+    trace_done(cx, || match maybe_it {
+        Some(it) => pprust::item_to_str(it, cx.parse_sess().interner),
+        None => ~"(no item)"
+    });
     cx.bt_pop();
     return maybe_it;
 }
@@ -232,6 +292,7 @@ pub fn expand_stmt(exts: HashMap<~str, SyntaxExtension>, cx: ext_ctxt,
             SyntaxExpanderTT{expander: exp, span: exp_sp})) => {
             cx.bt_push(ExpandedFrom(
                 {call_site: sp, callie: {name: *extname, span: exp_sp}}));
+            trace_start(cx, || tt_mac_to_str(cx, *extname, tts));
             let expanded = match exp(cx, mac.span, tts) {
                 MRExpr(e) =>
                     @codemap::spanned { node: stmt_expr(e, cx.next_id()),
@@ -243,10 +304,12 @@ pub fn expand_stmt(exts: HashMap<~str, SyntaxExtension>, cx: ext_ctxt,
             };
 
             //keep going, outside-in
-            let fully_expanded = fld.fold_stmt(expanded).node;
+            let fully_expanded = fld.fold_stmt(expanded);
+            trace_done(cx, || pprust::stmt_to_str(*fully_expanded,
+                                                  cx.parse_sess().interner));
             cx.bt_pop();
 
-            (fully_expanded, sp)
+            (fully_expanded.node, sp)
         }
 
         _ => {
@@ -263,6 +326,55 @@ pub fn expand_stmt(exts: HashMap<~str, SyntaxExtension>, cx: ext_ctxt,
 }
 
 
+// With -Z trace-expansion, each expansion is logged as it starts, with its
+// input, and once everything it produced has been expanded in turn, with
+// its output. Entries are indented by how deeply they are nested inside
+// other expansions, which is what `trace_macros!` can't show.
+fn trace_start(cx: ext_ctxt, input: fn() -> ~str) {
+    if !cx.trace_expansion() { return; }
+    match cx.backtrace() {
+      Some(ei) => {
+        trace_lines(cx, ~"expanding " + cx.codemap().expn_info_to_str(ei));
+        trace_lines(cx, ~"  input: " + input());
+      }
+      None => cx.bug(~"tracing an expansion that was not pushed")
+    }
+}
+
+fn trace_done(cx: ext_ctxt, output: fn() -> ~str) {
+    if !cx.trace_expansion() { return; }
+    trace_lines(cx, ~"  output: " + output());
+}
+
+// Writes `text` to stderr, indented to the depth of the current expansion
+fn trace_lines(cx: ext_ctxt, text: ~str) {
+    let mut depth = 0u;
+    let mut bt = cx.backtrace();
+    loop {
+        match bt {
+          Some(@ExpandedFrom({call_site: cs, _})) => {
+            depth += 1u;
+            bt = cs.expn_info;
+          }
+          None => break
+        }
+    }
+    let indent = str::repeat(~"    ", depth - 1u);
+    for str::lines_each(text) |line| {
+        io::stderr().write_line(indent + line);
+    }
+}
+
+fn tt_mac_to_str(cx: ext_ctxt, name: &str, tts: &[ast::token_tree]) -> ~str {
+    fmt!("%s! { %s }", name,
+         pprust::tts_to_str(tts, cx.parse_sess().interner))
+}
+
+fn items_to_str(items: &[@ast::item],
+                intr: @parse::token::ident_interner) -> ~str {
+    str::connect(items.map(|i| pprust::item_to_str(*i, intr)), ~"\n")
+}
+
 pub fn new_span(cx: ext_ctxt, sp: span) -> span {
     /* this discards information in the case of macro-defining macros */
     return span {lo: sp.lo, hi: sp.hi, expn_info: cx.backtrace()};
@@ -333,13 +445,18 @@ pub fn core_macros() -> ~str {
 }
 
 pub fn expand_crate(parse_sess: parse::parse_sess,
-                    cfg: ast::crate_cfg, c: @crate) -> @crate {
+                    cfg: ast::crate_cfg, load_macros: MacroCrateLoader,
+                    trace_expansion: bool, c: @crate) -> @crate {
     let exts = syntax_expander_table();
     let afp = default_ast_fold();
     let cx: ext_ctxt = mk_ctxt(parse_sess, cfg);
+    cx.set_trace_expansion(trace_expansion);
+    cx.set_crate_dir(
+        Path(parse_sess.cm.span_to_filename(c.span)).dir_path());
     let f_pre = @AstFoldFns {
         fold_expr: |a,b,c| expand_expr(exts, cx, a, b, c, afp.fold_expr),
-        fold_mod: |a,b| expand_mod_items(exts, cx, a, b, afp.fold_mod),
+        fold_mod: |a,b| expand_mod_items(exts, cx, load_macros, a, b,
+                                         afp.fold_mod),
         fold_item: |a,b| expand_item(exts, cx, a, b, afp.fold_item),
         fold_stmt: |a,b,c| expand_stmt(exts, cx, a, b, c, afp.fold_stmt),
         new_span: |a| new_span(cx, a),
@@ -355,7 +472,11 @@ pub fn expand_crate(parse_sess: parse::parse_sess,
     f.fold_expr(cm);
 
     let res = @f.fold_crate(*c);
-    return res;
+    return @codemap::spanned {
+        node: ast::crate_ { exported_macros: cx.exported_macros(),
+                            .. /*bad*/copy res.node },
+        .. /*bad*/copy *res
+    };
 }
 // Local Variables:
 // mode: rust
@@ -0,0 +1,298 @@
+// Copyright 2013 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+/*
+ * The compiler code necessary to support the format! and write!
+ * extensions. The format string is parsed by core::fmt::parse; each of
+ * its arguments becomes a call to the core::fmt::rt function for its
+ * type, which is bounded by the trait that does the conversion. Those
+ * calls are given spans inside the format string, so that an argument of
+ * the wrong type is reported there by the type checker.
+ */
+
+use core::prelude::*;
+
+use ast;
+use codemap::{span, BytePos};
+use ext::base::*;
+use ext::base;
+use ext::build::*;
+use parse;
+use parse::token;
+
+use core::fmt::parse::*;
+use core::fmt::{Spec, AlignLeft, AlignRight, AlignCenter, AlignDefault};
+use core::vec;
+
+// The arguments of a call, after the writer and the format string
+struct Args {
+    // Positional arguments followed by named ones
+    exprs: ~[@ast::expr],
+    // The names of the named arguments, which start at `positional`
+    names: ~[~str],
+    positional: uint
+}
+
+pub fn expand_format(cx: ext_ctxt, sp: span, tts: ~[ast::token_tree])
+    -> base::MacResult {
+    let p = parse::new_parser_from_tts(cx.parse_sess(), cx.cfg(), tts);
+    if p.token == token::EOF {
+        cx.span_fatal(sp, "format! takes at least 1 argument.");
+    }
+    let fmt = p.parse_expr();
+    let args = parse_args(cx, p);
+    let body = format_body(cx, sp, fmt, &args);
+
+    let ext_cx = cx;
+    let expr = quote_expr!( ::core::io::with_str_writer(|__wr| $body) );
+    MRExpr(bind_args(cx, sp, ~[], ~[], &args, expr))
+}
+
+pub fn expand_write(cx: ext_ctxt, sp: span, tts: ~[ast::token_tree])
+    -> base::MacResult {
+    let p = parse::new_parser_from_tts(cx.parse_sess(), cx.cfg(), tts);
+    if p.token == token::EOF {
+        cx.span_fatal(sp, "write! takes at least 2 arguments.");
+    }
+    let writer = p.parse_expr();
+    p.expect(token::COMMA);
+    let fmt = p.parse_expr();
+    let args = parse_args(cx, p);
+    let body = format_body(cx, sp, fmt, &args);
+
+    MRExpr(bind_args(cx, sp, ~[writer], ~[cx.ident_of(~"__wr")], &args,
+                     body))
+}
+
+// Parses `, arg, ..., name = arg, ...` up to the end of the call
+fn parse_args(cx: ext_ctxt, p: parse::parser::Parser) -> Args {
+    let mut exprs = ~[];
+    let mut names = ~[];
+
+    while p.token != token::EOF {
+        p.expect(token::COMMA);
+        if p.token == token::EOF { break; }
+
+        if token::is_ident(copy p.token) && p.look_ahead(1u) == token::EQ {
+            let name = cx.str_of(p.parse_ident());
+            p.expect(token::EQ);
+            let e = p.parse_expr();
+            if names.contains(&name) {
+                cx.span_err(e.span,
+                            fmt!("duplicate argument named `%s`", name));
+            }
+            names.push(name);
+            exprs.push(e);
+        } else {
+            if !names.is_empty() {
+                cx.span_fatal(copy p.span, "positional arguments cannot \
+                                            follow named arguments");
+            }
+            exprs.push(p.parse_expr());
+        }
+    }
+
+    let positional = exprs.len() - names.len();
+    Args { exprs: exprs, names: names, positional: positional }
+}
+
+// Builds a block that writes the formatted arguments to `__wr`, reading
+// argument i from `__argi`
+fn format_body(cx: ext_ctxt, sp: span, fmt_expr: @ast::expr, args: &Args)
+            -> @ast::expr {
+    let fmt = expr_to_str(cx, fmt_expr,
+                          ~"format string must be a string literal");
+    let fmt_sp = fmt_expr.span;
+
+    // Spans inside the string can only be found if its source is exactly
+    // the string, with no escapes
+    let exact = cx.codemap().span_to_snippet(fmt_sp) == ~"\"" + fmt + ~"\"";
+    let sub_span = |lo: uint, hi: uint| {
+        if exact {
+            span { lo: fmt_sp.lo + BytePos(lo + 1),
+                   hi: fmt_sp.lo + BytePos(hi + 1),
+                   expn_info: fmt_sp.expn_info }
+        } else {
+            fmt_sp
+        }
+    };
+    let err = fn@(lo: uint, hi: uint, msg: &str) -> ! {
+        cx.span_fatal(sub_span(lo, hi), msg)
+    };
+    let pieces = parse_format_string(fmt, err);
+
+    let wr = cx.ident_of(~"__wr");
+    let mut used = vec::from_elem(args.exprs.len(), false);
+    let mut next = 0;
+    let mut stmts = ~[];
+
+    for pieces.each |piece| {
+        match *piece {
+          PieceString(ref s) => {
+            stmts.push(mk_stmt(cx, fmt_sp, mk_call_global(
+                cx, fmt_sp, rt_path(cx, ~"write_str"),
+                ~[mk_path(cx, fmt_sp, ~[wr]),
+                  mk_base_str(cx, fmt_sp, copy *s)])));
+          }
+          PieceArgument(ref arg) => {
+            let arg_sp = sub_span(arg.lo, arg.hi);
+            let idx = match arg.position {
+              ArgumentNext => { next += 1; next - 1 }
+              ArgumentIs(i) => i,
+              ArgumentNamed(ref name) => {
+                match vec::position(args.names, |n| n == name) {
+                  Some(i) => args.positional + i,
+                  None => {
+                    cx.span_err(arg_sp, fmt!("there is no argument named \
+                                              `%s`", *name));
+                    loop;
+                  }
+                }
+              }
+            };
+            if idx >= args.exprs.len() {
+                cx.span_err(arg_sp,
+                            fmt!("invalid reference to argument `%u` \
+                                  (there %s %u)", idx,
+                                 if args.exprs.len() == 1 { "is" }
+                                 else { "are" },
+                                 args.exprs.len()));
+                loop;
+            }
+            used[idx] = true;
+
+            let f = match arg.ty {
+              None => ~"to_str",
+              Some('s') => ~"string",
+              Some('c') => ~"character",
+              Some('b') => ~"boolean",
+              Some('d') | Some('i') => ~"signed",
+              Some('u') => ~"unsigned",
+              Some('x') => ~"lower_hex",
+              Some('X') => ~"upper_hex",
+              Some('o') => ~"octal",
+              Some('t') => ~"binary",
+              Some('f') => ~"floating",
+              Some('?') => ~"poly",
+              Some(c) => cx.span_bug(arg_sp, fmt!("unknown format type \
+                                                   `%c`", c))
+            };
+            let call = mk_call_global(
+                cx, arg_sp, rt_path(cx, f),
+                ~[mk_path(cx, arg_sp, ~[wr]),
+                  mk_addr_of(cx, arg_sp, spec_expr(cx, arg_sp, arg.spec)),
+                  mk_path(cx, arg_sp, ~[arg_ident(cx, idx)])]);
+            stmts.push(mk_stmt(cx, arg_sp, call));
+          }
+        }
+    }
+
+    for used.eachi |i, u| {
+        if !*u {
+            let msg = if i < args.positional {
+                ~"argument never used"
+            } else {
+                fmt!("named argument `%s` never used",
+                     args.names[i - args.positional])
+            };
+            cx.span_err(args.exprs[i].span, msg);
+        }
+    }
+
+    mk_block(cx, fmt_sp, ~[], stmts, None)
+}
+
+// Wraps `body` in a match that evaluates `leading` and then the arguments
+// once each, binding them to `leading_idents` and to `__argi`. Arguments
+// are bound by reference, so formatting doesn't move them, and a match is
+// used so that temporaries in them live as long as `body`.
+fn bind_args(cx: ext_ctxt, sp: span, leading: ~[@ast::expr],
+             leading_idents: ~[ast::ident], args: &Args,
+             body: @ast::expr) -> @ast::expr {
+    let mut exprs = leading;
+    let mut idents = leading_idents;
+    for args.exprs.eachi |i, e| {
+        exprs.push(mk_addr_of(cx, e.span, *e));
+        idents.push(arg_ident(cx, i));
+    }
+
+    let (scrutinee, pat) = match exprs.len() {
+      0 => return body,
+      1 => (exprs[0], mk_pat_ident(cx, sp, idents[0])),
+      _ => {
+        let pats = idents.map(|i| mk_pat_ident(cx, sp, *i));
+        (mk_expr(cx, sp, ast::expr_tup(exprs)),
+         mk_pat(cx, sp, ast::pat_tup(pats)))
+      }
+    };
+    let arm = ast::arm {
+        pats: ~[pat],
+        guard: None,
+        body: mk_simple_block(cx, sp, body)
+    };
+    mk_expr(cx, sp, ast::expr_match(scrutinee, ~[arm]))
+}
+
+fn arg_ident(cx: ext_ctxt, i: uint) -> ast::ident {
+    cx.ident_of(fmt!("__arg%u", i))
+}
+
+fn rt_path(cx: ext_ctxt, name: ~str) -> ~[ast::ident] {
+    ~[cx.ident_of(~"core"), cx.ident_of(~"fmt"), cx.ident_of(~"rt"),
+      cx.ident_of(name)]
+}
+
+// `::core::fmt::Spec { ... }` for the given spec
+fn spec_expr(cx: ext_ctxt, sp: span, spec: Spec) -> @ast::expr {
+    fn core_path(cx: ext_ctxt, sp: span, names: ~[~str]) -> @ast::expr {
+        let idents = ~[cx.ident_of(~"core")] + names.map(|n| cx.ident_of(*n));
+        mk_path_global(cx, sp, idents)
+    }
+    fn count(cx: ext_ctxt, sp: span, c: Option<uint>) -> @ast::expr {
+        match c {
+          Some(n) => mk_call_(cx, sp,
+                              core_path(cx, sp, ~[~"option", ~"Some"]),
+                              ~[mk_uint(cx, sp, n)]),
+          None => core_path(cx, sp, ~[~"option", ~"None"])
+        }
+    }
+
+    let align = match spec.align {
+      AlignLeft => ~"AlignLeft",
+      AlignRight => ~"AlignRight",
+      AlignCenter => ~"AlignCenter",
+      AlignDefault => ~"AlignDefault"
+    };
+    mk_global_struct_e(
+        cx, sp,
+        ~[cx.ident_of(~"core"), cx.ident_of(~"fmt"), cx.ident_of(~"Spec")],
+        ~[
+            {ident: cx.ident_of(~"fill"),
+             ex: mk_lit(cx, sp, ast::lit_int(spec.fill as i64,
+                                             ast::ty_char))},
+            {ident: cx.ident_of(~"align"),
+             ex: core_path(cx, sp, ~[~"fmt", align])},
+            {ident: cx.ident_of(~"width"),
+             ex: count(cx, sp, spec.width)},
+            {ident: cx.ident_of(~"precision"),
+             ex: count(cx, sp, spec.precision)},
+        ]
+    )
+}
+
+//
+// Local Variables:
+// mode: rust
+// fill-column: 78;
+// indent-tabs-mode: nil
+// c-basic-offset: 4
+// buffer-file-coding-system: utf-8-unix
+// End:
+//
@@ -62,42 +62,69 @@ pub fn expand_mod(cx: ext_ctxt, sp: span, tts: ~[ast::token_tree])
                                   |x| cx.str_of(*x)), ~"::")))
 }
 
+/* include!("file"), include_str!("file") and include_bin!("file") resolve
+ * the path relative to the file they're called from;
+ * include!(crate_root, "file") and so on resolve it relative to the
+ * directory of the crate's root source file instead. */
 pub fn expand_include(cx: ext_ctxt, sp: span, tts: ~[ast::token_tree])
     -> base::MacResult {
-    let file = get_single_str_from_tts(cx, sp, tts, "include!");
+    let file = get_include_path(cx, sp, tts, "include!");
     let p = parse::new_sub_parser_from_file(
-        cx.parse_sess(), cx.cfg(),
-        &res_rel_file(cx, sp, &Path(file)), sp);
+        cx.parse_sess(), cx.cfg(), &file, sp);
     base::MRExpr(p.parse_expr())
 }
 
 pub fn expand_include_str(cx: ext_ctxt, sp: span, tts: ~[ast::token_tree])
     -> base::MacResult {
-    let file = get_single_str_from_tts(cx, sp, tts, "include_str!");
-    let res = io::read_whole_file_str(&res_rel_file(cx, sp, &Path(file)));
-    match res {
-      result::Ok(_) => { /* Continue. */ }
+    let file = get_include_path(cx, sp, tts, "include_str!");
+    match io::read_whole_file_str(&file) {
+      result::Ok(src) => {
+        record_included_file(cx, file);
+        base::MRExpr(mk_base_str(cx, sp, src))
+      }
       result::Err(ref e) => {
-        cx.parse_sess().span_diagnostic.handler().fatal((*e));
+        cx.span_fatal(sp, fmt!("couldn't read %s: %s", file.to_str(), *e))
       }
     }
-
-    base::MRExpr(mk_base_str(cx, sp, result::unwrap(res)))
 }
 
 pub fn expand_include_bin(cx: ext_ctxt, sp: span, tts: ~[ast::token_tree])
     -> base::MacResult {
-    let file = get_single_str_from_tts(cx, sp, tts, "include_bin!");
-    match io::read_whole_file(&res_rel_file(cx, sp, &Path(file))) {
+    let file = get_include_path(cx, sp, tts, "include_bin!");
+    match io::read_whole_file(&file) {
       result::Ok(src) => {
+        record_included_file(cx, file);
         let u8_exprs = vec::map(src, |char| {
             mk_u8(cx, sp, *char)
         });
         base::MRExpr(mk_base_vec_e(cx, sp, u8_exprs))
       }
       result::Err(ref e) => {
-        cx.parse_sess().span_diagnostic.handler().fatal((*e))
+        cx.span_fatal(sp, fmt!("couldn't read %s: %s", file.to_str(), *e))
+      }
+    }
+}
+
+// The path named by the arguments of an include macro, with an optional
+// leading `crate_root`
+fn get_include_path(cx: ext_ctxt, sp: span, tts: ~[ast::token_tree],
+                    name: &str) -> Path {
+    let exprs = get_exprs_from_tts(cx, tts);
+    let str_err = fmt!("%s requires a string.", name);
+    match exprs.len() {
+      1 => res_rel_file(cx, sp, &Path(expr_to_str(cx, exprs[0], str_err))),
+      2 => {
+        let mode_err = fmt!("the first argument of %s must be `crate_root`",
+                            name);
+        let mode = expr_to_ident(cx, exprs[0], copy mode_err);
+        if cx.str_of(mode) != ~"crate_root" {
+            cx.span_fatal(exprs[0].span, mode_err);
+        }
+        let arg = Path(expr_to_str(cx, exprs[1], str_err));
+        if arg.is_absolute { arg }
+        else { cx.crate_dir().push_many(arg.components) }
       }
+      _ => cx.span_fatal(sp, fmt!("%s takes 1 or 2 arguments.", name))
     }
 }
 
@@ -111,6 +138,15 @@ fn res_rel_file(cx: ext_ctxt, sp: codemap::span, arg: &Path) -> Path {
     }
 }
 
+// Files that aren't parsed don't get a filemap, so they're recorded on the
+// parse session for dependency info to find
+fn record_included_file(cx: ext_ctxt, file: Path) {
+    let sess = cx.parse_sess();
+    if !sess.included_files.contains(&file) {
+        sess.included_files.push(file);
+    }
+}
+
 //
 // Local Variables:
 // mode: rust
@@ -12,6 +12,7 @@
 use ast::{matcher, match_tok, match_seq, match_nonterminal, ident};
 use codemap::{BytePos, mk_sp};
 use codemap;
+use parse::attr::parser_attr;
 use parse::common::*; //resolve bug?
 use parse::lexer::*; //resolve bug?
 use parse::parse_sess;
@@ -119,6 +120,10 @@ pub type matcher_pos = ~{
     matches: ~[DVec<@named_match>],
     match_lo: uint, match_hi: uint,
     sp_lo: BytePos,
+    // tokens consumed before the current repetition began (its separator
+    // counts as part of it), and whether it is past its first repetition
+    mut tok_lo: uint,
+    mut repeated: bool,
 };
 
 pub fn copy_up(&& mpu: matcher_pos_up) -> matcher_pos {
@@ -128,6 +133,17 @@ pub fn copy_up(&& mpu: matcher_pos_up) -> matcher_pos {
     }
 }
 
+// Whether the sequence a repetition belongs to may repeat zero times
+pub fn up_zero_ok(&& mpu: matcher_pos_up) -> bool {
+    match &mpu {
+      &matcher_pos_up(Some(ref mp)) => match mp.elts[mp.idx].node {
+        match_seq(_, _, zero_ok, _, _) => zero_ok,
+        _ => die!()
+      },
+      _ => die!()
+    }
+}
+
 pub fn count_names(ms: &[matcher]) -> uint {
     vec::foldl(0u, ms, |ct, m| {
         ct + match m.node {
@@ -154,7 +170,8 @@ pub fn initial_matcher_pos(ms: ~[matcher], sep: Option<Token>, lo: BytePos)
     }
     ~{elts: ms, sep: sep, mut idx: 0u, mut up: matcher_pos_up(None),
       matches: copy vec::from_fn(count_names(ms), |_i| dvec::DVec()),
-      match_lo: 0u, match_hi: match_idx_hi, sp_lo: lo}
+      match_lo: 0u, match_hi: match_idx_hi, sp_lo: lo,
+      mut tok_lo: 0u, mut repeated: false}
 }
 
 // named_match is a pattern-match result for a single ast::match_nonterminal:
@@ -233,10 +250,15 @@ pub fn parse(sess: parse_sess,
     let mut cur_eis = ~[];
     cur_eis.push(initial_matcher_pos(ms, None, rdr.peek().sp.lo));
 
+    // the number of tokens consumed so far
+    let mut tokens_seen = 0u;
+
     loop {
         let mut bb_eis = ~[]; // black-box parsed by parser.rs
         let mut next_eis = ~[]; // or proceed normally
         let mut eof_eis = ~[];
+        // what the items that didn't match this token were waiting for
+        let mut expected = ~[];
 
         let TokenAndSpan {tok: tok, sp: sp} = rdr.peek();
 
@@ -254,10 +276,18 @@ pub fn parse(sess: parse_sess,
                     // hack: a matcher sequence is repeating iff it has a
                     // parent (the top level is just a container)
 
+                    // A repetition that consumed no tokens must not go
+                    // around again, or we would loop forever. It only
+                    // finishes the sequence if it is the first one of a
+                    // `+`: an empty first repetition of a `*` is the same
+                    // parse as skipping the sequence, and an empty later one
+                    // is the same as finishing after the one before.
+                    let empty = idx == len && ei.tok_lo == tokens_seen;
+                    let zero_ok = up_zero_ok(ei.up);
 
                     // disregard separator, try to go up
                     // (remove this condition to make trailing seps ok)
-                    if idx == len {
+                    if idx == len && !(empty && (ei.repeated || zero_ok)) {
                         // pop from the matcher position
 
                         let new_pos = copy_up(ei.up);
@@ -286,18 +316,31 @@ pub fn parse(sess: parse_sess,
 
                     // the *_t vars are workarounds for the lack of unary move
                     match copy ei.sep {
+                      _ if empty => (),
                       Some(ref t) if idx == len => { // we need a separator
                         if tok == (*t) { //pass the separator
                             let ei_t = move ei;
                             ei_t.idx += 1;
+                            ei_t.tok_lo = tokens_seen;
+                            ei_t.repeated = true;
                             next_eis.push(move ei_t);
+                        } else {
+                            push_expected(&mut expected,
+                                          token_desc(rdr, *t));
                         }
                       }
-                      _ => { // we don't need a separator
+                      Some(_) => { // we have passed the separator
                         let ei_t = move ei;
                         ei_t.idx = 0;
                         cur_eis.push(move ei_t);
                       }
+                      None => { // we don't need a separator
+                        let ei_t = move ei;
+                        ei_t.idx = 0;
+                        ei_t.tok_lo = tokens_seen;
+                        ei_t.repeated = true;
+                        cur_eis.push(move ei_t);
+                      }
                     }
                 } else {
                     eof_eis.push(move ei);
@@ -326,22 +369,31 @@ pub fn parse(sess: parse_sess,
                         mut up: matcher_pos_up(Some(move ei_t)),
                         matches: move matches,
                         match_lo: match_idx_lo, match_hi: match_idx_hi,
-                        sp_lo: sp.lo
+                        sp_lo: sp.lo,
+                        mut tok_lo: tokens_seen, mut repeated: false
                     });
                   }
-                  match_nonterminal(_,_,_) => { bb_eis.push(move ei) }
+                  match_nonterminal(_, name, _) => {
+                    let name = *sess.interner.get(name);
+                    if token_can_begin(name, tok) {
+                        bb_eis.push(move ei)
+                    }
+                    push_expected(&mut expected,
+                                  fmt!("a `%s` fragment", name));
+                  }
                   match_tok(ref t) => {
                     let ei_t = move ei;
                     if (*t) == tok {
                         ei_t.idx += 1;
                         next_eis.push(move ei_t);
+                    } else {
+                        push_expected(&mut expected, token_desc(rdr, *t));
                     }
                   }
                 }
             }
         }
 
-        /* error messages here could be improved with links to orig. rules */
         if tok == EOF {
             if eof_eis.len() == 1u {
                 return success(
@@ -350,7 +402,8 @@ pub fn parse(sess: parse_sess,
             } else if eof_eis.len() > 1u {
                 return error(sp, ~"Ambiguity: multiple successful parses");
             } else {
-                return failure(sp, ~"Unexpected end of macro invocation");
+                return failure(sp, ~"unexpected end of macro invocation; "
+                                   + expected_to_str(expected));
             }
         } else {
             if (bb_eis.len() > 0u && next_eis.len() > 0u)
@@ -368,14 +421,21 @@ pub fn parse(sess: parse_sess,
                      built-in NTs %s or %u other options.",
                     nts, next_eis.len()));
             } else if (bb_eis.len() == 0u && next_eis.len() == 0u) {
-                return failure(sp, ~"No rules expected the token: "
-                            + to_str(rdr.interner(), tok));
+                if eof_eis.len() > 0u {
+                    push_expected(&mut expected,
+                                  ~"the end of the macro invocation");
+                }
+                return failure(sp, fmt!("no rules expected the token `%s`; \
+                                         %s",
+                                        to_str(rdr.interner(), tok),
+                                        expected_to_str(expected)));
             } else if (next_eis.len() > 0u) {
                 /* Now process the next token */
                 while(next_eis.len() > 0u) {
                     cur_eis.push(next_eis.pop());
                 }
                 rdr.next_token();
+                tokens_seen += 1u;
             } else /* bb_eis.len() == 1 */ {
                 let rust_parser = Parser(sess, cfg, rdr.dup());
 
@@ -393,6 +453,7 @@ pub fn parse(sess: parse_sess,
                 for rust_parser.tokens_consumed.times() || {
                     rdr.next_token();
                 }
+                tokens_seen += rust_parser.tokens_consumed;
             }
         }
 
@@ -400,6 +461,36 @@ pub fn parse(sess: parse_sess,
     }
 }
 
+// Fragments that are a single token are checked here rather than by the
+// parser, so that they never make the parse ambiguous with a token that
+// they can't match
+fn token_can_begin(name: &str, tok: Token) -> bool {
+    match tok {
+      token::IDENT(*) => name != "lifetime",
+      token::LIFETIME(*) => name != "ident",
+      _ => name != "ident" && name != "lifetime"
+    }
+}
+
+fn token_desc(rdr: reader, t: Token) -> ~str {
+    fmt!("`%s`", to_str(rdr.interner(), t))
+}
+
+fn push_expected(expected: &mut ~[~str], desc: ~str) {
+    if !expected.contains(&desc) { expected.push(desc); }
+}
+
+// `expected x`, or `expected one of x, y or z`
+fn expected_to_str(expected: &[~str]) -> ~str {
+    match expected.len() {
+      0u => ~"expected nothing more",
+      1u => ~"expected " + expected[0],
+      n => fmt!("expected one of %s or %s",
+                str::connect(expected.slice(0u, n - 1u), ~", "),
+                expected[n - 1u])
+    }
+}
+
 pub fn parse_nt(p: Parser, name: ~str) -> nonterminal {
     match name {
       ~"item" => match p.parse_item(~[]) {
@@ -418,6 +509,20 @@ pub fn parse_nt(p: Parser, name: ~str) -> nonterminal {
                      + token::to_str(p.reader.interner(), copy p.token))
       },
       ~"path" => token::nt_path(p.parse_path_with_tps(false)),
+      // a literal is interpolated as an expression, which is also accepted
+      // wherever the parser expects a literal
+      ~"lit" => {
+        let lit = p.parse_lit();
+        token::nt_expr(p.mk_expr(lit.span.lo, lit.span.hi,
+                                 ast::expr_lit(@lit)))
+      }
+      ~"meta" => token::nt_meta(p.parse_meta_item()),
+      // like ident, this is a single token
+      ~"lifetime" => match copy p.token {
+        token::LIFETIME(id) => { p.bump(); token::nt_lifetime(id) }
+        _ => p.fatal(~"expected lifetime, found "
+                     + token::to_str(p.reader.interner(), copy p.token))
+      },
       ~"tt" => {
         p.quote_depth += 1u; //but in theory, non-quoted tts might be useful
         let res = token::nt_tt(@p.parse_token_tree());
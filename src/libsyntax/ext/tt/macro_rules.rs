@@ -13,7 +13,7 @@ use core::prelude::*;
 use ast::{ident, matcher_, matcher, match_tok, match_nonterminal, match_seq};
 use ast::{tt_delim};
 use ast;
-use codemap::{span, spanned, dummy_sp};
+use codemap::{span, spanned, dummy_sp, mk_sp};
 use ext::base::{ext_ctxt, MacResult, MRAny, MRDef, MacroDef, NormalTT};
 use ext::base;
 use ext::tt::macro_parser::{error};
@@ -54,7 +54,8 @@ pub fn add_new_extension(cx: ext_ctxt, sp: span, name: ident,
 
     // Parse the macro_rules! invocation (`none` is for no interpolations):
     let arg_reader = new_tt_reader(cx.parse_sess().span_diagnostic,
-                                   cx.parse_sess().interner, None, arg);
+                                   cx.parse_sess().interner, None, arg,
+                                   None);
     let argument_map = parse_or_else(cx.parse_sess(), cx.cfg(),
                                      arg_reader as reader, argument_gram);
 
@@ -69,7 +70,7 @@ pub fn add_new_extension(cx: ext_ctxt, sp: span, name: ident,
     };
 
     // Given `lhses` and `rhses`, this is the new macro we create
-    fn generic_extension(cx: ext_ctxt, sp: span, name: ident,
+    fn generic_extension(cx: ext_ctxt, sp: span, def_sp: span, name: ident,
                          arg: ~[ast::token_tree],
                          lhses: ~[@named_match], rhses: ~[@named_match])
     -> MacResult {
@@ -85,6 +86,7 @@ pub fn add_new_extension(cx: ext_ctxt, sp: span, name: ident,
         // Which arm's failure should we report? (the one furthest along)
         let mut best_fail_spot = dummy_sp();
         let mut best_fail_msg = ~"internal error: ran no matchers";
+        let mut best_fail_arm = 0u;
 
         let s_d = cx.parse_sess().span_diagnostic;
         let itr = cx.parse_sess().interner;
@@ -93,7 +95,8 @@ pub fn add_new_extension(cx: ext_ctxt, sp: span, name: ident,
             match *lhs {
               @matched_nonterminal(nt_matchers(ref mtcs)) => {
                 // `none` is because we're not interpolating
-                let arg_rdr = new_tt_reader(s_d, itr, None, arg) as reader;
+                let arg_rdr = new_tt_reader(s_d, itr, None, arg,
+                                            None) as reader;
                 match parse(cx.parse_sess(), cx.cfg(), arg_rdr, (*mtcs)) {
                   success(named_matches) => {
                     let rhs = match rhses[i] {
@@ -111,8 +114,10 @@ pub fn add_new_extension(cx: ext_ctxt, sp: span, name: ident,
                         _ => cx.span_bug(sp, ~"bad thing in rhs")
                     };
                     // rhs has holes ( `$id` and `$(...)` that need filled)
+                    // and its own identifiers are marked as coming from this
+                    // expansion
                     let trncbr = new_tt_reader(s_d, itr, Some(named_matches),
-                                               rhs);
+                                               rhs, Some(itr.fresh_mark()));
                     let p = @Parser(cx.parse_sess(), cx.cfg(),
                                     trncbr as reader);
 
@@ -125,6 +130,7 @@ pub fn add_new_extension(cx: ext_ctxt, sp: span, name: ident,
                   failure(sp, ref msg) => if sp.lo >= best_fail_spot.lo {
                     best_fail_spot = sp;
                     best_fail_msg = (*msg);
+                    best_fail_arm = i;
                   },
                   error(sp, ref msg) => cx.span_fatal(sp, (*msg))
                 }
@@ -132,11 +138,28 @@ pub fn add_new_extension(cx: ext_ctxt, sp: span, name: ident,
               _ => cx.bug(~"non-matcher found in parsed lhses")
             }
         }
-        cx.span_fatal(best_fail_spot, best_fail_msg);
+        if lhses.len() < 2u {
+            cx.span_fatal(best_fail_spot, best_fail_msg);
+        }
+
+        // Point at the arm too, since it may not be the one the caller meant
+        let arm_sp = match lhses[best_fail_arm] {
+          @matched_nonterminal(nt_matchers(ref mtcs)) if mtcs.len() > 0u => {
+            mk_sp(mtcs[0u].span.lo, mtcs.last().span.hi)
+          }
+          _ => def_sp
+        };
+        cx.span_err(best_fail_spot, best_fail_msg);
+        s_d.span_note(arm_sp, fmt!("rule %u of `%s!` matched the most \
+                                    tokens before failing",
+                                   best_fail_arm + 1u, cx.str_of(name)));
+        s_d.handler().abort_if_errors();
+        die!();
     }
 
     let exp: @fn(ext_ctxt, span, ~[ast::token_tree]) -> MacResult =
-        |cx, sp, arg| generic_extension(cx, sp, name, arg, lhses, rhses);
+        |cx, call_sp, arg| generic_extension(cx, call_sp, sp, name, arg,
+                                             lhses, rhses);
 
     return MRDef(MacroDef{
         name: *cx.parse_sess().interner.get(name),
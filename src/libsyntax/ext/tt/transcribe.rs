@@ -16,7 +16,8 @@ use ast_util;
 use codemap::{span, dummy_sp};
 use diagnostic::span_handler;
 use ext::tt::macro_parser::{named_match, matched_seq, matched_nonterminal};
-use parse::token::{EOF, INTERPOLATED, IDENT, Token, nt_ident, ident_interner};
+use parse::token::{EOF, INTERPOLATED, IDENT, LIFETIME, Token};
+use parse::token::{nt_ident, nt_lifetime, ident_interner};
 use parse::lexer::TokenAndSpan;
 
 use core::option;
@@ -43,6 +44,8 @@ pub struct TtReader {
     interpolations: std::oldmap::HashMap<ident, @named_match>,
     repeat_idx: ~[uint],
     repeat_len: ~[uint],
+    /* for hygiene: the mark of the expansion being transcribed */
+    mark: Option<uint>,
     /* cached: */
     cur_tok: Token,
     cur_span: span
@@ -50,11 +53,14 @@ pub struct TtReader {
 
 /** This can do Macro-By-Example transcription. On the other hand, if
  *  `src` contains no `tt_seq`s and `tt_nonterminal`s, `interp` can (and
- *  should) be none. */
+ *  should) be none. When transcribing a macro body, `mark` is the fresh mark
+ *  of the expansion; it is applied to every identifier that comes from `src`
+ *  rather than from the macro's arguments. */
 pub fn new_tt_reader(sp_diag: span_handler,
                      itr: @ident_interner,
                      interp: Option<std::oldmap::HashMap<ident,@named_match>>,
-                     src: ~[ast::token_tree])
+                     src: ~[ast::token_tree],
+                     mark: Option<uint>)
                   -> @mut TtReader {
     let r = @mut TtReader {
         sp_diag: sp_diag,
@@ -72,6 +78,7 @@ pub fn new_tt_reader(sp_diag: span_handler,
         },
         repeat_idx: ~[],
         repeat_len: ~[],
+        mark: mark,
         /* dummy values, never read: */
         cur_tok: EOF,
         cur_span: dummy_sp()
@@ -101,6 +108,7 @@ pub pure fn dup_tt_reader(r: @mut TtReader) -> @mut TtReader {
         interpolations: r.interpolations,
         repeat_idx: copy r.repeat_idx,
         repeat_len: copy r.repeat_len,
+        mark: r.mark,
         cur_tok: r.cur_tok,
         cur_span: r.cur_span
     }
@@ -161,6 +169,16 @@ fn lockstep_iter_size(t: token_tree, r: @mut TtReader) -> lis {
 }
 
 
+// Identifiers written in the macro body get the expansion's mark
+fn mark_token(r: @mut TtReader, tok: Token) -> Token {
+    match (r.mark, tok) {
+      (Some(mark), IDENT(id, is_mod_name)) => {
+        IDENT(r.interner.apply_mark(id, mark), is_mod_name)
+      }
+      _ => tok
+    }
+}
+
 pub fn tt_next_token(r: @mut TtReader) -> TokenAndSpan {
     let ret_val = TokenAndSpan { tok: r.cur_tok, sp: r.cur_span };
     while r.cur.idx >= r.cur.readme.len() {
@@ -189,7 +207,8 @@ pub fn tt_next_token(r: @mut TtReader) -> TokenAndSpan {
             r.repeat_idx[r.repeat_idx.len() - 1u] += 1u;
             match r.cur.sep {
               Some(tk) => {
-                r.cur_tok = tk; /* repeat same span, I guess */
+                /* repeat same span, I guess */
+                r.cur_tok = mark_token(r, tk);
                 return ret_val;
               }
               None => ()
@@ -210,7 +229,7 @@ pub fn tt_next_token(r: @mut TtReader) -> TokenAndSpan {
             // if this could be 0-length, we'd need to potentially recur here
           }
           tt_tok(sp, copy tok) => {
-            r.cur_span = sp; r.cur_tok = tok;
+            r.cur_span = sp; r.cur_tok = mark_token(r, tok);
             r.cur.idx += 1u;
             return ret_val;
           }
@@ -263,6 +282,11 @@ pub fn tt_next_token(r: @mut TtReader) -> TokenAndSpan {
                 r.cur.idx += 1u;
                 return ret_val;
               }
+              matched_nonterminal(nt_lifetime(sn)) => {
+                r.cur_span = sp; r.cur_tok = LIFETIME(sn);
+                r.cur.idx += 1u;
+                return ret_val;
+              }
               matched_nonterminal(ref other_whole_nt) => {
                 r.cur_span = sp; r.cur_tok = INTERPOLATED((*other_whole_nt));
                 r.cur.idx += 1u;
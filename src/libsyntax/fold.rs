@@ -155,6 +155,7 @@ pub fn noop_fold_crate(c: crate_, fld: ast_fold) -> crate_ {
         module: fld.fold_mod(c.module),
         attrs: c.attrs.map(|x| fold_attribute(*x)),
         config: c.config.map(|x| fold_meta_item(*x)),
+        exported_macros: /*bad*/copy c.exported_macros,
     }
 }
 
@@ -208,7 +209,8 @@ fn noop_fold_struct_field(&&sf: @struct_field, fld: ast_fold)
                        -> @struct_field {
     @spanned { node: ast::struct_field_ { kind: copy sf.node.kind,
                                           id: sf.node.id,
-                                          ty: fld.fold_ty(sf.node.ty) },
+                                          ty: fld.fold_ty(sf.node.ty),
+                                          attrs: copy sf.node.attrs },
                span: sf.span }
 }
 
@@ -288,7 +290,8 @@ fn fold_trait_ref(&&p: @trait_ref, fld: ast_fold) -> @trait_ref {
 fn fold_struct_field(&&f: @struct_field, fld: ast_fold) -> @struct_field {
     @spanned { node: ast::struct_field_ { kind: copy f.node.kind,
                                           id: fld.new_id(f.node.id),
-                                          ty: fld.fold_ty(f.node.ty) },
+                                          ty: fld.fold_ty(f.node.ty),
+                                          attrs: copy f.node.attrs },
                span: fld.new_span(f.span) }
 }
 
@@ -721,6 +724,7 @@ pub impl ast_fold_fns: ast_fold {
                 kind: copy sf.node.kind,
                 id: sf.node.id,
                 ty: (self as ast_fold).fold_ty(sf.node.ty),
+                attrs: copy sf.node.attrs,
             },
             span: (self.new_span)(sf.span),
         }
@@ -125,6 +125,13 @@ impl Parser: parser_attr {
     }
 
     fn parse_meta_item() -> @ast::meta_item {
+        match self.token {
+          token::INTERPOLATED(token::nt_meta(mi)) => {
+            self.bump();
+            return mi;
+          }
+          _ => ()
+        }
         let lo = self.span.lo;
         let name = *self.id_to_str(self.parse_ident());
         match self.token {
@@ -52,6 +52,9 @@ pub type parse_sess = @{
     mut next_id: node_id,
     span_diagnostic: span_handler,
     interner: @ident_interner,
+    // Files read by syntax extensions such as include_str!, which the
+    // output depends on without their being parsed
+    mut included_files: ~[Path],
 };
 
 pub fn new_parse_sess(demitter: Option<Emitter>) -> parse_sess {
@@ -60,6 +63,7 @@ pub fn new_parse_sess(demitter: Option<Emitter>) -> parse_sess {
              mut next_id: 1,
              span_diagnostic: mk_span_handler(mk_handler(demitter), cm),
              interner: mk_ident_interner(),
+             mut included_files: ~[],
             };
 }
 
@@ -69,6 +73,7 @@ pub fn new_parse_sess_special_handler(sh: span_handler, cm: @codemap::CodeMap)
              mut next_id: 1,
              span_diagnostic: sh,
              interner: mk_ident_interner(),
+             mut included_files: ~[],
              };
 }
 
@@ -218,7 +223,7 @@ pub fn new_sub_parser_from_file(sess: parse_sess, cfg: ast::crate_cfg,
 pub fn new_parser_from_tts(sess: parse_sess, cfg: ast::crate_cfg,
                        tts: ~[ast::token_tree]) -> Parser {
     let trdr = lexer::new_tt_reader(sess.span_diagnostic, sess.interner,
-                                    None, tts);
+                                    None, tts, None);
     return Parser(sess, cfg, trdr as reader)
 }
 
@@ -812,6 +812,14 @@ pub impl Parser {
     }
 
     fn parse_lit() -> lit {
+        // a `$x:lit` macro fragment
+        match self.token {
+          INTERPOLATED(token::nt_expr(e)) => match e.node {
+            expr_lit(lit) => { self.bump(); return /*bad*/copy *lit; }
+            _ => ()
+          },
+          _ => ()
+        }
         let lo = self.span.lo;
         let lit = if self.eat_keyword(~"true") {
             lit_bool(true)
@@ -2232,7 +2240,8 @@ pub impl Parser {
     }
 
     /* assumes "let" token has already been consumed */
-    fn parse_instance_var(pr: visibility) -> @struct_field {
+    fn parse_instance_var(pr: visibility, +attrs: ~[attribute])
+                       -> @struct_field {
         let mut is_mutbl = struct_immutable;
         let lo = self.span.lo;
         if self.eat_keyword(~"mut") {
@@ -2247,7 +2256,8 @@ pub impl Parser {
         @spanned(lo, self.last_span.hi, ast::struct_field_ {
             kind: named_field(name, is_mutbl, pr),
             id: self.get_id(),
-            ty: ty
+            ty: ty,
+            attrs: attrs
         })
     }
 
@@ -2958,10 +2968,12 @@ pub impl Parser {
                                                  seq_sep_trailing_allowed
                                                     (token::COMMA)) |p| {
                 let lo = p.span.lo;
+                let attrs = p.parse_outer_attributes();
                 let struct_field_ = ast::struct_field_ {
                     kind: unnamed_field,
                     id: self.get_id(),
-                    ty: p.parse_ty(false)
+                    ty: p.parse_ty(false),
+                    attrs: attrs
                 };
                 @spanned(lo, p.span.hi, struct_field_)
             };
@@ -3001,7 +3013,8 @@ pub impl Parser {
         }
     }
 
-    fn parse_single_class_item(vis: visibility) -> @struct_field {
+    fn parse_single_class_item(vis: visibility, +attrs: ~[attribute])
+                            -> @struct_field {
         let obsolete_let = self.eat_obsolete_ident("let");
         if obsolete_let { self.obsolete(copy self.last_span, ObsoleteLet) }
 
@@ -3011,7 +3024,7 @@ pub impl Parser {
               && !self.token_is_pound_or_doc_comment(copy self.token));
 
         if !parse_obsolete_method {
-            let a_var = self.parse_instance_var(vis);
+            let a_var = self.parse_instance_var(vis, attrs);
             match self.token {
               token::SEMI => {
                 self.obsolete(copy self.span, ObsoleteFieldTerminator);
@@ -3044,7 +3057,8 @@ pub impl Parser {
                         id: self.get_id(),
                         node: ty_nil,
                         span: copy self.span,
-                    }
+                    },
+                    attrs: ~[]
                 }
             )
         }
@@ -3062,16 +3076,16 @@ pub impl Parser {
             return members(~[]);
         }
 
+        let attrs = self.parse_outer_attributes();
+
         if self.eat_keyword(~"priv") {
-            return members(~[self.parse_single_class_item(private)])
+            return members(~[self.parse_single_class_item(private, attrs)])
         }
 
         if self.eat_keyword(~"pub") {
-           return members(~[self.parse_single_class_item(public)]);
+           return members(~[self.parse_single_class_item(public, attrs)]);
         }
 
-        let attrs = self.parse_outer_attributes();
-
         if self.try_parse_obsolete_struct_ctor() {
             return members(~[]);
         }
@@ -3080,7 +3094,8 @@ pub impl Parser {
            return self.parse_dtor(attrs);
         }
         else {
-           return members(~[self.parse_single_class_item(inherited)]);
+           return members(~[self.parse_single_class_item(inherited,
+                                                          attrs)]);
         }
     }
 
@@ -4011,7 +4026,8 @@ pub impl Parser {
         @spanned(lo, self.span.lo,
                  ast::crate_ { module: m,
                                attrs: crate_attrs.inner,
-                               config: self.cfg })
+                               config: self.cfg,
+                               exported_macros: ~[] })
     }
 
     fn parse_str() -> @~str {
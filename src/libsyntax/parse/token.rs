@@ -19,6 +19,7 @@ use util::interner;
 use core::cast;
 use core::char;
 use core::cmp;
+use core::dvec::DVec;
 use core::str;
 use core::task;
 use std::oldmap::HashMap;
@@ -110,7 +111,9 @@ pub enum nonterminal {
     nt_ident(ast::ident, bool),
     nt_path(@ast::path),
     nt_tt(  @ast::token_tree), //needs @ed to break a circularity
-    nt_matchers(~[ast::matcher])
+    nt_matchers(~[ast::matcher]),
+    nt_meta(@ast::meta_item),
+    nt_lifetime(ast::ident)
 }
 
 pub fn binop_to_str(o: binop) -> ~str {
@@ -215,7 +218,9 @@ pub fn to_str(in: @ident_interner, t: Token) -> ~str {
                       nt_ident(*) => ~"identifier",
                       nt_path(*) => ~"path",
                       nt_tt(*) => ~"tt",
-                      nt_matchers(*) => ~"matcher sequence"
+                      nt_matchers(*) => ~"matcher sequence",
+                      nt_meta(*) => ~"meta item",
+                      nt_lifetime(*) => ~"lifetime"
                     }
             }
         }
@@ -304,62 +309,67 @@ pub pure fn is_bar(t: Token) -> bool {
 pub mod special_idents {
     use ast::ident;
 
-    pub const underscore : ident = ident { repr: 0u };
-    pub const anon : ident = ident { repr: 1u };
-    pub const dtor : ident = ident { repr: 2u }; // 'drop', but that's
-                                                 // reserved
-    pub const invalid : ident = ident { repr: 3u }; // ''
-    pub const unary : ident = ident { repr: 4u };
-    pub const not_fn : ident = ident { repr: 5u };
-    pub const idx_fn : ident = ident { repr: 6u };
-    pub const unary_minus_fn : ident = ident { repr: 7u };
-    pub const clownshoes_extensions : ident = ident { repr: 8u };
+    pub const underscore : ident = ident { repr: 0u, ctxt: 0 };
+    pub const anon : ident = ident { repr: 1u, ctxt: 0 };
+    pub const dtor : ident = ident { repr: 2u, ctxt: 0 }; // 'drop', but
+                                                           // that's reserved
+    pub const invalid : ident = ident { repr: 3u, ctxt: 0 }; // ''
+    pub const unary : ident = ident { repr: 4u, ctxt: 0 };
+    pub const not_fn : ident = ident { repr: 5u, ctxt: 0 };
+    pub const idx_fn : ident = ident { repr: 6u, ctxt: 0 };
+    pub const unary_minus_fn : ident = ident { repr: 7u, ctxt: 0 };
+    pub const clownshoes_extensions : ident = ident { repr: 8u, ctxt: 0 };
 
-    pub const self_ : ident = ident { repr: 9u }; // 'self'
+    pub const self_ : ident = ident { repr: 9u, ctxt: 0 }; // 'self'
 
     /* for matcher NTs */
-    pub const item : ident = ident { repr: 10u };
-    pub const block : ident = ident { repr: 11u };
-    pub const stmt : ident = ident { repr: 12u };
-    pub const pat : ident = ident { repr: 13u };
-    pub const expr : ident = ident { repr: 14u };
-    pub const ty : ident = ident { repr: 15u };
-    pub const ident : ident = ident { repr: 16u };
-    pub const path : ident = ident { repr: 17u };
-    pub const tt : ident = ident { repr: 18u };
-    pub const matchers : ident = ident { repr: 19u };
-
-    pub const str : ident = ident { repr: 20u }; // for the type
+    pub const item : ident = ident { repr: 10u, ctxt: 0 };
+    pub const block : ident = ident { repr: 11u, ctxt: 0 };
+    pub const stmt : ident = ident { repr: 12u, ctxt: 0 };
+    pub const pat : ident = ident { repr: 13u, ctxt: 0 };
+    pub const expr : ident = ident { repr: 14u, ctxt: 0 };
+    pub const ty : ident = ident { repr: 15u, ctxt: 0 };
+    pub const ident : ident = ident { repr: 16u, ctxt: 0 };
+    pub const path : ident = ident { repr: 17u, ctxt: 0 };
+    pub const tt : ident = ident { repr: 18u, ctxt: 0 };
+    pub const matchers : ident = ident { repr: 19u, ctxt: 0 };
+
+    pub const str : ident = ident { repr: 20u, ctxt: 0 }; // for the type
 
     /* outside of libsyntax */
-    pub const ty_visitor : ident = ident { repr: 21u };
-    pub const arg : ident = ident { repr: 22u };
-    pub const descrim : ident = ident { repr: 23u };
-    pub const clownshoe_abi : ident = ident { repr: 24u };
-    pub const clownshoe_stack_shim : ident = ident { repr: 25u };
-    pub const tydesc : ident = ident { repr: 26u };
-    pub const literally_dtor : ident = ident { repr: 27u };
-    pub const main : ident = ident { repr: 28u };
-    pub const opaque : ident = ident { repr: 29u };
-    pub const blk : ident = ident { repr: 30u };
-    pub const static : ident = ident { repr: 31u };
-    pub const intrinsic : ident = ident { repr: 32u };
-    pub const clownshoes_foreign_mod: ident = ident { repr: 33 };
-    pub const unnamed_field: ident = ident { repr: 34 };
-    pub const c_abi: ident = ident { repr: 35 };
-    pub const type_self: ident = ident { repr: 36 };    // `Self`
+    pub const ty_visitor : ident = ident { repr: 21u, ctxt: 0 };
+    pub const arg : ident = ident { repr: 22u, ctxt: 0 };
+    pub const descrim : ident = ident { repr: 23u, ctxt: 0 };
+    pub const clownshoe_abi : ident = ident { repr: 24u, ctxt: 0 };
+    pub const clownshoe_stack_shim : ident = ident { repr: 25u, ctxt: 0 };
+    pub const tydesc : ident = ident { repr: 26u, ctxt: 0 };
+    pub const literally_dtor : ident = ident { repr: 27u, ctxt: 0 };
+    pub const main : ident = ident { repr: 28u, ctxt: 0 };
+    pub const opaque : ident = ident { repr: 29u, ctxt: 0 };
+    pub const blk : ident = ident { repr: 30u, ctxt: 0 };
+    pub const static : ident = ident { repr: 31u, ctxt: 0 };
+    pub const intrinsic : ident = ident { repr: 32u, ctxt: 0 };
+    pub const clownshoes_foreign_mod: ident = ident { repr: 33, ctxt: 0 };
+    pub const unnamed_field: ident = ident { repr: 34, ctxt: 0 };
+    pub const c_abi: ident = ident { repr: 35, ctxt: 0 };
+    pub const type_self: ident = ident { repr: 36, ctxt: 0 };    // `Self`
 }
 
 pub struct ident_interner {
     priv interner: Interner<@~str>,
+    /* the syntax context table, indexed by ast::SyntaxContext */
+    priv ctxts: DVec<ast::SyntaxContext_>,
+    priv marked_ctxts: HashMap<(uint, uint), ast::SyntaxContext>,
+    priv hygienic_names: HashMap<(uint, uint), ast::ident>,
+    priv next_mark: @mut uint,
 }
 
 pub impl ident_interner {
     fn intern(val: @~str) -> ast::ident {
-        ast::ident { repr: self.interner.intern(val) }
+        ast::ident { repr: self.interner.intern(val), ctxt: ast::empty_ctxt }
     }
     fn gensym(val: @~str) -> ast::ident {
-        ast::ident { repr: self.interner.gensym(val) }
+        ast::ident { repr: self.interner.gensym(val), ctxt: ast::empty_ctxt }
     }
     pure fn get(idx: ast::ident) -> @~str {
         self.interner.get(idx.repr)
@@ -367,6 +377,63 @@ pub impl ident_interner {
     fn len() -> uint {
         self.interner.len()
     }
+
+    /// A mark that no other macro expansion has used
+    fn fresh_mark() -> uint {
+        *self.next_mark += 1;
+        *self.next_mark
+    }
+
+    /// The identifier as transcribed by the expansion with the given mark
+    fn apply_mark(id: ast::ident, mark: uint) -> ast::ident {
+        let key = (mark, id.ctxt);
+        let ctxt = match self.marked_ctxts.find(&key) {
+            Some(ctxt) => ctxt,
+            None => {
+                let ctxt = self.ctxts.len();
+                self.ctxts.push(ast::Mark(mark, id.ctxt));
+                self.marked_ctxts.insert(key, ctxt);
+                ctxt
+            }
+        };
+        ast::ident { repr: id.repr, ctxt: ctxt }
+    }
+
+    /// The context the outermost mark of `ctxt` was applied to, if any
+    fn outer_ctxt(ctxt: ast::SyntaxContext) -> Option<ast::SyntaxContext> {
+        match self.ctxts.get_elt(ctxt) {
+            ast::EmptyCtxt => None,
+            ast::Mark(_, parent) => Some(parent)
+        }
+    }
+
+    /// An identifier, with an empty context, that is equal to the result for
+    /// another identifier exactly when both have the same name and context.
+    /// Identifiers written in the source map to themselves.
+    fn hygienic_name(id: ast::ident) -> ast::ident {
+        if id.ctxt == ast::empty_ctxt { return id; }
+        let key = (id.repr, id.ctxt);
+        match self.hygienic_names.find(&key) {
+            Some(name) => name,
+            None => {
+                let name = self.gensym(self.get(id));
+                self.hygienic_names.insert(key, name);
+                name
+            }
+        }
+    }
+}
+
+fn mk_ident_interner_(+interner: Interner<@~str>) -> @ident_interner {
+    let ctxts = DVec();
+    ctxts.push(ast::EmptyCtxt);
+    @ident_interner {
+        interner: interner,
+        ctxts: ctxts,
+        marked_ctxts: HashMap(),
+        hygienic_names: HashMap(),
+        next_mark: @mut 0
+    }
 }
 
 /* Key for thread-local data for sneaking interner information to the
@@ -426,9 +493,7 @@ pub fn mk_ident_interner() -> @ident_interner {
                     @~"Self",               // 36
                 ];
 
-                let rv = @ident_interner {
-                    interner: interner::mk_prefill(init_vec)
-                };
+                let rv = mk_ident_interner_(interner::mk_prefill(init_vec));
 
                 task::local_data::local_data_set(interner_key!(), @rv);
 
@@ -441,7 +506,7 @@ pub fn mk_ident_interner() -> @ident_interner {
 /* for when we don't care about the contents; doesn't interact with TLD or
    serialization */
 pub fn mk_fake_ident_interner() -> @ident_interner {
-    @ident_interner { interner: interner::mk::<@~str>() }
+    mk_ident_interner_(interner::mk::<@~str>())
 }
 
 /**
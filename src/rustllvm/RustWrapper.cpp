@@ -465,6 +465,34 @@ extern "C" LLVMModuleRef LLVMRustParseBitcode(LLVMMemoryBufferRef MemBuf) {
          ? NULL : M;
 }
 
+extern "C" LLVMModuleRef
+LLVMRustParseBitcodeInContext(LLVMContextRef C, LLVMMemoryBufferRef MemBuf) {
+  LLVMModuleRef M;
+  return LLVMParseBitcodeInContext(C, MemBuf, &M,
+                                   const_cast<char **>(&LLVMRustError))
+         ? NULL : M;
+}
+
+// Turns a function or global variable into an external declaration.
+extern "C" void LLVMRustMakeDeclaration(LLVMValueRef V) {
+  GlobalValue *GV = unwrap<GlobalValue>(V);
+  if (Function *F = dyn_cast<Function>(GV))
+    F->deleteBody();
+  else if (GlobalVariable *G = dyn_cast<GlobalVariable>(GV))
+    G->setInitializer(NULL);
+  GV->setLinkage(GlobalValue::ExternalLinkage);
+}
+
+extern "C" bool LLVMRustLinkModules(LLVMModuleRef Dest, LLVMModuleRef Src) {
+  std::string Err;
+  if (Linker::LinkModules(unwrap(Dest), unwrap(Src), Linker::DestroySource,
+                          &Err)) {
+    LLVMRustError = strdup(Err.c_str());
+    return false;
+  }
+  return true;
+}
+
 extern "C" LLVMValueRef LLVMRustConstSmallInt(LLVMTypeRef IntTy, unsigned N,
                                               LLVMBool SignExtend) {
   return LLVMConstInt(IntTy, (unsigned long long)N, SignExtend);
@@ -0,0 +1,29 @@
+// Copyright 2012 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#[link(name = "macro_export_lib", vers = "0.1")];
+#[crate_type = "lib"];
+
+#[macro_export]
+macro_rules! square (
+    ($x:expr) => ({ let y = $x; y * y })
+)
+
+#[macro_export]
+macro_rules! sum (
+    ($($x:expr),+) => (0 $(+ $x)+)
+)
+
+// Not exported: only usable inside this crate
+macro_rules! cube (
+    ($x:expr) => (square!($x) * $x)
+)
+
+pub fn twenty_seven() -> int { cube!(3) }
@@ -0,0 +1,31 @@
+// Copyright 2013 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#[link(name = "syntax_extension_lib", vers = "0.1")];
+#[crate_type = "lib"];
+#[syntax_extension];
+
+extern mod syntax;
+
+use syntax::ast;
+use syntax::codemap::span;
+use syntax::ext::base::{ext_ctxt, MacResult, MRExpr, SyntaxExtension};
+use syntax::ext::base::{builtin_normal_tt, check_zero_tts};
+use syntax::ext::build;
+
+pub fn register_syntax_extensions(register: fn@(~str, SyntaxExtension)) {
+    register(~"forty_two", builtin_normal_tt(expand_forty_two));
+}
+
+fn expand_forty_two(cx: ext_ctxt, sp: span, tts: ~[ast::token_tree])
+                 -> MacResult {
+    check_zero_tts(cx, sp, tts, "forty_two!");
+    MRExpr(build::mk_uint(cx, sp, 42))
+}
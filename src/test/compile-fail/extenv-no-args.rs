@@ -8,6 +8,6 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-// error-pattern: env! takes 1 argument
+// error-pattern: env! takes 1 or 2 arguments
 
 fn main() { env!(); }
@@ -8,6 +8,8 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+#[allow(dead_code)];
+
 fn f() -> ! {
     return 42i; //~ ERROR expected `!` but found `int`
     die!(); //~ WARNING unreachable statement
@@ -0,0 +1,62 @@
+// Copyright 2013 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#[deny(dead_code)];
+
+const used_const: int = 1;
+const unused_const: int = 2; //~ ERROR constant is never used: `unused_const`
+
+struct Used { x: int }
+struct Unused { x: int } //~ ERROR struct is never used: `Unused`
+
+enum Shape {
+    Circle(float),
+    Square(float), //~ ERROR variant is never used: `Square`
+}
+
+enum Unreachable { A, B } //~ ERROR enum is never used: `Unreachable`
+
+impl Used {
+    fn get(&self) -> int { self.x }
+    fn unused_method(&self) -> int { self.x } //~ ERROR method is never used: `unused_method`
+}
+
+impl Used : ToStr {
+    pure fn to_str(&self) -> ~str { helper_for_trait() }
+}
+
+fn helper_for_trait() -> ~str { ~"used" }
+
+fn called() -> int { used_const }
+
+fn only_called_from_dead() -> int { 0 } //~ ERROR function is never used: `only_called_from_dead`
+
+fn dead() -> int { only_called_from_dead() } //~ ERROR function is never used: `dead`
+
+#[test]
+fn a_test() {}
+
+// Public items are used by whoever uses the crate
+pub fn public_unused() {}
+
+pub struct PublicUnused { x: int }
+
+impl Used {
+    pub fn public_method(&self) -> int { self.x }
+}
+
+fn main() {
+    let u = Used { x: called() };
+    let _ = u.get();
+    match Circle(1.0) {
+        Circle(_) => (),
+        _ => ()
+    }
+}
@@ -0,0 +1,29 @@
+// Copyright 2013 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#[forbid(deprecated)]
+mod a {
+    trait T {
+        fn f(); //~ ERROR this method form is deprecated
+    }
+
+    #[allow(deprecated)]
+    mod b {
+        //~^ ERROR allow(deprecated) overruled by outer forbid(deprecated_mode)
+        //~^^ ERROR allow(deprecated) overruled by outer forbid(deprecated_pattern)
+        //~^^^ ERROR allow(deprecated) overruled by outer forbid(deprecated_self)
+        trait U {
+            fn g();
+        }
+    }
+}
+
+fn main() {
+}
@@ -8,6 +8,7 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+#[allow(dead_code)];
 #[forbid(non_camel_case_types)];
 
 struct foo { //~ ERROR type, variant, or trait should have a camel case identifier
@@ -0,0 +1,46 @@
+// Copyright 2013 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#[deny(unused_result)];
+
+#[must_use]
+struct Token { x: int }
+
+struct Plain { x: int }
+
+fn fallible() -> Result<int, ~str> { Ok(1) }
+fn token() -> Token { Token { x: 1 } }
+fn plain() -> Plain { Plain { x: 1 } }
+
+#[must_use]
+fn important() -> int { 1 }
+
+impl Plain {
+    #[must_use]
+    fn checked(&self) -> int { self.x }
+    fn unchecked(&self) -> int { self.x }
+}
+
+fn main() {
+    fallible(); //~ ERROR unused result of type
+    token(); //~ ERROR unused result of type
+    important(); //~ ERROR unused return value of `important` which must be used
+    plain().checked(); //~ ERROR unused return value of
+    plain();
+    plain().unchecked();
+
+    let _ = fallible();
+    let _t = token();
+    let _x = important();
+
+    #[allow(unused_result)]
+    fn allowed() { fallible(); }
+    allowed();
+}
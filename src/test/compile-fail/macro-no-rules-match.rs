@@ -0,0 +1,19 @@
+// Copyright 2013 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+macro_rules! point(
+    ($x:expr) => ($x);
+    ($x:expr, $y:expr) => ($x + $y) //~ NOTE rule 2 of `point!` matched
+)
+
+fn main() {
+    point!(1, 2; 3);
+    //~^ ERROR no rules expected the token `;`; expected one of `,` or the end
+}
@@ -0,0 +1,37 @@
+// Copyright 2013 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+fn main() {
+    let x: Option<Either<int, int>> = None;
+    match x { //~ ERROR non-exhaustive patterns: `Some(Left(_))` not covered
+      None => {}
+      Some(Right(_)) => {}
+    }
+    match (true, Some(1)) { //~ ERROR non-exhaustive patterns: `(true, None)` not covered
+      (false, _) => {}
+      (true, Some(_)) => {}
+    }
+    match ~[1, 2, 3] { //~ ERROR non-exhaustive patterns: `[_, _, _]` not covered
+      [] => {}
+      [_] => {}
+      [_, _] => {}
+      [_, _, _, _, .._rest] => {}
+    }
+    match Some(1) {
+      Some(_) => {}
+      None => {}
+      Some(2) => {} //~ ERROR unreachable pattern: arm 3 is never matched
+    }
+    match 1 {
+      1 | 2 => {}
+      3 | 1 => {} //~ ERROR unreachable pattern: alternative 2 of arm 2 is never matched
+      _ => {}
+    }
+}
@@ -0,0 +1,27 @@
+// Copyright 2013 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+mod geometry {
+    pub mod shapes {
+        pub fn circle_area(r: float) -> float { 3.14 * r * r }
+    }
+}
+
+use geometry::shapes::circle_aera;
+//~^ ERROR failed to resolve import
+//~^^ NOTE did you mean `circle_area`?
+
+fn main() {
+    let counter = 0;
+    log(debug, countr); //~ ERROR unresolved name: countr
+    //~^ NOTE did you mean `counter`?
+    log(debug, circle_area(1.0)); //~ ERROR unresolved name: circle_area
+    //~^ NOTE `circle_area` is defined at `geometry::shapes::circle_area`; consider adding `use geometry::shapes::circle_area;`
+}
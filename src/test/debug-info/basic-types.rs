@@ -0,0 +1,43 @@
+// Copyright 2013 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// compile-flags:-g
+// debugger:break _zzz
+// debugger:run
+// debugger:finish
+// debugger:print b
+// check:$1 = true
+// debugger:print i
+// check:$2 = -1
+// debugger:print u
+// check:$3 = 2
+// debugger:print f
+// check:$4 = 1.5
+// debugger:print t
+// check:$5 = {__0 = 3, __1 = false}
+// debugger:print p
+// check:$6 = {x = 4, y = 5}
+
+struct Point {
+    x: int,
+    y: int
+}
+
+fn main() {
+    let b: bool = true;
+    let i: int = -1;
+    let u: uint = 2;
+    let f: float = 1.5;
+    let t = (3, false);
+    let p = Point { x: 4, y: 5 };
+    _zzz();
+}
+
+fn _zzz() {()}
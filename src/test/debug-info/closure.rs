@@ -0,0 +1,35 @@
+// Copyright 2013 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// compile-flags:-g
+// debugger:break _zzz
+// debugger:run
+// debugger:finish
+// debugger:print x
+// check:$1 = 1
+// debugger:print y
+// check:$2 = 2.5
+// debugger:print z
+// check:$3 = 3
+
+fn call(f: fn(int)) {
+    f(3);
+}
+
+fn main() {
+    let x = 1;
+    let y = 2.5;
+    do call |z| {
+        _zzz();
+        let _ = (x, y, z);
+    }
+}
+
+fn _zzz() {()}
@@ -0,0 +1,46 @@
+// Copyright 2013 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// compile-flags:-g
+// debugger:break _zzz
+// debugger:run
+// debugger:finish
+// debugger:print a
+// check:$1 = Green
+// debugger:print b.Rect
+// check:$2 = {discriminant = Rect, __0 = 3, __1 = 4}
+// debugger:print c.Circle
+// check:$3 = {discriminant = Circle, __0 = 5}
+// debugger:print d.Wrapper
+// check:$4 = {__0 = 6}
+
+enum Color {
+    Red,
+    Green
+}
+
+enum Shape {
+    Circle(int),
+    Rect(int, int)
+}
+
+enum Wrapper {
+    Wrapper(int)
+}
+
+fn main() {
+    let a = Green;
+    let b = Rect(3, 4);
+    let c = Circle(5);
+    let d = Wrapper(6);
+    _zzz();
+}
+
+fn _zzz() {()}
@@ -0,0 +1,32 @@
+// Copyright 2013 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// compile-flags:-g
+// debugger:break _zzz
+// debugger:run
+// debugger:finish
+// debugger:print a->boxed.data[1]
+// check:$1 = 2
+// debugger:print b->boxed.data[2]
+// check:$2 = 6
+// debugger:print c
+// check:$3 = {7, 8, 9}
+// debugger:print d.data_ptr[0]
+// check:$4 = 10
+
+fn main() {
+    let a = ~[1, 2, 3];
+    let b = @[4, 5, 6];
+    let c = [7, 8, 9];
+    let d: &[int] = &[10, 11];
+    _zzz();
+}
+
+fn _zzz() {()}
@@ -0,0 +1,22 @@
+// Copyright 2013 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+macro_rules! inner (
+    ($e:expr) => ($e + 1)
+)
+
+// The expansion of `outer!` calls `inner!`
+macro_rules! outer (
+    ($e:expr) => (inner!($e) * 2)
+)
+
+fn main() {
+    assert outer!(1) == 4;
+}
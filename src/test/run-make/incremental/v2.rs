@@ -0,0 +1,36 @@
+// Copyright 2013 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Version 2 of the crate; see the Makefile
+
+// Warned about once per build, however many functions are reused
+#[warn(no_such_lint)];
+
+fn double(x: int) -> int { x * 3 }
+
+fn twice(x: int) -> int { double(x) }
+
+struct Seven { n: int }
+
+impl Seven {
+    fn get(&self) -> int { self.n }
+}
+
+// Only a reused function refers to `Seven` and its method
+fn unrelated() -> int {
+    let seven = Seven { n: 7 };
+    seven.get()
+}
+
+fn unused() {}
+
+fn main() {
+    io::println(fmt!("%d %d", twice(5), unrelated()));
+}
@@ -0,0 +1,24 @@
+# xfail-license
+
+# Checks the warning that warn.rs gets from the while_true lint, as
+# written by rustc --error-format json to the file given as argument
+
+import json
+import sys
+
+diagnostics = [json.loads(line) for line in open(sys.argv[1])]
+assert len(diagnostics) == 1, diagnostics
+d = diagnostics[0]
+
+assert d['level'] == 'warning', d
+assert d['lint'] == 'while_true', d
+assert d['message'] == 'denote infinite loops with loop { ... }', d
+assert d['notes'] == [], d
+
+assert len(d['spans']) == 1, d
+span = d['spans'][0]
+assert span['file_name'].endswith('warn.rs'), span
+assert span['is_primary'], span
+assert (span['line_start'], span['column_start']) == (12, 4), span
+assert (span['line_end'], span['column_end']) == (14, 5), span
+assert span['byte_end'] > span['byte_start'], span
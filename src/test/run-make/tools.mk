@@ -0,0 +1,14 @@
+# Included by the Makefile of every run-make test; see
+# src/etc/maketest.py for the variables that are set for them.
+
+export LD_LIBRARY_PATH:=$(TMPDIR):$(TARGET_LIB_DIR):$(LD_LIBRARY_PATH)
+export DYLD_LIBRARY_PATH:=$(TMPDIR):$(TARGET_LIB_DIR):$(DYLD_LIBRARY_PATH)
+
+# Runs the program $(1) that was built in $(TMPDIR)
+RUN = $(TMPDIR)/$(1)
+
+# Fails unless the file $(2) has a line matching the regexp $(1)
+HAS = grep -q -e $(1) $(2) || (echo "expected $(1) in $(2)" && false)
+
+# Fails if the file $(2) has a line matching the regexp $(1)
+HAS_NO = ! grep -q -e $(1) $(2) || (echo "unexpected $(1) in $(2)" && false)
@@ -0,0 +1,56 @@
+// Copyright 2013 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+extern mod std;
+
+use core::result;
+use std::json;
+use std::serialize::{Encodable, Decodable};
+
+#[auto_encode]
+#[auto_decode]
+struct Config {
+    #[serial_name = "user-name"]
+    name: ~str,
+    #[serial_skip]
+    cache: ~[~str],
+    #[serial_default]
+    retries: uint,
+    #[serial_default = "~[~\"localhost\"]"]
+    hosts: ~[~str],
+}
+
+fn decode(s: &str) -> Config {
+    let json = result::unwrap(json::from_str(s));
+    Decodable::decode(&json::Decoder(move json))
+}
+
+fn encode(c: &Config) -> ~str {
+    do io::with_str_writer |wr| { c.encode(&json::Encoder(wr)) }
+}
+
+pub fn main() {
+    let c = decode("{\"user-name\": \"bob\"}");
+    assert c.name == ~"bob";
+    assert c.cache.is_empty();
+    assert c.retries == 0;
+    assert c.hosts == ~[~"localhost"];
+
+    let c = decode("{\"user-name\": \"bob\", \"cache\": [\"x\"], \
+                    \"retries\": 3, \"hosts\": [\"a\", \"b\"]}");
+    assert c.cache.is_empty();
+    assert c.retries == 3;
+    assert c.hosts == ~[~"a", ~"b"];
+
+    let c = Config { name: ~"eve", cache: ~[~"x"], retries: 1,
+                     hosts: ~[~"h"] };
+    assert encode(&c) ==
+        ~"{\"user-name\":\"eve\",\"retries\":1,\"hosts\":[\"h\"]}";
+}
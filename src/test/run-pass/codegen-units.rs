@@ -0,0 +1,48 @@
+// Copyright 2013 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// compile-flags:--codegen-units 3 -O
+
+// Internal functions, glue and constants end up in different units and
+// must still link against each other.
+
+struct Counter {
+    count: @mut uint
+}
+
+impl Counter : Drop {
+    fn finalize(&self) { *self.count += 1; }
+}
+
+const greeting: &static/str = "hello";
+
+fn first<T: Copy>(v: &[T]) -> T { v[0] }
+
+fn sum(v: &[int]) -> int {
+    let mut total = 0;
+    for v.each |x| { total += *x; }
+    total
+}
+
+fn apply(f: fn(int) -> int, x: int) -> int { f(x) }
+
+pub fn main() {
+    assert sum(~[1, 2, 3]) == 6;
+    assert first(~[3, 4]) == 3;
+    assert apply(|x| x * 2, 21) == 42;
+    assert greeting.len() == 5;
+
+    let count = @mut 0;
+    {
+        let _a = Counter { count: count };
+        let _b = Counter { count: count };
+    }
+    assert *count == 2;
+}
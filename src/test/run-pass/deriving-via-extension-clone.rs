@@ -0,0 +1,36 @@
+// Copyright 2013 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#[deriving_eq]
+#[deriving_clone]
+struct Point {
+    x: int,
+    y: int,
+    name: ~str
+}
+
+#[deriving_eq]
+#[deriving_clone]
+enum Shape<T> {
+    Empty,
+    Dot(T),
+    Line { from: T, to: T }
+}
+
+pub fn main() {
+    let p = Point { x: 1, y: 2, name: ~"origin" };
+    let q = p.clone();
+    assert p == q;
+
+    let shapes = ~[ Empty, Dot(3), Line { from: 1, to: 4 } ];
+    for shapes.each |shape| {
+        assert shape.clone() == *shape;
+    }
+}
@@ -0,0 +1,45 @@
+// Copyright 2013 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#[deriving_eq]
+#[deriving_ord]
+struct Version {
+    major: int,
+    minor: int
+}
+
+#[deriving_eq]
+#[deriving_ord]
+enum Shape {
+    Point,
+    Circle(int),
+    Rect { w: int, h: int }
+}
+
+pub fn main() {
+    let v1_0 = Version { major: 1, minor: 0 };
+    let v1_2 = Version { major: 1, minor: 2 };
+    let v2_0 = Version { major: 2, minor: 0 };
+    assert v1_0 < v1_2;
+    assert v1_2 < v2_0;
+    assert v2_0 > v1_0;
+    assert v1_2 <= v1_2;
+    assert v1_2 >= v1_2;
+    assert !(v1_2 < v1_2);
+    assert !(v2_0 <= v1_2);
+
+    // Variants compare in declaration order, then by their fields
+    assert Point < Circle(0);
+    assert Circle(5) < Rect { w: 0, h: 0 };
+    assert Circle(1) < Circle(2);
+    assert Rect { w: 1, h: 2 } > Rect { w: 1, h: 1 };
+    assert Point <= Point;
+    assert !(Rect { w: 0, h: 0 } < Point);
+}
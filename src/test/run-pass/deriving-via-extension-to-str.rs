@@ -0,0 +1,32 @@
+// Copyright 2013 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#[deriving_to_str]
+struct Point {
+    x: int,
+    y: int
+}
+
+#[deriving_to_str]
+enum Shape {
+    Empty,
+    Circle(Point, uint),
+    Line { from: Point, to: Point }
+}
+
+pub fn main() {
+    let p = Point { x: 1, y: 2 };
+    assert p.to_str() == ~"Point { x: 1, y: 2 }";
+    assert Empty.to_str() == ~"Empty";
+    assert Circle(p, 3).to_str() == ~"Circle(Point { x: 1, y: 2 }, 3)";
+    let line = Line { from: p, to: Point { x: 0, y: 0 } };
+    assert line.to_str() ==
+        ~"Line { from: Point { x: 1, y: 2 }, to: Point { x: 0, y: 0 } }";
+}
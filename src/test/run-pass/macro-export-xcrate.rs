@@ -0,0 +1,21 @@
+// Copyright 2012 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// xfail-fast - check-fast doesn't understand aux-build
+// aux-build:macro_export_lib.rs
+
+extern mod macro_export_lib;
+
+pub fn main() {
+    let y = 2;
+    assert square!(y + 1) == 9;
+    assert sum!(1, 2, square!(3)) == 12;
+    assert macro_export_lib::twenty_seven() == 27;
+}
@@ -0,0 +1,57 @@
+// Copyright 2013 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// xfail-pretty - token trees can't pretty print
+
+// Tests the `lit`, `meta`, `stmt` and `lifetime` fragment specifiers
+
+macro_rules! lit_str(
+    ($l:lit) => (stringify!($l))
+)
+
+macro_rules! doc_fn(
+    ($doc:lit, $name:ident) => (
+        #[doc = $doc]
+        fn $name() -> int { 1 }
+    )
+)
+
+macro_rules! with_attr(
+    ($m:meta, $name:ident) => (
+        #[$m]
+        fn $name() -> int { 2 }
+    )
+)
+
+macro_rules! twice(
+    ($s:stmt) => ({ $s; $s; })
+)
+
+macro_rules! lifetime_name(
+    ($l:lifetime) => (stringify!($l))
+)
+
+doc_fn!("Returns one", one)
+with_attr!(inline(always), two)
+with_attr!(allow(non_camel_case_types), also_two)
+
+pub fn main() {
+    assert lit_str!(10) == "10";
+    assert lit_str!("hi") == "\"hi\"";
+    assert lit_str!(true) == "true";
+
+    assert one() + two() + also_two() == 5;
+
+    let mut n = 0;
+    twice!(n += 3);
+    assert n == 6;
+
+    assert lifetime_name!('a) == "'a";
+}
@@ -0,0 +1,48 @@
+// Copyright 2013 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// xfail-pretty - token trees can't pretty print
+
+// A temporary introduced by a macro doesn't capture the caller's variable
+macro_rules! add_twice(
+    ($a:expr, $b:expr) => ({ let tmp = $a; tmp + tmp + $b })
+)
+
+// nor does it shadow the caller's variables after the expansion
+macro_rules! declare_tmp(
+    () => ( let tmp = 100; )
+)
+
+// An identifier passed in by the caller binds in the caller's context
+macro_rules! bind(
+    ($x:ident, $val:expr) => ( let $x = $val; )
+)
+
+pub fn main() {
+    let tmp = 10;
+    assert add_twice!(1, tmp) == 12;
+
+    declare_tmp!();
+    assert tmp == 10;
+
+    bind!(y, 5);
+    assert y == 5;
+
+    // Names the macro doesn't bind itself still mean what they mean where
+    // the macro is used
+    macro_rules! get_tmp( () => ( tmp ) )
+    assert get_tmp!() == 10;
+
+    let mut total = 0;
+    for [1, 2, 3].each |tmp| {
+        total += add_twice!(*tmp, 0);
+    }
+    assert total == 12;
+}
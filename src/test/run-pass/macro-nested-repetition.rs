@@ -0,0 +1,46 @@
+// Copyright 2013 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// xfail-pretty - token trees can't pretty print
+
+// Nested repetitions, with and without separators, including separators
+// that are keywords or identifiers and bodies that can match nothing
+
+macro_rules! sum_rows(
+    ($($($x:expr),*);*) => (~[$(0 $(+ $x)*),*])
+)
+
+macro_rules! count_words(
+    ($($($w:ident)*)|*) => (0u $($(+ { let _ = stringify!($w); 1u })*)*)
+)
+
+macro_rules! pairs(
+    ($($k:ident => $($v:expr) and +),*) => (
+        ~[$((stringify!($k), ~[$($v),+])),*]
+    )
+)
+
+macro_rules! groups(
+    ($([$($x:ident)as*])*) => (~[$(~[$(stringify!($x)),*]),*])
+)
+
+pub fn main() {
+    assert sum_rows!(1, 2; 3; 4, 5, 6) == ~[3, 3, 15];
+    let empty: ~[int] = sum_rows!();
+    assert empty.is_empty();
+
+    assert count_words!() == 0u;
+    assert count_words!(a b | c | | d e f) == 6u;
+
+    let p = pairs!(a => 1 and 2, b => 3);
+    assert p == ~[("a", ~[1, 2]), ("b", ~[3])];
+
+    assert groups!([a as b] [] [c]) == ~[~["a", "b"], ~[], ~["c"]];
+}
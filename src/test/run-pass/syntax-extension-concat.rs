@@ -0,0 +1,17 @@
+// Copyright 2013 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+pub fn main() {
+    assert concat!() == "";
+    assert concat!("abc") == "abc";
+    assert concat!("x = ", 1, ", y = ", -2.5, 'c', true) ==
+        "x = 1, y = -2.5ctrue";
+    assert concat!(10u8, -3i, 7u) == "10-37";
+}
@@ -0,0 +1,77 @@
+// Copyright 2013 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+fn test(actual: ~str, expected: ~str) {
+    log(debug, copy actual);
+    log(debug, copy expected);
+    assert (actual == expected);
+}
+
+struct Point { x: int, y: int }
+
+pub fn main() {
+    // Plain strings and escaped braces
+    test(format!("hello"), ~"hello");
+    test(format!("{{}}"), ~"{}");
+    test(format!("a {{{}}} b", 1), ~"a {1} b");
+
+    // Positional arguments, given and omitted
+    test(format!("{} {} {}", 1, ~"two", 3.5), ~"1 two 3.5");
+    test(format!("{1} {0} {1}", ~"a", ~"b"), ~"b a b");
+    test(format!("{} {0} {}", 1, 2), ~"1 1 2");
+
+    // Named arguments
+    test(format!("{x}, {y}", x = 1, y = 2), ~"1, 2");
+    test(format!("{0} is {age:u}", "Bob", age = 30u), ~"Bob is 30");
+    test(format!("{a}{a}", a = 'z'), ~"zz");
+
+    // Types
+    test(format!("{:s}", "str"), ~"str");
+    test(format!("{:s}", ~"owned"), ~"owned");
+    test(format!("{:s}", @"managed"), ~"managed");
+    test(format!("{:c}", 'x'), ~"x");
+    test(format!("{:b}", true), ~"true");
+    test(format!("{:d} {:i}", -10, 7i8), ~"-10 7");
+    test(format!("{:u}", 10u16), ~"10");
+    test(format!("{:x} {:X}", 255u, 255u8), ~"ff FF");
+    test(format!("{:o} {:t}", 8u, 5u), ~"10 101");
+    test(format!("{:f}", 1.5f), ~"1.5");
+    test(format!("{:?}", Point { x: 1, y: 2 }), ~"{x: 1, y: 2}");
+
+    // Width, fill and alignment
+    test(format!("[{:5s}]", "ab"), ~"[ab   ]");
+    test(format!("[{:5d}]", 42), ~"[   42]");
+    test(format!("[{:<5d}]", 42), ~"[42   ]");
+    test(format!("[{:>5s}]", "ab"), ~"[   ab]");
+    test(format!("[{:^6s}]", "ab"), ~"[  ab  ]");
+    test(format!("[{:*^7s}]", "ab"), ~"[**ab***]");
+    test(format!("[{:05d}]", -42), ~"[-0042]");
+    test(format!("[{:2s}]", "long"), ~"[long]");
+
+    // Precision
+    test(format!("{:.3s}", "abcdef"), ~"abc");
+    test(format!("{:.4u}", 7u), ~"0007");
+    test(format!("{:.2f}", 3.14159f), ~"3.14");
+    test(format!("[{:8.2f}]", -1.5f), ~"[   -1.50]");
+
+    // Arguments are evaluated once, in order, and borrowed
+    let mut n = 0;
+    let v = ~[1, 2, 3];
+    test(format!("{1} {0}", { n += 1; n }, { n += 10; n }), ~"11 1");
+    test(format!("{:?} {:?}", v, v.len()), ~"~[1, 2, 3] 3");
+    assert v.len() == 3;
+
+    // write! into a writer
+    let s = do io::with_str_writer |w| {
+        write!(w, "{} + {} = {sum}", 1, 2, sum = 3);
+        write!(w, "!");
+    };
+    test(s, ~"1 + 2 = 3!");
+}
@@ -31,6 +31,15 @@ pub fn main() {
     assert(
         include_bin!("syntax-extension-source-utils-files/includeme.fragment")
         [1] == (42 as u8)); // '*'
+    // This file is the crate root, so crate_root makes no difference here
+    assert(
+        include_str!(crate_root,
+                     "syntax-extension-source-utils-files/includeme.fragment")
+        == include_str!("syntax-extension-source-utils-files/includeme.fragment"));
+    assert(
+        include_bin!(crate_root,
+                     "syntax-extension-source-utils-files/includeme.fragment")
+        [1] == (42 as u8));
     // The Windows tests are wrapped in an extra module for some reason
     assert(m1::m2::where_am_i().ends_with(~"m1::m2"));
 }